@@ -0,0 +1,134 @@
+use crate::{ray::Ray, vec3::Vec3};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    pub minimum: Vec3,
+    pub maximum: Vec3,
+}
+
+impl Aabb {
+    pub fn new(minimum: Vec3, maximum: Vec3) -> Aabb {
+        Aabb { minimum, maximum }
+    }
+
+    // smallest box containing all given points
+    pub fn from_points(points: &[Vec3]) -> Aabb {
+        let mut minimum = Vec3::new(f64::INFINITY, f64::INFINITY, f64::INFINITY);
+        let mut maximum = Vec3::new(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY);
+        for point in points {
+            minimum = Vec3::component_min(&minimum, point);
+            maximum = Vec3::component_max(&maximum, point);
+        }
+        Aabb { minimum, maximum }
+    }
+
+    pub fn surrounding_box(box_a: &Aabb, box_b: &Aabb) -> Aabb {
+        Aabb {
+            minimum: Vec3::component_min(&box_a.minimum, &box_b.minimum),
+            maximum: Vec3::component_max(&box_a.maximum, &box_b.maximum),
+        }
+    }
+
+    // grow flat boxes (e.g. axis aligned triangles) so the slab test never divides a zero extent
+    pub fn padded(&self, delta: f64) -> Aabb {
+        let pad = Vec3::new(delta, delta, delta);
+        Aabb {
+            minimum: self.minimum - pad,
+            maximum: self.maximum + pad,
+        }
+    }
+
+    pub fn centroid(&self) -> Vec3 {
+        0.5 * (self.minimum + self.maximum)
+    }
+
+    pub fn extent(&self) -> Vec3 {
+        self.maximum - self.minimum
+    }
+
+    pub fn surface_area(&self) -> f64 {
+        let extent = self.extent();
+        2.0 * (extent.x * extent.y + extent.y * extent.z + extent.z * extent.x)
+    }
+
+    pub fn longest_axis(&self) -> usize {
+        let extent = self.extent();
+        if extent.x > extent.y && extent.x > extent.z {
+            0
+        } else if extent.y > extent.z {
+            1
+        } else {
+            2
+        }
+    }
+
+    pub fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> bool {
+        self.hit_interval(ray, t_min, t_max).is_some()
+    }
+
+    // slab test: intersect the ray with the three pairs of planes and return the overlap of
+    // the resulting intervals inside [t_min, t_max], i.e. where the ray is inside the box
+    pub fn hit_interval(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<(f64, f64)> {
+        let mut t_min = t_min;
+        let mut t_max = t_max;
+        for axis in 0..3 {
+            let inverse_direction = 1.0 / ray.direction[axis];
+            let mut t_0 = (self.minimum[axis] - ray.origin[axis]) * inverse_direction;
+            let mut t_1 = (self.maximum[axis] - ray.origin[axis]) * inverse_direction;
+            if inverse_direction < 0.0 {
+                std::mem::swap(&mut t_0, &mut t_1);
+            }
+            // written so a NaN (origin on the slab plane of a parallel ray) keeps the interval
+            t_min = if t_0 > t_min { t_0 } else { t_min };
+            t_max = if t_1 < t_max { t_1 } else { t_max };
+            if t_max < t_min {
+                return None;
+            }
+        }
+        Some((t_min, t_max))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn ray_through_box_hits() {
+        let aabb = Aabb::new(Vec3::new(-1.0, -1.0, -1.0), Vec3::new(1.0, 1.0, 1.0));
+        let ray = Ray::new(Vec3::new(0.0, 0.0, -5.0), Vec3::new(0.0, 0.0, 1.0));
+        assert!(aabb.hit(&ray, 0.0, f64::MAX));
+    }
+    #[test]
+    fn interval_spans_the_box() {
+        let aabb = Aabb::new(Vec3::new(-1.0, -1.0, -1.0), Vec3::new(1.0, 1.0, 1.0));
+        let ray = Ray::new(Vec3::new(0.0, 0.0, -5.0), Vec3::new(0.0, 0.0, 2.0));
+        assert_eq!(aabb.hit_interval(&ray, 0.0, f64::MAX), Some((2.0, 3.0)));
+        assert_eq!(aabb.hit_interval(&ray, 2.5, f64::MAX), Some((2.5, 3.0)));
+    }
+    #[test]
+    fn ray_beside_box_misses() {
+        let aabb = Aabb::new(Vec3::new(-1.0, -1.0, -1.0), Vec3::new(1.0, 1.0, 1.0));
+        let ray = Ray::new(Vec3::new(2.0, 0.0, -5.0), Vec3::new(0.0, 0.0, 1.0));
+        assert!(!aabb.hit(&ray, 0.0, f64::MAX));
+    }
+    #[test]
+    fn ray_pointing_away_misses() {
+        let aabb = Aabb::new(Vec3::new(-1.0, -1.0, -1.0), Vec3::new(1.0, 1.0, 1.0));
+        let ray = Ray::new(Vec3::new(0.0, 0.0, -5.0), Vec3::new(0.0, 0.0, -1.0));
+        assert!(!aabb.hit(&ray, 0.0, f64::MAX));
+    }
+    #[test]
+    fn surrounding_box_contains_both() {
+        let box_a = Aabb::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 1.0, 1.0));
+        let box_b = Aabb::new(Vec3::new(-1.0, 2.0, 0.5), Vec3::new(0.5, 3.0, 0.7));
+        assert_eq!(
+            Aabb::surrounding_box(&box_a, &box_b),
+            Aabb::new(Vec3::new(-1.0, 0.0, 0.0), Vec3::new(1.0, 3.0, 1.0))
+        );
+    }
+    #[test]
+    fn surface_area_unit_cube() {
+        let aabb = Aabb::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 1.0, 1.0));
+        assert_eq!(aabb.surface_area(), 6.0);
+    }
+}
@@ -0,0 +1,310 @@
+use crate::{aabb::Aabb, mat4::Mat4, transform::Transform, vec3::Vec3};
+
+const BOUNDING_BOX_STEPS: usize = 64; // times at which the object is boxed along its motion
+
+// a transform changing from one key transform at start_time to another at end_time and
+// holding still outside that interval. Both keys are split into translation, rotation and
+// the remaining scale, which are interpolated separately (the rotation along the shortest
+// arc), so spinning objects keep their shape in between
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AnimatedTransform {
+    start: Transform,
+    end: Transform,
+    start_time: f64,
+    end_time: f64,
+    start_pose: Pose,
+    end_pose: Pose,
+}
+
+// an affine transformation as translation * rotation * stretch, where stretch is the
+// symmetric rest (scaling and shearing)
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Pose {
+    translation: Vec3,
+    rotation: [f64; 4], // unit quaternion w, x, y, z
+    stretch: Mat4,
+}
+
+impl AnimatedTransform {
+    pub fn new(
+        start: Transform,
+        end: Transform,
+        start_time: f64,
+        end_time: f64,
+    ) -> AnimatedTransform {
+        AnimatedTransform {
+            start,
+            end,
+            start_time,
+            end_time,
+            start_pose: Pose::new(start.matrix()),
+            end_pose: Pose::new(end.matrix()),
+        }
+    }
+
+    pub fn at(&self, time: f64) -> Transform {
+        let duration = self.end_time - self.start_time;
+        let progress = if duration > 0.0 {
+            (time - self.start_time) / duration
+        } else if time < self.start_time {
+            0.0
+        } else {
+            1.0
+        };
+        if progress <= 0.0 {
+            return self.start;
+        }
+        if progress >= 1.0 {
+            return self.end;
+        }
+
+        let (start, end) = (&self.start_pose, &self.end_pose);
+        let translation = start.translation + (end.translation - start.translation) * progress;
+        let rotation = slerp(&start.rotation, &end.rotation, progress);
+        let mut stretch = start.stretch;
+        for (column, end_column) in stretch.columns.iter_mut().zip(end.stretch.columns) {
+            for (value, end_value) in column.iter_mut().zip(end_column) {
+                *value += (end_value - *value) * progress;
+            }
+        }
+        let matrix = Mat4::translation(translation) * rotation_matrix(&rotation) * stretch;
+        // stretches of opposite handedness pass through a singular one
+        Transform::new(matrix).unwrap_or(if progress < 0.5 { self.start } else { self.end })
+    }
+
+    // boxes around the object at densely sampled times, padded by how far rotating corners
+    // can bulge out between two of them
+    pub fn bounding_box(&self, object_box: &Aabb) -> Aabb {
+        let mut bounding_box = self.start.bounding_box(object_box);
+        let mut reach: f64 = 0.0; // distance of the farthest corner from the rotation center
+        for step in 0..=BOUNDING_BOX_STEPS {
+            let progress = step as f64 / BOUNDING_BOX_STEPS as f64;
+            let transform = self.at(self.start_time + (self.end_time - self.start_time) * progress);
+            let step_box = transform.bounding_box(object_box);
+            bounding_box = Aabb::surrounding_box(&bounding_box, &step_box);
+
+            let center = transform.point(&Vec3::new(0.0, 0.0, 0.0));
+            let farthest =
+                Vec3::component_max(&(step_box.maximum - center), &(center - step_box.minimum));
+            reach = reach.max(farthest.length());
+        }
+        let cos_half_angle = dot(&self.start_pose.rotation, &self.end_pose.rotation)
+            .abs()
+            .min(1.0);
+        let angle = 2.0 * cos_half_angle.acos();
+        let bulge = reach * (1.0 - (angle / (2.0 * BOUNDING_BOX_STEPS as f64)).cos());
+        bounding_box.padded(bulge)
+    }
+}
+
+impl Pose {
+    fn new(matrix: &Mat4) -> Pose {
+        let translation = Vec3::new(
+            matrix.columns[3][0],
+            matrix.columns[3][1],
+            matrix.columns[3][2],
+        );
+        let mut linear = *matrix;
+        linear.columns[3] = [0.0, 0.0, 0.0, 1.0];
+
+        // polar decomposition: averaging with the inverse transpose converges to the
+        // rotation closest to the matrix
+        let mut rotation = linear;
+        for _ in 0..100 {
+            let Some(inverse) = rotation.inverse() else {
+                break;
+            };
+            let inverse_transpose = inverse.transpose();
+            let mut change: f64 = 0.0;
+            for column in 0..3 {
+                for row in 0..3 {
+                    let value = 0.5
+                        * (rotation.columns[column][row] + inverse_transpose.columns[column][row]);
+                    change = change.max((value - rotation.columns[column][row]).abs());
+                    rotation.columns[column][row] = value;
+                }
+            }
+            if change < 1e-12 {
+                break;
+            }
+        }
+        // mirroring transforms are a proper rotation and a negative stretch
+        if determinant(&rotation) < 0.0 {
+            rotation = rotation * Mat4::scaling(Vec3::new(-1.0, -1.0, -1.0));
+        }
+        Pose {
+            translation,
+            rotation: quaternion(&rotation),
+            stretch: rotation.transpose() * linear,
+        }
+    }
+}
+
+fn determinant(matrix: &Mat4) -> f64 {
+    let column = |i: usize| {
+        Vec3::new(
+            matrix.columns[i][0],
+            matrix.columns[i][1],
+            matrix.columns[i][2],
+        )
+    };
+    Vec3::dot(&column(0), &Vec3::cross(&column(1), &column(2)))
+}
+
+fn dot(a: &[f64; 4], b: &[f64; 4]) -> f64 {
+    a.iter().zip(b).map(|(a, b)| a * b).sum()
+}
+
+// unit quaternion of a rotation matrix, starting from its largest component for precision
+fn quaternion(rotation: &Mat4) -> [f64; 4] {
+    let m = |row: usize, column: usize| rotation.columns[column][row];
+    let trace = m(0, 0) + m(1, 1) + m(2, 2);
+    if trace > 0.0 {
+        let s = 2.0 * (trace + 1.0).sqrt();
+        [
+            s / 4.0,
+            (m(2, 1) - m(1, 2)) / s,
+            (m(0, 2) - m(2, 0)) / s,
+            (m(1, 0) - m(0, 1)) / s,
+        ]
+    } else if m(0, 0) > m(1, 1) && m(0, 0) > m(2, 2) {
+        let s = 2.0 * (1.0 + m(0, 0) - m(1, 1) - m(2, 2)).sqrt();
+        [
+            (m(2, 1) - m(1, 2)) / s,
+            s / 4.0,
+            (m(0, 1) + m(1, 0)) / s,
+            (m(0, 2) + m(2, 0)) / s,
+        ]
+    } else if m(1, 1) > m(2, 2) {
+        let s = 2.0 * (1.0 + m(1, 1) - m(0, 0) - m(2, 2)).sqrt();
+        [
+            (m(0, 2) - m(2, 0)) / s,
+            (m(0, 1) + m(1, 0)) / s,
+            s / 4.0,
+            (m(1, 2) + m(2, 1)) / s,
+        ]
+    } else {
+        let s = 2.0 * (1.0 + m(2, 2) - m(0, 0) - m(1, 1)).sqrt();
+        [
+            (m(1, 0) - m(0, 1)) / s,
+            (m(0, 2) + m(2, 0)) / s,
+            (m(1, 2) + m(2, 1)) / s,
+            s / 4.0,
+        ]
+    }
+}
+
+fn rotation_matrix(quaternion: &[f64; 4]) -> Mat4 {
+    let [w, x, y, z] = *quaternion;
+    Mat4::from_columns([
+        [
+            1.0 - 2.0 * (y * y + z * z),
+            2.0 * (x * y + w * z),
+            2.0 * (x * z - w * y),
+            0.0,
+        ],
+        [
+            2.0 * (x * y - w * z),
+            1.0 - 2.0 * (x * x + z * z),
+            2.0 * (y * z + w * x),
+            0.0,
+        ],
+        [
+            2.0 * (x * z + w * y),
+            2.0 * (y * z - w * x),
+            1.0 - 2.0 * (x * x + y * y),
+            0.0,
+        ],
+        [0.0, 0.0, 0.0, 1.0],
+    ])
+}
+
+// spherical interpolation along the shorter of the two arcs between the rotations
+fn slerp(start: &[f64; 4], end: &[f64; 4], progress: f64) -> [f64; 4] {
+    let mut cos_angle = dot(start, end);
+    let mut end = *end;
+    if cos_angle < 0.0 {
+        end = end.map(|value| -value);
+        cos_angle = -cos_angle;
+    }
+    let (start_weight, end_weight) = if cos_angle > 0.9995 {
+        (1.0 - progress, progress) // nearly parallel, normalized below
+    } else {
+        let angle = cos_angle.acos();
+        (
+            ((1.0 - progress) * angle).sin() / angle.sin(),
+            (progress * angle).sin() / angle.sin(),
+        )
+    };
+    let mut result = [0.0; 4];
+    for (i, value) in result.iter_mut().enumerate() {
+        *value = start_weight * start[i] + end_weight * end[i];
+    }
+    let length = dot(&result, &result).sqrt();
+    result.map(|value| value / length)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: Vec3, b: Vec3) {
+        assert!((a - b).length() < 1e-9, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn keys_are_reproduced() {
+        let start = Transform::default()
+            .scale(Vec3::new(1.0, 2.0, 3.0))
+            .rotate(Vec3::new(1.0, 1.0, 0.0), 30.0)
+            .translate(Vec3::new(1.0, 0.0, 0.0));
+        let end = Transform::default()
+            .scale(Vec3::new(-1.0, 1.0, 1.0))
+            .rotate(Vec3::new(0.0, 0.0, 1.0), 120.0);
+        let animated = AnimatedTransform::new(start, end, 0.0, 1.0);
+        for (pose, transform) in [(animated.start_pose, start), (animated.end_pose, end)] {
+            let matrix = Mat4::translation(pose.translation)
+                * rotation_matrix(&pose.rotation)
+                * pose.stretch;
+            let point = Vec3::new(0.3, -2.0, 1.5);
+            assert_close(matrix.transform_point(&point), transform.point(&point));
+        }
+        assert_eq!(animated.at(-1.0), start);
+        assert_eq!(animated.at(2.0), end);
+    }
+
+    #[test]
+    fn rotation_keeps_the_shape_halfway() {
+        // a quarter turn around y: halfway the point is turned by 45 degrees, not pulled in
+        // towards the origin as with interpolated matrices
+        let start = Transform::default();
+        let end = Transform::default()
+            .rotate(Vec3::new(0.0, 1.0, 0.0), 90.0)
+            .translate(Vec3::new(0.0, 2.0, 0.0));
+        let animated = AnimatedTransform::new(start, end, 0.0, 2.0);
+        let halfway = animated.at(1.0).point(&Vec3::new(1.0, 0.0, 0.0));
+        let diagonal = 0.5_f64.sqrt();
+        assert_close(halfway, Vec3::new(diagonal, 1.0, -diagonal));
+    }
+
+    #[test]
+    fn bounding_box_covers_the_motion() {
+        let animated = AnimatedTransform::new(
+            Transform::default(),
+            Transform::default().rotate(Vec3::new(0.0, 0.0, 1.0), 180.0),
+            0.0,
+            1.0,
+        );
+        let object_box = Aabb::new(Vec3::new(1.0, -0.1, -0.1), Vec3::new(2.0, 0.1, 0.1));
+        let bounding_box = animated.bounding_box(&object_box);
+        for step in 0..=100 {
+            let transform = animated.at(step as f64 / 100.0);
+            let corner = transform.point(&Vec3::new(2.0, 0.1, 0.1));
+            for axis in 0..3 {
+                assert!(corner[axis] >= bounding_box.minimum[axis]);
+                assert!(corner[axis] <= bounding_box.maximum[axis]);
+            }
+        }
+        assert!(bounding_box.maximum.y > 2.0);
+    }
+}
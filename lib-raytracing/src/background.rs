@@ -0,0 +1,17 @@
+use crate::{ray::Ray, vec3::Vec3};
+
+// radiance arriving along rays that leave the scene without hitting anything
+pub trait Background: Sync {
+    fn color(&self, ray: &Ray) -> Vec3;
+
+    // backgrounds with bright spots (e.g. the sun in an HDR map) can pick directions
+    // proportional to their radiance, None means no importance sampling is available
+    fn sample_direction(&self) -> Option<Vec3> {
+        None
+    }
+
+    // solid angle density of sample_direction for the given direction
+    fn pdf(&self, _direction: &Vec3) -> f64 {
+        0.0
+    }
+}
@@ -0,0 +1,195 @@
+use std::path::Path;
+
+use image::{
+    error::{ParameterError, ParameterErrorKind},
+    ImageError, ImageResult,
+};
+
+use crate::{
+    background::Background,
+    distribution::Distribution2D,
+    ray::Ray,
+    util::{random, PI},
+    vec3::Vec3,
+};
+
+// equirectangular (latitude/longitude) image wrapped around the scene
+pub struct EnvironmentMap {
+    width: usize,
+    height: usize,
+    pixels: Vec<Vec3>, // row major, the first row looks straight up
+    distribution: Distribution2D,
+}
+
+impl EnvironmentMap {
+    pub fn new(width: usize, height: usize, pixels: Vec<Vec3>) -> EnvironmentMap {
+        assert!(
+            width > 0 && height > 0,
+            "environment map needs at least one pixel"
+        );
+        assert_eq!(
+            pixels.len(),
+            width * height,
+            "environment map needs width * height pixels"
+        );
+        // weight every texel by its luminance and by sin(theta), since rows near the poles
+        // cover less solid angle than rows at the horizon
+        let weights: Vec<f64> = pixels
+            .iter()
+            .enumerate()
+            .map(|(i, pixel)| {
+                let theta = PI * ((i / width) as f64 + 0.5) / height as f64;
+                pixel.luminance().max(0.0) * theta.sin()
+            })
+            .collect();
+        let distribution = Distribution2D::new(&weights, width, height);
+        EnvironmentMap {
+            width,
+            height,
+            pixels,
+            distribution,
+        }
+    }
+
+    // loads a Radiance .hdr (or any other format the image crate understands) in linear rgb
+    pub fn load<P: AsRef<Path>>(path: P) -> ImageResult<EnvironmentMap> {
+        let image = image::open(path)?.into_rgb32f();
+        let (width, height) = image.dimensions();
+        if width == 0 || height == 0 {
+            return Err(ImageError::Parameter(ParameterError::from_kind(
+                ParameterErrorKind::Generic(format!(
+                    "an environment map needs at least one pixel, the image has {}x{}",
+                    width, height
+                )),
+            )));
+        }
+        let pixels = image
+            .pixels()
+            .map(|pixel| Vec3::new(pixel[0] as f64, pixel[1] as f64, pixel[2] as f64))
+            .collect();
+        Ok(EnvironmentMap::new(width as usize, height as usize, pixels))
+    }
+
+    // u runs around the y axis starting at -x, v runs from straight up (0) to straight down (1)
+    pub fn direction_to_uv(direction: &Vec3) -> (f64, f64) {
+        let unit_direction = Vec3::unit_vector(direction);
+        let phi = f64::atan2(-unit_direction.z, unit_direction.x) + PI;
+        let theta = unit_direction.y.clamp(-1.0, 1.0).acos();
+        (phi / (2.0 * PI), theta / PI)
+    }
+
+    pub fn uv_to_direction(u: f64, v: f64) -> Vec3 {
+        let phi = u * 2.0 * PI - PI;
+        let theta = v * PI;
+        Vec3::new(
+            theta.sin() * phi.cos(),
+            theta.cos(),
+            -theta.sin() * phi.sin(),
+        )
+    }
+
+    fn texel(&self, x: usize, y: usize) -> Vec3 {
+        self.pixels[y * self.width + x]
+    }
+
+    // bilinear lookup, wrapping horizontally and clamping at the poles
+    pub fn lookup(&self, u: f64, v: f64) -> Vec3 {
+        let x = u * self.width as f64 - 0.5;
+        let y = (v * self.height as f64 - 0.5).clamp(0.0, (self.height - 1) as f64);
+        let x_floor = x.floor();
+        let y_floor = y.floor();
+        let tx = x - x_floor;
+        let ty = y - y_floor;
+
+        let x0 = (x_floor as i64).rem_euclid(self.width as i64) as usize;
+        let x1 = (x0 + 1) % self.width;
+        let y0 = y_floor as usize;
+        let y1 = (y0 + 1).min(self.height - 1);
+
+        (1.0 - ty) * ((1.0 - tx) * self.texel(x0, y0) + tx * self.texel(x1, y0))
+            + ty * ((1.0 - tx) * self.texel(x0, y1) + tx * self.texel(x1, y1))
+    }
+}
+
+impl Background for EnvironmentMap {
+    fn color(&self, ray: &Ray) -> Vec3 {
+        let (u, v) = Self::direction_to_uv(&ray.direction);
+        self.lookup(u, v)
+    }
+
+    fn sample_direction(&self) -> Option<Vec3> {
+        let (u, v, _) = self.distribution.sample(random(), random());
+        Some(Self::uv_to_direction(u, v))
+    }
+
+    fn pdf(&self, direction: &Vec3) -> f64 {
+        let (u, v) = Self::direction_to_uv(direction);
+        let sin_theta = (v * PI).sin();
+        if sin_theta <= 0.0 {
+            return 0.0;
+        }
+        // change of variables from the unit square to the sphere: dω = 2π² sin(theta) du dv
+        self.distribution.pdf(u, v) / (2.0 * PI * PI * sin_theta)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn uv_round_trip() {
+        for _ in 0..100 {
+            let direction = Vec3::random_unit_vector();
+            let (u, v) = EnvironmentMap::direction_to_uv(&direction);
+            let round_trip = EnvironmentMap::uv_to_direction(u, v);
+            assert!((round_trip - direction).length() < 1e-9);
+        }
+    }
+    #[test]
+    fn up_samples_first_row() {
+        let sky = Vec3::new(0.2, 0.4, 1.0);
+        let ground = Vec3::new(0.3, 0.2, 0.1);
+        let map = EnvironmentMap::new(4, 2, [vec![sky; 4], vec![ground; 4]].concat());
+        let up = Ray::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0));
+        let down = Ray::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, -1.0, 0.0));
+        assert_eq!(map.color(&up), sky);
+        assert_eq!(map.color(&down), ground);
+    }
+    #[test]
+    fn samples_find_the_sun() {
+        let mut pixels = vec![Vec3::new(0.1, 0.1, 0.1); 32 * 16];
+        pixels[5 * 32 + 20] = Vec3::new(10000.0, 10000.0, 10000.0);
+        let map = EnvironmentMap::new(32, 16, pixels);
+        let sun_direction = EnvironmentMap::uv_to_direction(20.5 / 32.0, 5.5 / 16.0);
+        let near_sun = (0..1000)
+            .filter(|_| {
+                let direction = map.sample_direction().unwrap();
+                Vec3::dot(&direction, &sun_direction) > 0.95
+            })
+            .count();
+        assert!(near_sun > 900);
+    }
+    #[test]
+    fn pdf_integrates_to_one_over_sphere() {
+        let pixels = (0..16 * 8).map(|_| Vec3::random()).collect();
+        let map = EnvironmentMap::new(16, 8, pixels);
+        let samples = 200000;
+        let integral: f64 = (0..samples)
+            .map(|_| 4.0 * PI * map.pdf(&Vec3::random_unit_vector()))
+            .sum::<f64>()
+            / samples as f64;
+        assert!((integral - 1.0).abs() < 0.02);
+    }
+    #[test]
+    fn load_hdr_file() {
+        let path = std::env::temp_dir().join("lib-raytracing-environment-map-test.hdr");
+        let pixels = [image::Rgb([0.5f32, 2.0, 8.0]); 8];
+        image::codecs::hdr::HdrEncoder::new(std::fs::File::create(&path).unwrap())
+            .encode(&pixels, 4, 2)
+            .unwrap();
+        let map = EnvironmentMap::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let ray = Ray::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        assert_eq!(map.color(&ray), Vec3::new(0.5, 2.0, 8.0));
+    }
+}
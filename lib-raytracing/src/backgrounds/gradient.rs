@@ -0,0 +1,25 @@
+use crate::{background::Background, ray::Ray, vec3::Vec3};
+
+// vertical blend between two colors, looking straight down gives bottom and straight up gives top
+pub struct Gradient {
+    pub bottom: Vec3,
+    pub top: Vec3,
+}
+
+impl Default for Gradient {
+    // the classic white to light blue sky
+    fn default() -> Self {
+        Gradient {
+            bottom: Vec3::new(1.0, 1.0, 1.0),
+            top: Vec3::new(0.5, 0.7, 1.0),
+        }
+    }
+}
+
+impl Background for Gradient {
+    fn color(&self, ray: &Ray) -> Vec3 {
+        let unit_direction = Vec3::unit_vector(&ray.direction);
+        let t = 0.5 * (unit_direction.y + 1.0);
+        (1.0 - t) * self.bottom + t * self.top
+    }
+}
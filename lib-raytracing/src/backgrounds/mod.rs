@@ -0,0 +1,3 @@
+pub mod environment_map;
+pub mod gradient;
+pub mod solid_color;
@@ -0,0 +1,11 @@
+use crate::{background::Background, ray::Ray, vec3::Vec3};
+
+pub struct SolidColor {
+    pub color: Vec3,
+}
+
+impl Background for SolidColor {
+    fn color(&self, _ray: &Ray) -> Vec3 {
+        self.color
+    }
+}
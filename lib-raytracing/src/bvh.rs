@@ -0,0 +1,410 @@
+use crate::{
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable, HittableList},
+    ray::Ray,
+    vec3::Vec3,
+};
+
+const SAH_BUCKETS: usize = 12;
+const MAX_LEAF_SIZE: usize = 4;
+const TRAVERSAL_COST: f64 = 0.125; // cost of visiting a node relative to one object intersection
+
+enum BvhNode {
+    Leaf {
+        bounding_box: Aabb,
+        first: usize,
+        count: usize,
+    },
+    // the first child always directly follows its parent in the node list
+    Interior {
+        bounding_box: Aabb,
+        second_child: usize,
+        axis: usize,
+    },
+}
+
+struct BuildEntry {
+    bounding_box: Aabb,
+    centroid: Vec3,
+    index: usize,
+}
+
+// node hierarchy over primitives addressed by index, shared by Bvh and the meshes that
+// keep their own primitives
+pub(crate) struct BvhTree {
+    nodes: Vec<BvhNode>,
+}
+
+pub struct Bvh {
+    tree: BvhTree,
+    objects: Vec<Box<dyn Hittable>>,
+    unbounded: Vec<Box<dyn Hittable>>, // objects without a bounding box are tested linearly
+}
+
+impl Bvh {
+    pub fn new(list: HittableList) -> Bvh {
+        let mut bounded = vec![];
+        let mut bounding_boxes = vec![];
+        let mut unbounded = vec![];
+        for object in list.objects {
+            match object.bounding_box() {
+                Some(bounding_box) => {
+                    bounding_boxes.push(bounding_box);
+                    bounded.push(Some(object));
+                }
+                None => unbounded.push(object),
+            }
+        }
+
+        let (tree, order) = BvhTree::build(&bounding_boxes);
+        Bvh {
+            tree,
+            objects: order
+                .into_iter()
+                .map(|index| bounded[index].take().unwrap())
+                .collect(),
+            unbounded,
+        }
+    }
+}
+
+impl BvhTree {
+    // returns the tree and the order in which the primitives have to be stored, leaves
+    // reference ranges of that order
+    pub(crate) fn build(bounding_boxes: &[Aabb]) -> (BvhTree, Vec<usize>) {
+        let mut entries: Vec<BuildEntry> = bounding_boxes
+            .iter()
+            .enumerate()
+            .map(|(index, bounding_box)| BuildEntry {
+                bounding_box: *bounding_box,
+                centroid: bounding_box.centroid(),
+                index,
+            })
+            .collect();
+
+        let mut nodes = Vec::with_capacity(2 * entries.len());
+        if !entries.is_empty() {
+            Self::build_node(&mut nodes, &mut entries, 0);
+        }
+        (
+            BvhTree { nodes },
+            entries.into_iter().map(|entry| entry.index).collect(),
+        )
+    }
+
+    fn build_node(nodes: &mut Vec<BvhNode>, entries: &mut [BuildEntry], offset: usize) {
+        let bounding_box = entries
+            .iter()
+            .skip(1)
+            .fold(entries[0].bounding_box, |acc, entry| {
+                Aabb::surrounding_box(&acc, &entry.bounding_box)
+            });
+        let node_index = nodes.len();
+        nodes.push(BvhNode::Leaf {
+            bounding_box,
+            first: offset,
+            count: entries.len(),
+        });
+        if entries.len() == 1 {
+            return;
+        }
+
+        let centroid_bounds = Aabb::from_points(
+            &entries
+                .iter()
+                .map(|entry| entry.centroid)
+                .collect::<Vec<Vec3>>(),
+        );
+
+        let (axis, mid) = match Self::sah_split(entries, &bounding_box, &centroid_bounds) {
+            Some((axis, bucket)) => {
+                let mid = partition(entries, |entry| {
+                    bucket_index(entry, &centroid_bounds, axis) <= bucket
+                });
+                (axis, mid)
+            }
+            None if entries.len() <= MAX_LEAF_SIZE => return,
+            None => (centroid_bounds.longest_axis(), 0),
+        };
+
+        // SAH found nothing useful (e.g. all centroids coincide): fall back to a median split
+        let mid = if mid == 0 || mid == entries.len() {
+            let mid = entries.len() / 2;
+            entries
+                .select_nth_unstable_by(mid, |a, b| a.centroid[axis].total_cmp(&b.centroid[axis]));
+            mid
+        } else {
+            mid
+        };
+
+        let (left, right) = entries.split_at_mut(mid);
+        Self::build_node(nodes, left, offset);
+        let second_child = nodes.len();
+        Self::build_node(nodes, right, offset + mid);
+        nodes[node_index] = BvhNode::Interior {
+            bounding_box,
+            second_child,
+            axis,
+        };
+    }
+
+    // bin the centroids along every axis and return the axis and last bucket of the left side
+    // for the split with the lowest surface area heuristic cost, or None if a leaf is cheaper
+    fn sah_split(
+        entries: &[BuildEntry],
+        bounding_box: &Aabb,
+        centroid_bounds: &Aabb,
+    ) -> Option<(usize, usize)> {
+        let leaf_cost = entries.len() as f64;
+        let mut best: Option<(usize, usize)> = None;
+        let mut best_cost = leaf_cost;
+
+        for axis in 0..3 {
+            if centroid_bounds.extent()[axis] <= 0.0 {
+                continue;
+            }
+            let mut counts = [0usize; SAH_BUCKETS];
+            let mut boxes: [Option<Aabb>; SAH_BUCKETS] = [None; SAH_BUCKETS];
+            for entry in entries {
+                let bucket = bucket_index(entry, centroid_bounds, axis);
+                counts[bucket] += 1;
+                boxes[bucket] = Some(union(boxes[bucket], &entry.bounding_box));
+            }
+
+            for split in 0..SAH_BUCKETS - 1 {
+                let (left_count, left_box) = sweep(&counts[..=split], &boxes[..=split]);
+                let (right_count, right_box) = sweep(&counts[split + 1..], &boxes[split + 1..]);
+                let (Some(left_box), Some(right_box)) = (left_box, right_box) else {
+                    continue;
+                };
+                let cost = TRAVERSAL_COST
+                    + (left_count as f64 * left_box.surface_area()
+                        + right_count as f64 * right_box.surface_area())
+                        / bounding_box.surface_area();
+                if cost < best_cost {
+                    best_cost = cost;
+                    best = Some((axis, split));
+                }
+            }
+        }
+        best
+    }
+}
+
+fn bucket_index(entry: &BuildEntry, centroid_bounds: &Aabb, axis: usize) -> usize {
+    let relative =
+        (entry.centroid[axis] - centroid_bounds.minimum[axis]) / centroid_bounds.extent()[axis];
+    ((relative * SAH_BUCKETS as f64) as usize).min(SAH_BUCKETS - 1)
+}
+
+fn union(bounding_box: Option<Aabb>, other: &Aabb) -> Aabb {
+    match bounding_box {
+        Some(bounding_box) => Aabb::surrounding_box(&bounding_box, other),
+        None => *other,
+    }
+}
+
+fn sweep(counts: &[usize], boxes: &[Option<Aabb>]) -> (usize, Option<Aabb>) {
+    let count = counts.iter().sum();
+    let bounding_box = boxes
+        .iter()
+        .flatten()
+        .fold(None, |acc, bounding_box| Some(union(acc, bounding_box)));
+    (count, bounding_box)
+}
+
+// move all entries matching the predicate to the front, returns the number of matches
+fn partition(entries: &mut [BuildEntry], predicate: impl Fn(&BuildEntry) -> bool) -> usize {
+    let mut mid = 0;
+    for i in 0..entries.len() {
+        if predicate(&entries[i]) {
+            entries.swap(i, mid);
+            mid += 1;
+        }
+    }
+    mid
+}
+
+impl BvhTree {
+    // walks all nodes the ray passes through, hit_primitive is called with the primitive
+    // index and the current closest distance and returns the hit on that primitive if any
+    pub(crate) fn hit<'a>(
+        &self,
+        ray: &Ray,
+        t_min: f64,
+        t_max: f64,
+        mut hit_primitive: impl FnMut(usize, f64) -> Option<HitRecord<'a>>,
+    ) -> Option<HitRecord<'a>> {
+        let mut hit_record = None;
+        let mut closest_so_far = t_max;
+        if self.nodes.is_empty() {
+            return hit_record;
+        }
+
+        let mut stack = Vec::with_capacity(64);
+        stack.push(0);
+        while let Some(index) = stack.pop() {
+            match &self.nodes[index] {
+                BvhNode::Leaf {
+                    bounding_box,
+                    first,
+                    count,
+                } => {
+                    if !bounding_box.hit(ray, t_min, closest_so_far) {
+                        continue;
+                    }
+                    for primitive in *first..*first + *count {
+                        if let Some(record) = hit_primitive(primitive, closest_so_far) {
+                            closest_so_far = record.distance;
+                            hit_record = Some(record)
+                        }
+                    }
+                }
+                BvhNode::Interior {
+                    bounding_box,
+                    second_child,
+                    axis,
+                } => {
+                    if !bounding_box.hit(ray, t_min, closest_so_far) {
+                        continue;
+                    }
+                    // visit the child on the near side first so closest_so_far shrinks early
+                    if ray.direction[*axis] < 0.0 {
+                        stack.push(index + 1);
+                        stack.push(*second_child);
+                    } else {
+                        stack.push(*second_child);
+                        stack.push(index + 1);
+                    }
+                }
+            }
+        }
+        hit_record
+    }
+
+    pub(crate) fn bounding_box(&self) -> Option<Aabb> {
+        match self.nodes.first()? {
+            BvhNode::Leaf { bounding_box, .. } | BvhNode::Interior { bounding_box, .. } => {
+                Some(*bounding_box)
+            }
+        }
+    }
+}
+
+impl From<HittableList> for Bvh {
+    fn from(list: HittableList) -> Self {
+        Bvh::new(list)
+    }
+}
+
+impl Hittable for Bvh {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
+        let mut hit_record = None;
+        let mut closest_so_far = t_max;
+        for obj in &self.unbounded {
+            if let Some(record) = obj.hit(ray, t_min, closest_so_far) {
+                closest_so_far = record.distance;
+                hit_record = Some(record)
+            }
+        }
+        // anything the tree finds is closer than the unbounded hit
+        self.tree
+            .hit(ray, t_min, closest_so_far, |index, closest_so_far| {
+                self.objects[index].hit(ray, t_min, closest_so_far)
+            })
+            .or(hit_record)
+    }
+
+    fn transmittance(&self, ray: &Ray, t_min: f64, t_max: f64) -> f64 {
+        let mut transmittance = 1.0;
+        for obj in &self.unbounded {
+            transmittance *= obj.transmittance(ray, t_min, t_max);
+        }
+        // never report a hit so every leaf along the whole segment is visited
+        self.tree.hit(ray, t_min, t_max, |index, _| {
+            if transmittance > 0.0 {
+                transmittance *= self.objects[index].transmittance(ray, t_min, t_max);
+            }
+            None
+        });
+        transmittance
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        if !self.unbounded.is_empty() {
+            return None;
+        }
+        self.tree.bounding_box()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{materials::lambertian::Lambertian, objects::sphere::Sphere, util};
+
+    fn spheres(specs: &[(Vec3, f64)]) -> HittableList {
+        let mut list: HittableList = Default::default();
+        for (center, radius) in specs {
+            list.add(Sphere {
+                center: *center,
+                radius: *radius,
+                material: Box::new(Lambertian::new(Vec3::random())),
+            });
+        }
+        list
+    }
+
+    fn random_specs() -> Vec<(Vec3, f64)> {
+        (0..200)
+            .map(|_| {
+                (
+                    Vec3::random_range(-10.0, 10.0),
+                    util::random_range(0.1, 1.0),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn matches_linear_search() {
+        let specs = random_specs();
+        let list = spheres(&specs);
+        let bvh = Bvh::new(spheres(&specs));
+
+        for _ in 0..1000 {
+            let ray = Ray::new(Vec3::random_range(-15.0, 15.0), Vec3::random_unit_vector());
+            let expected = list.hit(&ray, 0.001, util::INFTY).map(|r| r.distance);
+            let actual = bvh.hit(&ray, 0.001, util::INFTY).map(|r| r.distance);
+            assert_eq!(expected, actual);
+        }
+    }
+
+    #[test]
+    fn bounding_box_matches_list() {
+        let list = spheres(&random_specs());
+        let expected = list.bounding_box();
+        let bvh = Bvh::new(list);
+        assert_eq!(bvh.bounding_box(), expected);
+    }
+
+    #[test]
+    fn identical_objects_are_split() {
+        let specs = vec![(Vec3::new(1.0, 2.0, 3.0), 0.5); 50];
+        let list = spheres(&specs);
+        let bvh = Bvh::new(spheres(&specs));
+        let ray = Ray::new(Vec3::new(1.0, 2.0, -10.0), Vec3::new(0.0, 0.0, 1.0));
+        assert_eq!(
+            bvh.hit(&ray, 0.001, util::INFTY).map(|r| r.distance),
+            list.hit(&ray, 0.001, util::INFTY).map(|r| r.distance)
+        );
+    }
+
+    #[test]
+    fn empty_bvh_never_hits() {
+        let bvh = Bvh::new(Default::default());
+        let ray = Ray::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 1.0));
+        assert!(bvh.hit(&ray, 0.001, util::INFTY).is_none());
+        assert!(bvh.bounding_box().is_none());
+    }
+}
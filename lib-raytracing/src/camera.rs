@@ -1,4 +1,4 @@
-use crate::{ray::Ray, vec3::Vec3};
+use crate::{ray::Ray, util, vec3::Vec3};
 
 pub struct Camera {
     origin: Vec3,
@@ -7,8 +7,9 @@ pub struct Camera {
     vertical: Vec3,
     view_plane_vector_one: Vec3,
     view_plane_vector_2: Vec3,
-    view_direction: Vec3,
     lens_radius: f64,
+    shutter_open: f64,  // rays are cast at random times between opening
+    shutter_close: f64, // and closing the shutter, which blurs moving objects
 }
 
 impl Default for Camera {
@@ -68,20 +69,28 @@ impl Camera {
             vertical: focus_dist * viewport_height * view_plane_vector_2,
             view_plane_vector_one: view_plane_vector_1,
             view_plane_vector_2,
-            view_direction,
             lens_radius: aperture / 2.0,
+            shutter_open: 0.0,
+            shutter_close: 0.0,
         }
     }
 
+    pub fn with_shutter(mut self, open: f64, close: f64) -> Camera {
+        self.shutter_open = open;
+        self.shutter_close = close;
+        self
+    }
+
     pub fn shoot_ray(&self, viewport_x: f64, viewport_y: f64) -> Ray {
         let random_xy_plane_offset = self.lens_radius * Vec3::random_in_unit_disk();
         let offset_vector = self.view_plane_vector_one * random_xy_plane_offset.x
             + self.view_plane_vector_2 * random_xy_plane_offset.y;
         let viewport_target =
             self.lower_left_corner + viewport_x * self.horizontal + viewport_y * self.vertical;
-        Ray::new(
+        Ray::with_time(
             self.origin + offset_vector,                   // ray origin
             viewport_target - self.origin - offset_vector, // ray direction
+            util::random_range(self.shutter_open, self.shutter_close),
         )
     }
 }
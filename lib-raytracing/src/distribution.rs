@@ -0,0 +1,157 @@
+// piecewise constant distributions used to importance sample tabulated functions like
+// the luminance of an environment map
+
+pub struct Distribution1D {
+    function: Vec<f64>,
+    cdf: Vec<f64>, // function.len() + 1 entries, running from 0 to 1
+    integral: f64, // integral of the function over [0, 1]
+}
+
+impl Distribution1D {
+    pub fn new(function: &[f64]) -> Distribution1D {
+        let count = function.len();
+        let mut cdf = vec![0.0; count + 1];
+        for i in 0..count {
+            cdf[i + 1] = cdf[i] + function[i].abs() / count as f64;
+        }
+        let integral = cdf[count];
+        for (i, value) in cdf.iter_mut().enumerate() {
+            *value = if integral > 0.0 {
+                *value / integral
+            } else {
+                // an all zero function is sampled uniformly
+                i as f64 / count as f64
+            };
+        }
+        Distribution1D {
+            function: function.iter().map(|value| value.abs()).collect(),
+            cdf,
+            integral,
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        self.function.len()
+    }
+
+    pub fn integral(&self) -> f64 {
+        self.integral
+    }
+
+    // index of the segment containing the random number, found by binary search over the cdf
+    fn find_segment(&self, random: f64) -> usize {
+        let index = self.cdf.partition_point(|value| *value <= random);
+        index.saturating_sub(1).min(self.count() - 1)
+    }
+
+    // maps a uniform random number in [0, 1) to (x in [0, 1), density at x, segment index)
+    pub fn sample_continuous(&self, random: f64) -> (f64, f64, usize) {
+        let index = self.find_segment(random);
+        let segment_width = self.cdf[index + 1] - self.cdf[index];
+        let offset = if segment_width > 0.0 {
+            (random - self.cdf[index]) / segment_width
+        } else {
+            0.0
+        };
+        let x = (index as f64 + offset) / self.count() as f64;
+        (x, self.pdf(x), index)
+    }
+
+    // maps a uniform random number in [0, 1) to (segment index, probability of that segment)
+    pub fn sample_discrete(&self, random: f64) -> (usize, f64) {
+        let index = self.find_segment(random);
+        (index, self.cdf[index + 1] - self.cdf[index])
+    }
+
+    pub fn pdf(&self, x: f64) -> f64 {
+        let index = ((x * self.count() as f64) as usize).min(self.count() - 1);
+        if self.integral > 0.0 {
+            self.function[index] / self.integral
+        } else {
+            1.0
+        }
+    }
+}
+
+// 2D distribution over [0, 1]², sampled as a marginal over rows (v) and a conditional per row (u)
+pub struct Distribution2D {
+    conditionals: Vec<Distribution1D>,
+    marginal: Distribution1D,
+}
+
+impl Distribution2D {
+    // function is row major with width * height entries
+    pub fn new(function: &[f64], width: usize, height: usize) -> Distribution2D {
+        let conditionals: Vec<Distribution1D> = function
+            .chunks_exact(width)
+            .take(height)
+            .map(Distribution1D::new)
+            .collect();
+        let marginal = Distribution1D::new(
+            &conditionals
+                .iter()
+                .map(|row| row.integral())
+                .collect::<Vec<f64>>(),
+        );
+        Distribution2D {
+            conditionals,
+            marginal,
+        }
+    }
+
+    // returns (u, v, density in uv space)
+    pub fn sample(&self, random_u: f64, random_v: f64) -> (f64, f64, f64) {
+        let (v, marginal_pdf, row) = self.marginal.sample_continuous(random_v);
+        let (u, conditional_pdf, _) = self.conditionals[row].sample_continuous(random_u);
+        (u, v, marginal_pdf * conditional_pdf)
+    }
+
+    pub fn pdf(&self, u: f64, v: f64) -> f64 {
+        let row = ((v * self.conditionals.len() as f64) as usize).min(self.conditionals.len() - 1);
+        self.marginal.pdf(v) * self.conditionals[row].pdf(u)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::random;
+
+    #[test]
+    fn samples_follow_function() {
+        let distribution = Distribution1D::new(&[1.0, 3.0]);
+        let in_second_half = (0..10000)
+            .filter(|_| distribution.sample_continuous(random()).0 >= 0.5)
+            .count();
+        assert!((in_second_half as f64 / 10000.0 - 0.75).abs() < 0.03);
+    }
+
+    #[test]
+    fn pdf_matches_sample() {
+        let distribution = Distribution1D::new(&[0.5, 2.0, 0.0, 1.5]);
+        for _ in 0..100 {
+            let (x, pdf, _) = distribution.sample_continuous(random());
+            assert_eq!(pdf, distribution.pdf(x));
+            assert!(pdf > 0.0);
+        }
+    }
+
+    #[test]
+    fn zero_function_is_uniform() {
+        let distribution = Distribution1D::new(&[0.0, 0.0, 0.0, 0.0]);
+        assert_eq!(distribution.sample_continuous(0.6).0, 0.6);
+        assert_eq!(distribution.pdf(0.1), 1.0);
+    }
+
+    #[test]
+    fn pdf_2d_integrates_to_one() {
+        let function: Vec<f64> = (0..64).map(|_| random()).collect();
+        let distribution = Distribution2D::new(&function, 8, 8);
+        let samples = 100000;
+        let integral: f64 = (0..samples)
+            .map(|_| distribution.pdf(random(), random()))
+            .sum::<f64>()
+            / samples as f64;
+        assert!((integral - 1.0).abs() < 0.02);
+    }
+}
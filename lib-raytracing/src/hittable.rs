@@ -1,11 +1,17 @@
-use crate::{material::Material, ray::Ray, vec3::Vec3};
+use std::sync::Arc;
+
+use crate::{aabb::Aabb, material::Material, ray::Ray, vec3::Vec3};
 
 pub struct HitRecord<'a> {
-    pub point: Vec3,      // where is it hit
-    pub normal: Vec3,     // where does it point
-    pub distance: f64,    // distance
-    pub front_face: bool, // does the hit come from a ray facing in or out the object
-    pub material: &'a Box<dyn Material>,
+    pub point: Vec3,                // where is it hit
+    pub normal: Vec3,               // where does it point
+    pub distance: f64,              // distance
+    pub u: f64,                     // horizontal surface coordinate for texture lookups
+    pub v: f64,                     // vertical surface coordinate for texture lookups
+    pub front_face: bool,           // does the hit come from a ray facing in or out the object
+    pub vertex_color: Option<Vec3>, // interpolated color of meshes that carry vertex colors
+    pub tangent: Option<Vec3>, // unit direction of fibers (e.g. curves) for materials like hair
+    pub material: &'a dyn Material,
 }
 
 impl HitRecord<'_> {
@@ -19,10 +25,71 @@ impl HitRecord<'_> {
     }
 }
 
-pub trait Hittable: Sync {
-    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord>;
+#[cfg(test)]
+impl<'a> HitRecord<'a> {
+    // a hit one unit along the ray without uvs, colors or tangents, for tests of materials
+    pub(crate) fn for_test(
+        point: Vec3,
+        normal: Vec3,
+        front_face: bool,
+        material: &'a dyn Material,
+    ) -> HitRecord<'a> {
+        HitRecord {
+            point,
+            normal,
+            distance: 1.0,
+            u: 0.0,
+            v: 0.0,
+            front_face,
+            vertex_color: None,
+            tangent: None,
+            material,
+        }
+    }
+}
+
+// gray and diffuse, for tests of objects that do not care about the material
+#[cfg(test)]
+pub(crate) fn test_material() -> Box<dyn Material> {
+    let gray = Vec3::new(0.5, 0.5, 0.5);
+    Box::new(crate::materials::lambertian::Lambertian::new(gray))
+}
+
+// Send so objects can be shared between instances through an Arc
+pub trait Hittable: Send + Sync {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>>;
+
+    // None for objects without finite extent (e.g. infinite planes)
+    fn bounding_box(&self) -> Option<Aabb>;
+
+    // every crossing of the surface along the ray sorted by distance. front_face tells
+    // whether the ray enters or leaves, so for closed objects the crossings bound the
+    // intervals of the ray inside the object (see objects::csg).
+    // The default collects the hits one after another
+    fn hit_all(&self, ray: &Ray, t_min: f64, t_max: f64) -> Vec<HitRecord<'_>> {
+        const STEP: f64 = 1e-7; // keeps the next query from finding the same crossing again
+        let mut hits: Vec<HitRecord<'_>> = vec![];
+        let mut t = t_min;
+        while let Some(record) = self.hit(ray, t, t_max) {
+            t = record.distance + STEP;
+            hits.push(record);
+        }
+        hits
+    }
+
+    // fraction of the light that makes it from ray.at(t_min) to ray.at(t_max), used for
+    // shadow rays. Surfaces block everything, participating media let part of it through
+    // and estimate how much (see objects::heterogeneous_medium)
+    fn transmittance(&self, ray: &Ray, t_min: f64, t_max: f64) -> f64 {
+        if self.hit(ray, t_min, t_max).is_some() {
+            0.0
+        } else {
+            1.0
+        }
+    }
 }
 
+#[derive(Default)]
 pub struct HittableList {
     pub objects: Vec<Box<dyn Hittable>>,
 }
@@ -37,25 +104,55 @@ impl HittableList {
     }
 }
 
-impl Default for HittableList {
-    fn default() -> Self {
-        Self { objects: vec![] }
-    }
-}
-
 impl Hittable for HittableList {
-    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
         let mut hit_record = None;
         let mut closest_so_far = t_max;
         for obj in &self.objects {
-            match obj.hit(ray, t_min, closest_so_far) {
-                Some(record) => {
-                    closest_so_far = record.distance;
-                    hit_record = Some(record)
-                }
-                None => (),
+            if let Some(record) = obj.hit(ray, t_min, closest_so_far) {
+                closest_so_far = record.distance;
+                hit_record = Some(record)
             }
         }
         hit_record
     }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let mut objects = self.objects.iter();
+        let mut bounding_box = objects.next()?.bounding_box()?;
+        for obj in objects {
+            bounding_box = Aabb::surrounding_box(&bounding_box, &obj.bounding_box()?);
+        }
+        Some(bounding_box)
+    }
+
+    fn transmittance(&self, ray: &Ray, t_min: f64, t_max: f64) -> f64 {
+        let mut transmittance = 1.0;
+        for obj in &self.objects {
+            transmittance *= obj.transmittance(ray, t_min, t_max);
+            if transmittance <= 0.0 {
+                break;
+            }
+        }
+        transmittance
+    }
+}
+
+// lets one object (e.g. a large mesh) be referenced by many objects::transformed::Instance
+impl<H: Hittable + ?Sized> Hittable for Arc<H> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
+        self.as_ref().hit(ray, t_min, t_max)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.as_ref().bounding_box()
+    }
+
+    fn hit_all(&self, ray: &Ray, t_min: f64, t_max: f64) -> Vec<HitRecord<'_>> {
+        self.as_ref().hit_all(ray, t_min, t_max)
+    }
+
+    fn transmittance(&self, ray: &Ray, t_min: f64, t_max: f64) -> f64 {
+        self.as_ref().transmittance(ray, t_min, t_max)
+    }
 }
@@ -1,21 +1,41 @@
 use camera::Camera;
-use hittable::{Hittable, HittableList};
+use hittable::HitRecord;
 use ray::Ray;
 use rayon::prelude::{IntoParallelIterator, ParallelIterator};
+use scene::Scene;
 use util::INFTY;
 use vec3::Vec3;
 
+pub mod aabb;
+pub mod animated_transform;
+pub mod background;
+pub mod backgrounds;
+pub mod bvh;
 pub mod camera;
+pub mod distribution;
 pub mod hittable;
+pub mod light;
+pub mod lights;
+pub mod loaders;
+pub mod mat4;
 pub mod material;
 pub mod materials;
+pub mod microfacet;
 pub mod objects;
+pub mod polynomial;
 pub mod ray;
+pub mod scene;
+pub mod sdf;
+pub mod sdfs;
+pub mod texture;
+pub mod textures;
+pub mod transform;
 pub mod util;
 pub mod vec3;
+pub mod voxel_grid;
 
 pub fn render_scene(
-    scene: &HittableList,
+    scene: &Scene,
     camera: &Camera,
     image_height: u32,
     image_width: u32,
@@ -30,25 +50,354 @@ pub fn render_scene(
             let v = ((j as f64) + util::random()) / ((image_height - 1) as f64);
 
             let ray = camera.shoot_ray(u, v);
-            raytrace(&ray, &scene, max_bounce)
+            raytrace(&ray, scene, max_bounce)
         })
         .collect()
 }
 
-fn raytrace(ray: &Ray, scene: &HittableList, depth: u32) -> Vec3 {
-    if depth <= 0 {
+fn raytrace(ray: &Ray, scene: &Scene, depth: u32) -> Vec3 {
+    trace(ray, scene, depth, None)
+}
+
+// scattering_pdf is the density with which the previous bounce picked the ray if it also
+// sampled the lights and the background directly, so emission found by the ray counts with
+// its MIS weight only
+fn trace(ray: &Ray, scene: &Scene, depth: u32, scattering_pdf: Option<f64>) -> Vec3 {
+    if depth == 0 {
         return Vec3::new(0.0, 0.0, 0.0);
     }
 
-    match scene.hit(&ray, 0.001, INFTY) {
-        Some(hit_record) => match hit_record.material.scatter(&ray, &hit_record) {
-            Some((color, scattered_ray)) => color * raytrace(&scattered_ray, &scene, depth - 1),
-            None => Vec3::new(0.0, 0.0, 0.0),
-        },
+    match scene.world.hit(ray, 0.001, INFTY) {
+        Some(hit_record) => {
+            let material = hit_record.material;
+            let mut emitted = material.emitted(ray, &hit_record);
+            if let Some(material_pdf) = scattering_pdf.filter(|pdf| *pdf > 0.0) {
+                let light_pdf = scene.light_pdf(ray, hit_record.distance);
+                emitted *= material_pdf / (material_pdf + light_pdf);
+            }
+            // lights are sampled for the lobes of the material that can be evaluated, even
+            // when its own sample fails. The continued path sees the lights only at the next
+            // bounce, so both strategies stop together at the last one
+            let direct = if depth > 1 {
+                sample_lights(ray, &hit_record, scene) + sample_background(ray, &hit_record, scene)
+            } else {
+                Vec3::new(0.0, 0.0, 0.0)
+            };
+            let Some(sample) = material.sample(ray, &hit_record) else {
+                return emitted + direct;
+            };
+            // what a sharp lobe finds was not found by light sampling and counts fully
+            let pdf = Some(sample.pdf).filter(|_| !sample.is_specular && depth > 1);
+            emitted + direct + sample.attenuation * trace(&sample.ray, scene, depth - 1, pdf)
+        }
         None => {
-            let unit_direction = Vec3::unit_vector(&ray.direction);
-            let t = 0.5 * (unit_direction.y + 1.0);
-            (1.0 - t) * Vec3::new(1.0, 1.0, 1.0) + t * Vec3::new(0.5, 0.7, 1.0) //skybox
+            let color = scene.background.color(ray);
+            match scattering_pdf {
+                Some(material_pdf) if material_pdf > 0.0 => {
+                    let background_pdf = scene.background.pdf(&ray.direction);
+                    color * (material_pdf / (material_pdf + background_pdf))
+                }
+                _ => color,
+            }
+        }
+    }
+}
+
+// next event estimation towards the lights: a shadow ray to a point on one of them,
+// weighted against the material's own sampling with the balance heuristic
+fn sample_lights(ray: &Ray, hit_record: &HitRecord, scene: &Scene) -> Vec3 {
+    let black = Vec3::new(0.0, 0.0, 0.0);
+    let Some(light) = scene.sample_light(&hit_record.point, ray.time) else {
+        return black;
+    };
+    let material = hit_record.material;
+    let wo = Vec3::unit_vector(&(-1.0 * ray.direction));
+    let scattering = material.eval(&light.direction, &wo, hit_record);
+    if light.pdf <= 0.0 || scattering == black {
+        return black;
+    }
+    // the light itself must not block the shadow ray
+    let shadow_ray = Ray::with_time(hit_record.point, light.direction, ray.time);
+    let transmittance = scene
+        .world
+        .transmittance(&shadow_ray, 0.001, light.distance - 0.001);
+    if transmittance <= 0.0 {
+        return black;
+    }
+    let material_pdf = if light.is_delta {
+        0.0 // no material sample finds the light
+    } else {
+        material.pdf(&light.direction, &wo, hit_record)
+    };
+    scattering * light.radiance * (transmittance / (light.pdf + material_pdf))
+}
+
+// next event estimation towards the background: a direction from the background's
+// importance sampler, attenuated by whatever lies in between (fully by surfaces, partially
+// by participating media) and weighted against the material's own sampling with the
+// balance heuristic
+fn sample_background(ray: &Ray, hit_record: &HitRecord, scene: &Scene) -> Vec3 {
+    let black = Vec3::new(0.0, 0.0, 0.0);
+    let Some(direction) = scene.background.sample_direction() else {
+        return black;
+    };
+    let material = hit_record.material;
+    let wi = Vec3::unit_vector(&direction);
+    let wo = Vec3::unit_vector(&(-1.0 * ray.direction));
+    let material_pdf = material.pdf(&wi, &wo, hit_record);
+    let background_pdf = scene.background.pdf(&direction);
+    if material_pdf <= 0.0 || background_pdf <= 0.0 {
+        return black;
+    }
+    let shadow_ray = Ray::with_time(hit_record.point, direction, ray.time);
+    let transmittance = scene.world.transmittance(&shadow_ray, 0.001, INFTY);
+    if transmittance <= 0.0 {
+        return black;
+    }
+    material.eval(&wi, &wo, hit_record)
+        * scene.background.color(&shadow_ray)
+        * (transmittance / (material_pdf + background_pdf))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        aabb::Aabb,
+        backgrounds::{
+            environment_map::EnvironmentMap, gradient::Gradient, solid_color::SolidColor,
+        },
+        hittable::HittableList,
+        lights::{directional_light::DirectionalLight, point_light::PointLight},
+        material::Material,
+        materials::{
+            diffuse_light::DiffuseLight, lambertian::Lambertian, principled::Principled,
+            rough_conductor::RoughConductor,
+        },
+        objects::{heterogeneous_medium::HeterogeneousMedium, quad::Quad, sphere::Sphere},
+        voxel_grid::VoxelGrid,
+    };
+    use std::sync::Arc;
+
+    // a light sphere inside a closed diffuse room, so no ray can escape to the sky
+    fn closed_room(light_color: Vec3) -> Scene {
+        let mut scene: HittableList = Default::default();
+        scene.add(Sphere {
+            center: Vec3::new(0.0, 0.0, 0.0),
+            radius: 10.0,
+            material: Box::new(Lambertian::new(Vec3::new(0.5, 0.5, 0.5))),
+        });
+        scene.add(Sphere {
+            center: Vec3::new(0.0, 0.0, 0.0),
+            radius: 1.0,
+            material: Box::new(DiffuseLight::new(light_color)),
+        });
+        Scene::new(scene, Gradient::default())
+    }
+
+    #[test]
+    fn ray_into_light_returns_emission() {
+        let light_color = Vec3::new(4.0, 3.0, 2.0);
+        let scene = closed_room(light_color);
+        let ray = Ray::new(Vec3::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0));
+        assert_eq!(raytrace(&ray, &scene, 1), light_color);
+    }
+
+    #[test]
+    fn dark_closed_room_is_black() {
+        let scene = closed_room(Vec3::new(0.0, 0.0, 0.0));
+        for _ in 0..100 {
+            let ray = Ray::new(Vec3::new(0.0, 0.0, 5.0), Vec3::random_unit_vector());
+            assert_eq!(raytrace(&ray, &scene, 10), Vec3::new(0.0, 0.0, 0.0));
         }
     }
+
+    #[test]
+    fn walls_are_lit_by_emission() {
+        let scene = closed_room(Vec3::new(4.0, 4.0, 4.0));
+        let ray = Ray::new(Vec3::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, 1.0));
+        let mut sum = Vec3::new(0.0, 0.0, 0.0);
+        for _ in 0..1000 {
+            sum += raytrace(&ray, &scene, 10);
+        }
+        assert!(sum.x > 0.0 && sum.x.is_finite());
+    }
+
+    #[test]
+    fn background_sampling_keeps_expectation() {
+        // a white diffuse floor under a uniform sky reflects exactly its albedo
+        let mut world: HittableList = Default::default();
+        world.add(Sphere {
+            center: Vec3::new(0.0, -1000.0, 0.0),
+            radius: 1000.0,
+            material: Box::new(Lambertian::new(Vec3::new(0.5, 0.5, 0.5))),
+        });
+        let mut pixels = vec![Vec3::new(1.0, 1.0, 1.0); 16 * 8];
+        pixels[3] = Vec3::new(1.0, 1.0, 1.0) * 1.0001; // non uniform weights
+        let scene = Scene::new(world, EnvironmentMap::new(16, 8, pixels));
+        let ray = Ray::new(Vec3::new(0.0, 1.0, 0.0), Vec3::new(0.0, -1.0, 0.0));
+        let samples = 20000;
+        let mut sum = Vec3::new(0.0, 0.0, 0.0);
+        for _ in 0..samples {
+            sum += raytrace(&ray, &scene, 2);
+        }
+        assert!((sum.x / samples as f64 - 0.5).abs() < 0.02);
+    }
+
+    #[test]
+    fn white_medium_in_a_white_furnace() {
+        // a medium that absorbs nothing under a uniform sky neither adds nor removes light,
+        // whether the sky is reached by the continued path or by shadow rays through it
+        let mut world: HittableList = Default::default();
+        let values = (0..27).map(|i| (i % 5) as f64).collect();
+        world.add(HeterogeneousMedium::new(
+            VoxelGrid::new([3, 3, 3], values),
+            Aabb::new(Vec3::new(-1.0, -1.0, -1.0), Vec3::new(1.0, 1.0, 1.0)),
+            0.5,
+            Vec3::new(1.0, 1.0, 1.0),
+            0.5,
+        ));
+        let scene = Scene::new(
+            world,
+            EnvironmentMap::new(4, 2, vec![Vec3::new(1.0, 1.0, 1.0); 8]),
+        );
+        let ray = Ray::new(Vec3::new(0.0, 0.0, -5.0), Vec3::new(0.0, 0.0, 1.0));
+        let samples = 20000;
+        let mut sum = Vec3::new(0.0, 0.0, 0.0);
+        for _ in 0..samples {
+            sum += raytrace(&ray, &scene, 50);
+        }
+        assert!((sum.x / samples as f64 - 1.0).abs() < 0.02);
+    }
+
+    // a diffuse floor under a sampled light sphere with its center above the origin, in the dark
+    fn lamp_over_floor(radius: f64, height: f64, radiance: f64) -> Scene {
+        let floor = Lambertian::new(Vec3::new(0.5, 0.5, 0.5));
+        lamp_over(Box::new(floor), radius, height, radiance)
+    }
+
+    fn lamp_over(floor: Box<dyn Material>, radius: f64, height: f64, radiance: f64) -> Scene {
+        let mut world: HittableList = Default::default();
+        world.add(Quad::new(
+            Vec3::new(-100.0, 0.0, -100.0),
+            Vec3::new(0.0, 0.0, 200.0),
+            Vec3::new(200.0, 0.0, 0.0),
+            floor,
+        ));
+        let lamp = Arc::new(Sphere {
+            center: Vec3::new(0.0, height, 0.0),
+            radius,
+            material: Box::new(DiffuseLight::new(Vec3::new(radiance, radiance, radiance))),
+        });
+        world.add(lamp.clone());
+        let black = Vec3::new(0.0, 0.0, 0.0);
+        let mut scene = Scene::new(world, SolidColor { color: black });
+        scene.lights.push(lamp);
+        scene
+    }
+
+    // radiance reflected by the floor right below the lamp: albedo * L * sin²(half angle)
+    fn floor_below_lamp(radius: f64, height: f64, radiance: f64) -> f64 {
+        0.5 * radiance * (radius / height).powi(2)
+    }
+
+    #[test]
+    fn tiny_lights_are_sampled_directly() {
+        let scene = lamp_over_floor(0.05, 2.0, 100.0);
+        let expected = floor_below_lamp(0.05, 2.0, 100.0);
+        let ray = Ray::new(Vec3::new(0.0, 0.5, 0.0), Vec3::new(0.0, -1.0, 0.0));
+        // every single sample is close, not just the mean of thousands
+        for _ in 0..16 {
+            let color = raytrace(&ray, &scene, 2);
+            assert!((color.x / expected - 1.0).abs() < 0.05, "{}", color.x);
+        }
+    }
+
+    #[test]
+    fn tiny_lights_light_microfacet_floors() {
+        // rays hit the lamp once in thousands of bounces, so every sample that is lit was
+        // lit by light sampling
+        let expected = floor_below_lamp(0.05, 2.0, 100.0);
+        let ray = Ray::new(Vec3::new(0.0, 0.5, 0.0), Vec3::new(0.0, -1.0, 0.0));
+        let floors: [Box<dyn Material>; 2] = [
+            Box::new(Principled::new(Vec3::new(0.5, 0.5, 0.5), 0.0, 0.5)),
+            Box::new(RoughConductor::new(Vec3::new(0.9, 0.9, 0.9), 0.5)),
+        ];
+        for floor in floors {
+            let scene = lamp_over(floor, 0.05, 2.0, 100.0);
+            for _ in 0..16 {
+                let color = raytrace(&ray, &scene, 2);
+                assert!(color.x > 0.5 * expected, "{}", color.x);
+            }
+        }
+    }
+
+    #[test]
+    fn light_sampling_keeps_expectation() {
+        // a large light close by is found by both strategies, which have to add up
+        let scene = lamp_over_floor(1.0, 2.0, 1.0);
+        let expected = floor_below_lamp(1.0, 2.0, 1.0);
+        let ray = Ray::new(Vec3::new(0.0, 0.5, 0.0), Vec3::new(0.0, -1.0, 0.0));
+        let samples = 20000;
+        let mut sum = Vec3::new(0.0, 0.0, 0.0);
+        for _ in 0..samples {
+            sum += raytrace(&ray, &scene, 2);
+        }
+        assert!((sum.x / samples as f64 / expected - 1.0).abs() < 0.02);
+    }
+
+    #[test]
+    fn punctual_lights_light_the_floor() {
+        // the floor reflects albedo / π of the irradiance, I / d² from the point light and
+        // E cos(60°) from the sun, next to nothing from the dark lamp
+        let mut scene = lamp_over_floor(0.05, 2.0, 0.0);
+        scene.lights.push(Arc::new(PointLight::new(
+            Vec3::new(0.0, 1.0, 0.0),
+            Vec3::new(2.0, 2.0, 2.0),
+        )));
+        scene.lights.push(Arc::new(DirectionalLight::new(
+            Vec3::new(3.0_f64.sqrt(), -1.0, 0.0),
+            Vec3::new(3.0, 3.0, 3.0),
+        )));
+        let expected = 0.5 / util::PI * (2.0 + 3.0 * 0.5);
+        let ray = Ray::new(Vec3::new(0.5, 0.5, 0.0), Vec3::new(-1.0, -1.0, 0.0));
+        let samples = 1000;
+        let mut sum = Vec3::new(0.0, 0.0, 0.0);
+        for _ in 0..samples {
+            sum += raytrace(&ray, &scene, 2);
+        }
+        // one of the three lights is picked at random for each sample
+        assert!((sum.x / samples as f64 / expected - 1.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn punctual_lights_light_microfacet_floors() {
+        // a point light is never hit by a ray, so all of it comes through eval. Half of what
+        // a diffuse floor of the same color reflects is plenty to tell it from black
+        let expected = 0.5 / util::PI * 2.0;
+        let ray = Ray::new(Vec3::new(0.5, 0.5, 0.0), Vec3::new(-1.0, -1.0, 0.0));
+        let floors: [Box<dyn Material>; 2] = [
+            Box::new(Principled::new(Vec3::new(0.5, 0.5, 0.5), 0.0, 0.5)),
+            Box::new(RoughConductor::new(Vec3::new(0.9, 0.9, 0.9), 0.5)),
+        ];
+        for floor in floors {
+            let mut scene = lamp_over(floor, 0.05, 2.0, 0.0);
+            scene.lights = vec![Arc::new(PointLight::new(
+                Vec3::new(0.0, 1.0, 0.0),
+                Vec3::new(2.0, 2.0, 2.0),
+            ))];
+            for _ in 0..16 {
+                let color = raytrace(&ray, &scene, 2);
+                assert!(color.x > 0.5 * expected, "{}", color.x);
+            }
+        }
+    }
+
+    #[test]
+    fn missed_rays_return_background() {
+        let color = Vec3::new(0.1, 0.2, 0.3);
+        let scene = Scene::new(HittableList::default(), SolidColor { color });
+        let ray = Ray::new(Vec3::new(0.0, 0.0, 0.0), Vec3::random_unit_vector());
+        assert_eq!(raytrace(&ray, &scene, 10), color);
+    }
 }
@@ -0,0 +1,25 @@
+use crate::{ray::Ray, vec3::Vec3};
+
+// a direction towards a light picked by Light::sample
+pub struct LightSample {
+    pub direction: Vec3, // unit length, from the shaded point towards the light
+    pub distance: f64,   // along direction to the sampled point of the light
+    pub radiance: Vec3,  // arriving from the light, before anything in between is accounted for
+    pub pdf: f64,        // solid angle density of the direction
+    // the light sits in a single point or direction that rays never hit by chance, radiance
+    // is then the irradiance perpendicular to direction and pdf the odds of the sample
+    pub is_delta: bool,
+}
+
+// emitters the integrator aims shadow rays at (next event estimation). Lights made of
+// geometry also have to be part of the world so rays can hit them, the same object is then
+// shared between both through an Arc. The punctual lights in lights:: are only sampled, so
+// they only light materials that are not specular
+pub trait Light: Send + Sync {
+    fn sample(&self, point: &Vec3, time: f64) -> Option<LightSample>;
+
+    // solid angle density with which sample() picks the direction of ray from its origin, if
+    // the ray reaches the light at distance, 0 otherwise. Weighs rays that hit the light by
+    // chance against the samples aimed at it
+    fn pdf(&self, ray: &Ray, distance: f64) -> f64;
+}
@@ -0,0 +1,38 @@
+use crate::{
+    light::{Light, LightSample},
+    ray::Ray,
+    util::INFTY,
+    vec3::Vec3,
+};
+
+// light from infinitely far away arriving from the same direction everywhere, like the sun.
+// The irradiance is what a surface facing the light receives
+pub struct DirectionalLight {
+    pub direction: Vec3, // unit length, the direction the light travels in
+    pub irradiance: Vec3,
+}
+
+impl DirectionalLight {
+    pub fn new(direction: Vec3, irradiance: Vec3) -> DirectionalLight {
+        DirectionalLight {
+            direction: Vec3::unit_vector(&direction),
+            irradiance,
+        }
+    }
+}
+
+impl Light for DirectionalLight {
+    fn sample(&self, _point: &Vec3, _time: f64) -> Option<LightSample> {
+        Some(LightSample {
+            direction: -1.0 * self.direction,
+            distance: INFTY,
+            radiance: self.irradiance,
+            pdf: 1.0,
+            is_delta: true,
+        })
+    }
+
+    fn pdf(&self, _ray: &Ray, _distance: f64) -> f64 {
+        0.0
+    }
+}
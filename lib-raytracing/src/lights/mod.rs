@@ -0,0 +1,3 @@
+pub mod directional_light;
+pub mod point_light;
+pub mod spot_light;
@@ -0,0 +1,43 @@
+use crate::{
+    light::{Light, LightSample},
+    ray::Ray,
+    vec3::Vec3,
+};
+
+// light shining equally into all directions from a single point, the intensity is the
+// radiance it gives a surface facing it from a distance of 1
+pub struct PointLight {
+    pub position: Vec3,
+    pub intensity: Vec3,
+}
+
+impl PointLight {
+    pub fn new(position: Vec3, intensity: Vec3) -> PointLight {
+        PointLight {
+            position,
+            intensity,
+        }
+    }
+}
+
+impl Light for PointLight {
+    fn sample(&self, point: &Vec3, _time: f64) -> Option<LightSample> {
+        let to_light = self.position - *point;
+        let distance_squared = to_light.length_squared();
+        if distance_squared <= 0.0 {
+            return None;
+        }
+        let distance = distance_squared.sqrt();
+        Some(LightSample {
+            direction: to_light / distance,
+            distance,
+            radiance: self.intensity / distance_squared,
+            pdf: 1.0,
+            is_delta: true,
+        })
+    }
+
+    fn pdf(&self, _ray: &Ray, _distance: f64) -> f64 {
+        0.0
+    }
+}
@@ -0,0 +1,107 @@
+use crate::{
+    degrees_to_radians,
+    light::{Light, LightSample},
+    ray::Ray,
+    vec3::Vec3,
+};
+
+// point light restricted to a cone around direction. Inside falloff_start degrees of the
+// axis the spot is at full intensity, from there it fades smoothly to nothing at
+// cone_angle degrees
+pub struct SpotLight {
+    pub position: Vec3,
+    pub direction: Vec3, // unit length axis of the cone
+    pub intensity: Vec3,
+    cos_cone_angle: f64,
+    cos_falloff_start: f64,
+}
+
+impl SpotLight {
+    pub fn new(
+        position: Vec3,
+        target: Vec3,
+        intensity: Vec3,
+        cone_angle: f64,
+        falloff_start: f64,
+    ) -> SpotLight {
+        assert!(
+            falloff_start <= cone_angle,
+            "the falloff of a spot light has to start inside its cone"
+        );
+        SpotLight {
+            position,
+            direction: Vec3::unit_vector(&(target - position)),
+            intensity,
+            cos_cone_angle: f64::cos(degrees_to_radians!(cone_angle)),
+            cos_falloff_start: f64::cos(degrees_to_radians!(falloff_start)),
+        }
+    }
+
+    // fraction of the intensity sent out towards a unit direction
+    pub fn falloff(&self, direction: &Vec3) -> f64 {
+        let cos_theta = Vec3::dot(&self.direction, direction);
+        if cos_theta <= self.cos_cone_angle {
+            return 0.0;
+        }
+        if cos_theta >= self.cos_falloff_start {
+            return 1.0;
+        }
+        let x = (cos_theta - self.cos_cone_angle) / (self.cos_falloff_start - self.cos_cone_angle);
+        x * x * (3.0 - 2.0 * x) // smoothstep
+    }
+}
+
+impl Light for SpotLight {
+    fn sample(&self, point: &Vec3, _time: f64) -> Option<LightSample> {
+        let to_light = self.position - *point;
+        let distance_squared = to_light.length_squared();
+        if distance_squared <= 0.0 {
+            return None;
+        }
+        let distance = distance_squared.sqrt();
+        let direction = to_light / distance;
+        let falloff = self.falloff(&(-1.0 * direction));
+        if falloff <= 0.0 {
+            return None;
+        }
+        Some(LightSample {
+            direction,
+            distance,
+            radiance: self.intensity * (falloff / distance_squared),
+            pdf: 1.0,
+            is_delta: true,
+        })
+    }
+
+    fn pdf(&self, _ray: &Ray, _distance: f64) -> f64 {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cone_fades_out() {
+        let spot = SpotLight::new(
+            Vec3::new(0.0, 4.0, 0.0),
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(16.0, 16.0, 16.0),
+            45.0,
+            30.0,
+        );
+        let at_angle = |degrees: f64| {
+            let radians = degrees_to_radians!(degrees);
+            let point = Vec3::new(4.0 * radians.tan(), 0.0, 0.0);
+            spot.sample(&point, 0.0)
+                .map_or(0.0, |sample| sample.radiance.x * sample.distance.powi(2))
+        };
+        assert!((at_angle(0.0) - 16.0).abs() < 1e-9);
+        assert!((at_angle(29.0) - 16.0).abs() < 1e-9);
+        let half_way = at_angle(38.0);
+        assert!(half_way > 0.0 && half_way < 16.0);
+        assert!(at_angle(37.0) > half_way);
+        assert_eq!(at_angle(46.0), 0.0);
+    }
+}
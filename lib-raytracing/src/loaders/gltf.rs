@@ -0,0 +1,697 @@
+use std::path::Path;
+
+use gltf::{
+    camera::Projection, image::Format, json::Value, material::AlphaMode, mesh::Mode,
+    texture::WrappingMode,
+};
+
+use crate::{
+    camera::Camera,
+    hittable::HittableList,
+    mat4::Mat4,
+    material::Material,
+    materials::principled::{constant, Principled},
+    objects::triangle_mesh::{MeshFace, TriangleMesh},
+    texture::Texture,
+    textures::{
+        image_texture::{srgb_to_linear, ImageTexture, Wrap},
+        solid_color::SolidColor,
+    },
+    vec3::Vec3,
+};
+
+use super::LoadError;
+
+pub struct GltfScene {
+    pub objects: HittableList,
+    pub camera: Camera,
+    pub warnings: Vec<String>, // features of the file that were ignored or approximated
+}
+
+// imports the default scene of a .gltf or .glb file. All mesh instances are baked into one
+// TriangleMesh in world space, the first camera found becomes the scene camera.
+// aspect_ratio is used for cameras that do not define their own
+pub fn load_gltf<P: AsRef<Path>>(path: P, aspect_ratio: f64) -> Result<GltfScene, LoadError> {
+    let (document, buffers, images) =
+        gltf::import(path).map_err(|error| LoadError::Parse(error.to_string()))?;
+    let mut importer = Importer {
+        buffers,
+        images,
+        textures: document
+            .textures()
+            .map(|texture| {
+                let sampler = texture.sampler();
+                (
+                    texture.source().index(),
+                    wrap(sampler.wrap_s()),
+                    wrap(sampler.wrap_t()),
+                )
+            })
+            .collect(),
+        aspect_ratio,
+        positions: vec![],
+        normals: vec![],
+        uvs: vec![],
+        faces: vec![],
+        camera: None,
+        warnings: vec![],
+    };
+
+    let mut materials: Vec<Box<dyn Material>> = document
+        .materials()
+        .map(|material| importer.convert_material(&material))
+        .collect();
+    let default_material = materials.len();
+    materials.push(Box::<Principled>::default());
+
+    let scene = document
+        .default_scene()
+        .or_else(|| document.scenes().next())
+        .ok_or_else(|| LoadError::Parse("file contains no scene".to_string()))?;
+    for node in scene.nodes() {
+        importer.visit_node(&node, &Mat4::IDENTITY, default_material)?;
+    }
+
+    let mut objects: HittableList = Default::default();
+    if !importer.faces.is_empty() {
+        objects.add(TriangleMesh::new(
+            importer.positions,
+            importer.normals,
+            importer.uvs,
+            importer.faces,
+            materials,
+        ));
+    }
+    let camera = importer.camera.unwrap_or_else(|| {
+        importer
+            .warnings
+            .push("scene has no perspective camera, using the default camera".to_string());
+        Camera::default()
+    });
+
+    Ok(GltfScene {
+        objects,
+        camera,
+        warnings: importer.warnings,
+    })
+}
+
+struct Importer {
+    buffers: Vec<gltf::buffer::Data>,
+    images: Vec<gltf::image::Data>,
+    textures: Vec<(usize, Wrap, Wrap)>, // image and wrap modes along u and v of every texture
+    aspect_ratio: f64,
+    positions: Vec<Vec3>,
+    normals: Vec<Vec3>,
+    uvs: Vec<(f64, f64)>,
+    faces: Vec<MeshFace>,
+    camera: Option<Camera>,
+    warnings: Vec<String>,
+}
+
+impl Importer {
+    fn visit_node(
+        &mut self,
+        node: &gltf::Node,
+        parent: &Mat4,
+        default_material: usize,
+    ) -> Result<(), LoadError> {
+        let local = Mat4::from_columns(
+            node.transform()
+                .matrix()
+                .map(|column| column.map(|value| value as f64)),
+        );
+        let world = *parent * local;
+
+        if let Some(mesh) = node.mesh() {
+            for primitive in mesh.primitives() {
+                self.add_primitive(&primitive, &world, default_material)?;
+            }
+        }
+        if let Some(camera) = node.camera() {
+            self.add_camera(&camera, &world);
+        }
+        for child in node.children() {
+            self.visit_node(&child, &world, default_material)?;
+        }
+        Ok(())
+    }
+
+    fn add_primitive(
+        &mut self,
+        primitive: &gltf::Primitive,
+        world: &Mat4,
+        default_material: usize,
+    ) -> Result<(), LoadError> {
+        if primitive.mode() != Mode::Triangles {
+            self.warnings.push(format!(
+                "primitive mode {:?} is not supported, only triangles are imported",
+                primitive.mode()
+            ));
+            return Ok(());
+        }
+        let reader = primitive.reader(|buffer| Some(&self.buffers[buffer.index()]));
+        let positions: Vec<Vec3> = reader
+            .read_positions()
+            .ok_or_else(|| LoadError::Parse("primitive without positions".to_string()))?
+            .map(|p| world.transform_point(&Vec3::new(p[0] as f64, p[1] as f64, p[2] as f64)))
+            .collect();
+        let normals: Option<Vec<Vec3>> = reader.read_normals().map(|normals| {
+            normals
+                .map(|n| world.transform_normal(&Vec3::new(n[0] as f64, n[1] as f64, n[2] as f64)))
+                .collect()
+        });
+        let uvs: Option<Vec<(f64, f64)>> = reader.read_tex_coords(0).map(|uvs| {
+            // glTF puts the uv origin at the top left, textures here use the bottom left
+            uvs.into_f32()
+                .map(|uv| (uv[0] as f64, 1.0 - uv[1] as f64))
+                .collect()
+        });
+        let indices: Vec<usize> = match reader.read_indices() {
+            Some(indices) => indices.into_u32().map(|i| i as usize).collect(),
+            None => (0..positions.len()).collect(),
+        };
+        if let Some(index) = indices.iter().find(|index| **index >= positions.len()) {
+            return Err(LoadError::Parse(format!(
+                "index {} is out of range for {} vertices",
+                index,
+                positions.len()
+            )));
+        }
+
+        let offset = self.positions.len();
+        let material = primitive.material().index().unwrap_or(default_material);
+        let has_normals = normals
+            .as_ref()
+            .is_some_and(|normals| normals.len() == positions.len());
+        let has_uvs = uvs.as_ref().is_some_and(|uvs| uvs.len() == positions.len());
+        // a mirroring node turns counter clockwise triangles clockwise, swapping two corners
+        // keeps them facing outwards
+        let mirrored = world.determinant() < 0.0;
+        for corners in indices.chunks_exact(3) {
+            let mut corners = [
+                corners[0] + offset,
+                corners[1] + offset,
+                corners[2] + offset,
+            ];
+            if mirrored {
+                corners.swap(1, 2);
+            }
+            self.faces.push(MeshFace {
+                positions: corners,
+                normals: has_normals.then_some(corners),
+                uvs: has_uvs.then_some(corners),
+                material,
+            });
+        }
+
+        // keep the buffers index aligned so faces can use one index for all of them
+        let vertex_count = positions.len();
+        self.positions.extend(positions);
+        self.normals.extend(
+            normals
+                .filter(|_| has_normals)
+                .unwrap_or_else(|| vec![Vec3::new(0.0, 1.0, 0.0); vertex_count]),
+        );
+        self.uvs.extend(
+            uvs.filter(|_| has_uvs)
+                .unwrap_or_else(|| vec![(0.0, 0.0); vertex_count]),
+        );
+        Ok(())
+    }
+
+    fn add_camera(&mut self, camera: &gltf::Camera, world: &Mat4) {
+        if self.camera.is_some() {
+            self.warnings
+                .push("scene has more than one camera, using the first one".to_string());
+            return;
+        }
+        match camera.projection() {
+            Projection::Perspective(perspective) => {
+                // glTF cameras look down their local -z axis with +y up
+                let lookfrom = world.transform_point(&Vec3::new(0.0, 0.0, 0.0));
+                let lookat = world.transform_point(&Vec3::new(0.0, 0.0, -1.0));
+                let view_up = world.transform_direction(&Vec3::new(0.0, 1.0, 0.0));
+                self.camera = Some(Camera::new(
+                    lookfrom,
+                    lookat,
+                    view_up,
+                    (perspective.yfov() as f64).to_degrees(),
+                    perspective
+                        .aspect_ratio()
+                        .map(|ratio| ratio as f64)
+                        .unwrap_or(self.aspect_ratio),
+                    0.0, // glTF cameras are pinhole cameras
+                    1.0,
+                ));
+            }
+            Projection::Orthographic(_) => self
+                .warnings
+                .push("orthographic cameras are not supported".to_string()),
+        }
+    }
+
+    // maps the metallic-roughness model and the clearcoat, sheen, specular, transmission,
+    // ior and emissive strength extensions onto Principled
+    fn convert_material(&mut self, material: &gltf::Material) -> Box<dyn Material> {
+        let name = material.name().unwrap_or("unnamed").to_string();
+        let mut unsupported = vec![];
+        if material.normal_texture().is_some() {
+            unsupported.push("normal texture");
+        }
+        if material.occlusion_texture().is_some() {
+            unsupported.push("occlusion texture");
+        }
+        if material.alpha_mode() != AlphaMode::Opaque {
+            unsupported.push("alpha blending and masking");
+        }
+        if let Some(specular) = material.specular() {
+            if specular.specular_texture().is_some() {
+                unsupported.push("specular texture, the factor is used instead");
+            }
+            if specular.specular_color_texture().is_some()
+                || specular.specular_color_factor() != [1.0, 1.0, 1.0]
+            {
+                unsupported.push("specular color");
+            }
+        }
+        let clearcoat = material.extension_value("KHR_materials_clearcoat");
+        if clearcoat.is_some_and(|clearcoat| clearcoat.get("clearcoatNormalTexture").is_some()) {
+            unsupported.push("clearcoat normal texture");
+        }
+        for feature in unsupported {
+            self.warnings
+                .push(format!("material '{}': {} is not supported", name, feature));
+        }
+
+        let to_vec3 =
+            |color: [f32; 3]| Vec3::new(color[0] as f64, color[1] as f64, color[2] as f64);
+        let gray = |value: f64| Vec3::new(value, value, value);
+        let texture_of = |info: gltf::texture::Info| TextureRef {
+            index: info.texture().index(),
+            tex_coord: info.tex_coord(),
+            transformed: info.texture_transform().is_some(),
+        };
+        let pbr = material.pbr_metallic_roughness();
+
+        let [red, green, blue, _] = pbr.base_color_factor();
+        let factor = to_vec3([red, green, blue]);
+        let base_color = self.convert_input(
+            &name,
+            "base color",
+            pbr.base_color_texture().map(texture_of),
+            true,
+            |color| color * factor,
+            factor,
+        );
+
+        // metallic in the blue and roughness in the green channel of one linear image
+        let metallic_roughness = pbr.metallic_roughness_texture().map(texture_of);
+        let metallic_factor = pbr.metallic_factor() as f64;
+        let roughness_factor = pbr.roughness_factor() as f64;
+        let metallic = self.convert_input(
+            &name,
+            "metallic",
+            metallic_roughness,
+            false,
+            |texel| gray(texel.z * metallic_factor),
+            gray(metallic_factor),
+        );
+        let roughness = self.convert_input(
+            &name,
+            "roughness",
+            metallic_roughness,
+            false,
+            |texel| gray(texel.y * roughness_factor),
+            gray(roughness_factor),
+        );
+
+        let strength = material.emissive_strength().unwrap_or(1.0) as f64;
+        let emissive = to_vec3(material.emissive_factor()) * strength;
+        let emission = self.convert_input(
+            &name,
+            "emissive",
+            material.emissive_texture().map(texture_of),
+            true,
+            |color| color * emissive,
+            emissive,
+        );
+
+        let (transmission_factor, transmission_texture) = match material.transmission() {
+            Some(transmission) => (
+                transmission.transmission_factor() as f64,
+                transmission.transmission_texture().map(texture_of),
+            ),
+            None => (0.0, None),
+        };
+        let transmission = self.convert_input(
+            &name,
+            "transmission",
+            transmission_texture,
+            false,
+            |texel| gray(texel.x * transmission_factor),
+            gray(transmission_factor),
+        );
+
+        // the specular input is relative to the 4% of an index of refraction of 1.5
+        let index_of_refraction = material.ior().unwrap_or(1.5) as f64;
+        let reflectance = ((index_of_refraction - 1.0) / (index_of_refraction + 1.0)).powi(2);
+        let specular_factor = material
+            .specular()
+            .map_or(1.0, |specular| specular.specular_factor() as f64);
+        let specular = (specular_factor * reflectance / 0.08).clamp(0.0, 1.0);
+
+        // factors and textures of the extensions gltf does not parse itself
+        let extension_factor = |extension: Option<&Value>, key: &str, default: Vec3| match extension
+            .and_then(|extension| extension.get(key))
+        {
+            Some(Value::Number(number)) => gray(number.as_f64().unwrap_or(default.x)),
+            Some(Value::Array(values)) if values.len() == 3 => Vec3::new(
+                values[0].as_f64().unwrap_or(default.x),
+                values[1].as_f64().unwrap_or(default.y),
+                values[2].as_f64().unwrap_or(default.z),
+            ),
+            _ => default,
+        };
+        let extension_texture = |extension: Option<&Value>, key: &str| {
+            let info = extension?.get(key)?;
+            let tex_coord = info.get("texCoord").and_then(Value::as_u64).unwrap_or(0);
+            Some(TextureRef {
+                index: info.get("index")?.as_u64()? as usize,
+                tex_coord: tex_coord as u32,
+                transformed: info
+                    .get("extensions")
+                    .is_some_and(|extensions| extensions.get("KHR_texture_transform").is_some()),
+            })
+        };
+        let clearcoat_factor = extension_factor(clearcoat, "clearcoatFactor", gray(0.0)).x;
+        let clearcoat_roughness_factor =
+            extension_factor(clearcoat, "clearcoatRoughnessFactor", gray(0.0)).x;
+        let sheen = material.extension_value("KHR_materials_sheen");
+        let sheen_factor = extension_factor(sheen, "sheenColorFactor", gray(0.0));
+        let clearcoat_texture = extension_texture(clearcoat, "clearcoatTexture");
+        let clearcoat_roughness_texture = extension_texture(clearcoat, "clearcoatRoughnessTexture");
+        let sheen_texture = extension_texture(sheen, "sheenColorTexture");
+
+        Box::new(Principled {
+            base_color,
+            metallic,
+            roughness,
+            specular: constant(specular),
+            clearcoat: self.convert_input(
+                &name,
+                "clearcoat",
+                clearcoat_texture,
+                false,
+                |texel| gray(texel.x * clearcoat_factor),
+                gray(clearcoat_factor),
+            ),
+            clearcoat_roughness: self.convert_input(
+                &name,
+                "clearcoat roughness",
+                clearcoat_roughness_texture,
+                false,
+                |texel| gray(texel.y * clearcoat_roughness_factor),
+                gray(clearcoat_roughness_factor),
+            ),
+            sheen: self.convert_input(
+                &name,
+                "sheen",
+                sheen_texture,
+                true,
+                |color| color * sheen_factor,
+                sheen_factor,
+            ),
+            transmission,
+            index_of_refraction,
+            emission,
+        })
+    }
+
+    // the texture of one material input: the image of a glTF texture mapped through map, or
+    // the factor alone without a usable image
+    fn convert_input(
+        &mut self,
+        name: &str,
+        input: &str,
+        texture: Option<TextureRef>,
+        srgb: bool,
+        map: impl Fn(Vec3) -> Vec3,
+        factor: Vec3,
+    ) -> Box<dyn Texture> {
+        let Some(texture) = texture else {
+            return Box::new(SolidColor { color: factor });
+        };
+        if texture.tex_coord != 0 {
+            self.warnings.push(format!(
+                "material '{}': texture coordinate sets other than 0 are not supported",
+                name
+            ));
+            return Box::new(SolidColor { color: factor });
+        }
+        if texture.transformed {
+            self.warnings.push(format!(
+                "material '{}': {} texture transform is not supported",
+                name, input
+            ));
+        }
+        let source = self.textures.get(texture.index).copied();
+        let image = source.and_then(|(image, wrap_u, wrap_v)| {
+            Some(
+                self.convert_image(image, srgb, map)?
+                    .with_wrap(wrap_u, wrap_v),
+            )
+        });
+        match image {
+            Some(texture) => Box::new(texture),
+            None => {
+                self.warnings.push(format!(
+                    "material '{}': unsupported {} image format",
+                    name, input
+                ));
+                Box::new(SolidColor { color: factor })
+            }
+        }
+    }
+
+    // decodes an image into rgb in [0, 1], linear if it is sRGB encoded, and maps every texel
+    fn convert_image(
+        &self,
+        index: usize,
+        srgb: bool,
+        map: impl Fn(Vec3) -> Vec3,
+    ) -> Option<ImageTexture> {
+        let image = self.images.get(index)?;
+        let (channels, bytes_per_channel) = match image.format {
+            Format::R8 => (1, 1),
+            Format::R8G8 => (2, 1),
+            Format::R8G8B8 => (3, 1),
+            Format::R8G8B8A8 => (4, 1),
+            Format::R16 => (1, 2),
+            Format::R16G16 => (2, 2),
+            Format::R16G16B16 => (3, 2),
+            Format::R16G16B16A16 => (4, 2),
+            _ => return None,
+        };
+        let channel = |texel: &[u8], i: usize| -> f64 {
+            let i = i.min(channels - 1).min(2);
+            let value = if bytes_per_channel == 1 {
+                texel[i] as f64 / 255.0
+            } else {
+                u16::from_le_bytes([texel[2 * i], texel[2 * i + 1]]) as f64 / 65535.0
+            };
+            if srgb {
+                srgb_to_linear(value)
+            } else {
+                value
+            }
+        };
+        let pixels = image
+            .pixels
+            .chunks_exact(channels * bytes_per_channel)
+            .map(|texel| {
+                // gray images replicate their single channel
+                let color = if channels < 3 {
+                    let gray = channel(texel, 0);
+                    Vec3::new(gray, gray, gray)
+                } else {
+                    Vec3::new(channel(texel, 0), channel(texel, 1), channel(texel, 2))
+                };
+                map(color)
+            })
+            .collect();
+        Some(ImageTexture::new(
+            image.width as usize,
+            image.height as usize,
+            pixels,
+        ))
+    }
+}
+
+// a material input's reference to a glTF texture
+#[derive(Clone, Copy)]
+struct TextureRef {
+    index: usize,
+    tex_coord: u32,
+    transformed: bool, // by KHR_texture_transform
+}
+
+// glTF samplers repeat unless they say otherwise
+fn wrap(mode: WrappingMode) -> Wrap {
+    match mode {
+        WrappingMode::Repeat => Wrap::Repeat,
+        WrappingMode::MirroredRepeat => Wrap::MirroredRepeat,
+        WrappingMode::ClampToEdge => Wrap::Clamp,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{hittable::Hittable, ray::Ray};
+
+    // one triangle in the xy plane, translated by (0, 0, -5) and seen by a camera at the origin
+    fn write_files(directory: &Path) -> std::path::PathBuf {
+        let mut buffer: Vec<u8> = vec![];
+        for value in [0.0f32, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0] {
+            buffer.extend(value.to_le_bytes());
+        }
+        for index in [0u16, 1, 2, 0] {
+            buffer.extend(index.to_le_bytes());
+        }
+        std::fs::write(directory.join("triangle.bin"), &buffer).unwrap();
+
+        let gltf = r#"{
+            "asset": { "version": "2.0" },
+            "scene": 0,
+            "scenes": [ { "nodes": [0, 1] } ],
+            "nodes": [
+                { "mesh": 0, "translation": [0.0, 0.0, -5.0] },
+                { "camera": 0 }
+            ],
+            "cameras": [ { "type": "perspective", "perspective": { "yfov": 0.8, "znear": 0.1 } } ],
+            "materials": [ {
+                "name": "chrome",
+                "pbrMetallicRoughness": { "metallicFactor": 1.0, "roughnessFactor": 0.2 },
+                "normalTexture": { "index": 0 },
+                "emissiveFactor": [1.0, 0.5, 0.0],
+                "extensions": {
+                    "KHR_materials_emissive_strength": { "emissiveStrength": 4.0 }
+                }
+            } ],
+            "extensionsUsed": [ "KHR_materials_emissive_strength" ],
+            "textures": [ { "source": 0 } ],
+            "images": [ { "uri": "missing.png" } ],
+            "meshes": [ { "primitives": [ {
+                "attributes": { "POSITION": 0 }, "indices": 1, "material": 0
+            } ] } ],
+            "buffers": [ { "uri": "triangle.bin", "byteLength": 44 } ],
+            "bufferViews": [
+                { "buffer": 0, "byteOffset": 0, "byteLength": 36 },
+                { "buffer": 0, "byteOffset": 36, "byteLength": 6 }
+            ],
+            "accessors": [
+                { "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3",
+                  "min": [0.0, 0.0, 0.0], "max": [1.0, 1.0, 0.0] },
+                { "bufferView": 1, "componentType": 5123, "count": 3, "type": "SCALAR" }
+            ]
+        }"#;
+        let path = directory.join("triangle.gltf");
+        std::fs::write(&path, gltf).unwrap();
+        path
+    }
+
+    #[test]
+    fn imports_transformed_mesh_and_warns() {
+        let directory = std::env::temp_dir().join("lib-raytracing-gltf-test");
+        std::fs::create_dir_all(&directory).unwrap();
+        let path = write_files(&directory);
+        // the referenced image does not exist, gltf::import fails on that
+        assert!(load_gltf(&path, 1.0).is_err());
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        std::fs::write(&path, content.replace("missing.png", "triangle.png")).unwrap();
+        image::RgbImage::new(1, 1)
+            .save(directory.join("triangle.png"))
+            .unwrap();
+        let scene = load_gltf(&path, 1.0).unwrap();
+        std::fs::remove_dir_all(&directory).unwrap();
+
+        assert_eq!(scene.objects.objects.len(), 1);
+        let ray = Ray::new(Vec3::new(0.25, 0.25, 0.0), Vec3::new(0.0, 0.0, -1.0));
+        let record = scene.objects.hit(&ray, 0.001, f64::MAX).unwrap();
+        assert!((record.distance - 5.0).abs() < 1e-9);
+        let emitted = record.material.emitted(&ray, &record);
+        assert!((emitted - Vec3::new(4.0, 2.0, 0.0)).length() < 1e-6);
+        assert!(scene
+            .warnings
+            .iter()
+            .any(|warning| warning.contains("normal texture")));
+        assert!(!scene
+            .warnings
+            .iter()
+            .any(|warning| warning.contains("default camera")));
+    }
+
+    #[test]
+    fn mirrored_nodes_keep_triangles_facing_out() {
+        let directory = std::env::temp_dir().join("lib-raytracing-gltf-mirror-test");
+        std::fs::create_dir_all(&directory).unwrap();
+        let path = write_files(&directory);
+        let content = std::fs::read_to_string(&path)
+            .unwrap()
+            .replace("missing.png", "triangle.png")
+            .replace(
+                "[0.0, 0.0, -5.0] }",
+                "[0.0, 0.0, -5.0], \"scale\": [-1.0, 1.0, 1.0] }",
+            );
+        std::fs::write(&path, content).unwrap();
+        image::RgbImage::new(1, 1)
+            .save(directory.join("triangle.png"))
+            .unwrap();
+        let scene = load_gltf(&path, 1.0).unwrap();
+        std::fs::remove_dir_all(&directory).unwrap();
+
+        // the triangle faces the camera before mirroring, and has to after it as well
+        let ray = Ray::new(Vec3::new(-0.25, 0.25, 0.0), Vec3::new(0.0, 0.0, -1.0));
+        let record = scene.objects.hit(&ray, 0.001, f64::MAX).unwrap();
+        assert!(record.front_face);
+        assert!((record.normal - Vec3::new(0.0, 0.0, 1.0)).length() < 1e-9);
+    }
+
+    #[test]
+    fn warns_about_texture_transforms() {
+        let directory = std::env::temp_dir().join("lib-raytracing-gltf-transform-test");
+        std::fs::create_dir_all(&directory).unwrap();
+        let path = write_files(&directory);
+        let content = std::fs::read_to_string(&path)
+            .unwrap()
+            .replace("missing.png", "triangle.png")
+            .replace(
+                r#""metallicFactor": 1.0, "roughnessFactor": 0.2 },"#,
+                r#""metallicFactor": 0.0, "baseColorTexture": { "index": 0, "extensions": {
+                    "KHR_texture_transform": { "scale": [2.0, 2.0] }
+                } } },"#,
+            )
+            .replace(
+                r#""textures": [ { "source": 0 } ],"#,
+                r#""textures": [ { "source": 0, "sampler": 0 } ],
+                "samplers": [ { "wrapS": 33648, "wrapT": 33071 } ],"#,
+            );
+        std::fs::write(&path, content).unwrap();
+        image::RgbImage::new(1, 1)
+            .save(directory.join("triangle.png"))
+            .unwrap();
+        let scene = load_gltf(&path, 1.0).unwrap();
+        std::fs::remove_dir_all(&directory).unwrap();
+
+        assert!(scene
+            .warnings
+            .iter()
+            .any(|warning| warning.contains("texture transform")));
+        assert!(!scene
+            .warnings
+            .iter()
+            .any(|warning| warning.contains("image format")));
+    }
+}
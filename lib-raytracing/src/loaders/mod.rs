@@ -0,0 +1,38 @@
+use std::{error::Error, fmt, io};
+
+pub mod gltf;
+pub mod obj;
+pub mod ply;
+pub mod stl;
+pub mod voxel_grid;
+
+#[derive(Debug)]
+pub enum LoadError {
+    Io(io::Error),
+    Image(image::ImageError),
+    Parse(String), // the file is readable but its content is malformed or unsupported
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::Io(error) => write!(f, "could not read file: {}", error),
+            LoadError::Image(error) => write!(f, "could not load image: {}", error),
+            LoadError::Parse(message) => write!(f, "could not parse file: {}", message),
+        }
+    }
+}
+
+impl Error for LoadError {}
+
+impl From<io::Error> for LoadError {
+    fn from(error: io::Error) -> Self {
+        LoadError::Io(error)
+    }
+}
+
+impl From<image::ImageError> for LoadError {
+    fn from(error: image::ImageError) -> Self {
+        LoadError::Image(error)
+    }
+}
@@ -0,0 +1,260 @@
+use std::path::Path;
+
+use crate::{
+    material::Material,
+    materials::principled::{constant, Principled},
+    objects::triangle_mesh::{MeshFace, TriangleMesh},
+    texture::Texture,
+    textures::{image_texture::ImageTexture, solid_color::SolidColor},
+    vec3::Vec3,
+};
+
+use super::LoadError;
+
+// MTL illumination models that mean "transparent with refraction"
+const REFRACTIVE_ILLUMINATION_MODELS: [u8; 4] = [4, 6, 7, 9];
+const REFLECTIVE_ILLUMINATION_MODEL: u8 = 3;
+
+// loads all objects of a Wavefront .obj file (and the .mtl files it references) into one
+// mesh, polygons are triangulated
+pub fn load_obj<P: AsRef<Path>>(path: P) -> Result<TriangleMesh, LoadError> {
+    let path = path.as_ref();
+    let options = tobj::LoadOptions {
+        triangulate: true,
+        ignore_points: true,
+        ignore_lines: true,
+        ..Default::default()
+    };
+    let (models, obj_materials) = tobj::load_obj(path, &options).map_err(tobj_error)?;
+    // a missing or broken .mtl file leaves the faces with the default material
+    let obj_materials = obj_materials.unwrap_or_default();
+
+    let directory = path.parent().unwrap_or_else(|| Path::new(""));
+    let mut materials = obj_materials
+        .iter()
+        .map(|material| {
+            convert_material(material, directory)
+                .map(|principled| Box::new(principled) as Box<dyn Material>)
+        })
+        .collect::<Result<Vec<Box<dyn Material>>, LoadError>>()?;
+    let default_material = materials.len();
+    materials.push(Box::<Principled>::default());
+
+    let mut positions = vec![];
+    let mut normals = vec![];
+    let mut uvs = vec![];
+    let mut faces = vec![];
+    for model in models {
+        let mesh = model.mesh;
+        let position_offset = positions.len();
+        let normal_offset = normals.len();
+        let uv_offset = uvs.len();
+        positions.extend(
+            mesh.positions
+                .chunks_exact(3)
+                .map(|p| Vec3::new(p[0] as f64, p[1] as f64, p[2] as f64)),
+        );
+        normals.extend(
+            mesh.normals
+                .chunks_exact(3)
+                .map(|n| Vec3::new(n[0] as f64, n[1] as f64, n[2] as f64)),
+        );
+        uvs.extend(
+            mesh.texcoords
+                .chunks_exact(2)
+                .map(|uv| (uv[0] as f64, uv[1] as f64)),
+        );
+
+        let material = match mesh.material_id {
+            Some(id) if id < default_material => id,
+            _ => default_material,
+        };
+        for face in 0..mesh.indices.len() / 3 {
+            let corners = |buffer: &[u32], offset: usize| {
+                buffer
+                    .get(3 * face..3 * face + 3)
+                    .map(|corner| [0, 1, 2].map(|i| corner[i] as usize + offset))
+            };
+            faces.push(MeshFace {
+                positions: corners(&mesh.indices, position_offset).unwrap(),
+                normals: corners(&mesh.normal_indices, normal_offset),
+                uvs: corners(&mesh.texcoord_indices, uv_offset),
+                material,
+            });
+        }
+    }
+
+    if let Some(face) = faces.iter().find(|face| {
+        face.positions.iter().any(|i| *i >= positions.len())
+            || face.normals.iter().flatten().any(|i| *i >= normals.len())
+            || face.uvs.iter().flatten().any(|i| *i >= uvs.len())
+    }) {
+        return Err(LoadError::Parse(format!(
+            "face {:?} references a missing vertex",
+            face.positions
+        )));
+    }
+
+    Ok(TriangleMesh::new(positions, normals, uvs, faces, materials))
+}
+
+// map the phong style MTL parameters onto the closest of our materials
+fn convert_material(material: &tobj::Material, directory: &Path) -> Result<Principled, LoadError> {
+    let to_vec3 = |color: [f32; 3]| Vec3::new(color[0] as f64, color[1] as f64, color[2] as f64);
+    let diffuse = material
+        .diffuse
+        .map(to_vec3)
+        .unwrap_or(Vec3::new(0.8, 0.8, 0.8));
+    let specular = material.specular.map(to_vec3).unwrap_or_default();
+    let illumination_model = material.illumination_model.unwrap_or(2);
+
+    // the PBR extension of MTL: Pr, Pm, Ps, Pc and Pcr with their maps, and map_Ke
+    let parameter = |key: &str| {
+        let values = material
+            .unknown_param
+            .get(key)?
+            .split_whitespace()
+            .map(|value| value.parse::<f64>().ok())
+            .collect::<Option<Vec<f64>>>()?;
+        match values[..] {
+            [value] => Some(Vec3::new(value, value, value)),
+            [x, y, z] => Some(Vec3::new(x, y, z)),
+            _ => None,
+        }
+    };
+    let input = |key: &str, map: &str, default: Vec3| -> Result<Box<dyn Texture>, LoadError> {
+        match material.unknown_param.get(map) {
+            Some(file) => Ok(Box::new(ImageTexture::load_data(directory.join(file))?)),
+            None => Ok(Box::new(SolidColor {
+                color: parameter(key).unwrap_or(default),
+            })),
+        }
+    };
+    let gray = |value: f64| Vec3::new(value, value, value);
+
+    let mut principled = Principled {
+        base_color: match &material.diffuse_texture {
+            Some(texture) => Box::new(ImageTexture::load(directory.join(texture))?),
+            None => Box::new(SolidColor { color: diffuse }),
+        },
+        sheen: input("Ps", "map_Ps", gray(0.0))?,
+        clearcoat: input("Pc", "map_Pc", gray(0.0))?,
+        clearcoat_roughness: input("Pcr", "map_Pcr", gray(0.03))?,
+        emission: match material.unknown_param.get("map_Ke") {
+            Some(file) => Box::new(ImageTexture::load(directory.join(file))?),
+            None => Box::new(SolidColor {
+                color: material.emissive.map(to_vec3).unwrap_or_default(),
+            }),
+        },
+        ..Default::default()
+    };
+
+    // without Pr, approximate the roughness of a blinn-phong lobe with exponent Ns
+    let shininess = material.shininess.unwrap_or(0.0).max(0.0) as f64;
+    let blinn_phong_roughness = (2.0 / (shininess + 2.0)).sqrt().min(1.0);
+    principled.roughness = input("Pr", "map_Pr", gray(blinn_phong_roughness))?;
+
+    let transparent = material.dissolve.is_some_and(|dissolve| dissolve < 1.0)
+        || REFRACTIVE_ILLUMINATION_MODELS.contains(&illumination_model);
+    let pbr = ["Pm", "map_Pm"]
+        .iter()
+        .any(|key| material.unknown_param.contains_key(*key));
+    if transparent {
+        principled.transmission = constant(1.0);
+        principled.index_of_refraction = material
+            .optical_density
+            .filter(|density| *density >= 1.0)
+            .map(|density| density as f64)
+            .unwrap_or(1.5);
+    } else if pbr {
+        principled.metallic = input("Pm", "map_Pm", gray(0.0))?;
+    } else if illumination_model == REFLECTIVE_ILLUMINATION_MODEL
+        || specular.luminance() > diffuse.luminance()
+    {
+        // classic materials describe metals by their specular color
+        principled.base_color = Box::new(SolidColor { color: specular });
+        principled.metallic = constant(1.0);
+    }
+    Ok(principled)
+}
+
+fn tobj_error(error: tobj::LoadError) -> LoadError {
+    match error {
+        tobj::LoadError::OpenFileFailed | tobj::LoadError::ReadError => {
+            LoadError::Io(std::io::Error::other(error.to_string()))
+        }
+        error => LoadError::Parse(error.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{hittable::Hittable, ray::Ray};
+
+    fn write_files(name: &str, obj: &str, mtl: &str) -> std::path::PathBuf {
+        let directory = std::env::temp_dir().join(name);
+        std::fs::create_dir_all(&directory).unwrap();
+        std::fs::write(directory.join("scene.mtl"), mtl).unwrap();
+        let path = directory.join("scene.obj");
+        std::fs::write(&path, obj).unwrap();
+        path
+    }
+
+    #[test]
+    fn loads_quad_with_materials() {
+        let path = write_files(
+            "lib-raytracing-obj-test",
+            "mtllib scene.mtl\n\
+             v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\n\
+             vt 0 0\nvt 1 0\nvt 1 1\nvt 0 1\n\
+             vn 0 0 1\n\
+             usemtl glass\n\
+             f 1/1/1 2/2/1 3/3/1 4/4/1\n",
+            "newmtl glass\nKd 1 1 1\nd 0.5\nNi 1.33\n",
+        );
+        let mesh = load_obj(&path).unwrap();
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+
+        assert_eq!(mesh.face_count(), 2);
+        assert_eq!(mesh.material_count(), 2); // glass and the default
+        let ray = Ray::new(Vec3::new(0.5, 0.25, 1.0), Vec3::new(0.0, 0.0, -1.0));
+        let record = mesh.hit(&ray, 0.001, f64::MAX).unwrap();
+        assert!((record.u - 0.5).abs() < 1e-6 && (record.v - 0.25).abs() < 1e-6);
+        assert!(record.front_face);
+    }
+
+    #[test]
+    fn reads_pbr_parameters() {
+        let path = write_files(
+            "lib-raytracing-obj-pbr-test",
+            "mtllib scene.mtl\n\
+             v 0 0 0\nv 1 0 0\nv 0 1 0\n\
+             usemtl lamp\n\
+             f 1 2 3\n",
+            "newmtl lamp\nKd 1 0 0\nKe 0 3 0\nPr 0.4\nPm 0.5\nPc 1\n",
+        );
+        let mesh = load_obj(&path).unwrap();
+        let directory = path.parent().unwrap();
+        let (materials, _) = tobj::load_mtl(directory.join("scene.mtl")).unwrap();
+        let principled = convert_material(&materials[0], directory).unwrap();
+        std::fs::remove_dir_all(directory).unwrap();
+
+        let value = |texture: &dyn Texture| texture.value(0.0, 0.0, &Vec3::default()).x;
+        assert_eq!(value(principled.roughness.as_ref()), 0.4);
+        assert_eq!(value(principled.metallic.as_ref()), 0.5);
+        assert_eq!(value(principled.clearcoat.as_ref()), 1.0);
+
+        let ray = Ray::new(Vec3::new(0.25, 0.25, 1.0), Vec3::new(0.0, 0.0, -1.0));
+        let record = mesh.hit(&ray, 0.001, f64::MAX).unwrap();
+        assert_eq!(
+            record.material.emitted(&ray, &record),
+            Vec3::new(0.0, 3.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn missing_file_is_an_error() {
+        assert!(load_obj("/does/not/exist.obj").is_err());
+    }
+}
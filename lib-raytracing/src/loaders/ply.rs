@@ -0,0 +1,451 @@
+use std::path::Path;
+
+use crate::{
+    material::Material,
+    objects::triangle_mesh::{MeshFace, TriangleMesh},
+    textures::image_texture::srgb_to_linear,
+    vec3::Vec3,
+};
+
+use super::LoadError;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Format {
+    Ascii,
+    BinaryLittleEndian,
+    BinaryBigEndian,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ScalarType {
+    Int8,
+    UInt8,
+    Int16,
+    UInt16,
+    Int32,
+    UInt32,
+    Float32,
+    Float64,
+}
+
+impl ScalarType {
+    fn parse(name: &str) -> Result<ScalarType, LoadError> {
+        match name {
+            "char" | "int8" => Ok(ScalarType::Int8),
+            "uchar" | "uint8" => Ok(ScalarType::UInt8),
+            "short" | "int16" => Ok(ScalarType::Int16),
+            "ushort" | "uint16" => Ok(ScalarType::UInt16),
+            "int" | "int32" => Ok(ScalarType::Int32),
+            "uint" | "uint32" => Ok(ScalarType::UInt32),
+            "float" | "float32" => Ok(ScalarType::Float32),
+            "double" | "float64" => Ok(ScalarType::Float64),
+            _ => Err(LoadError::Parse(format!("unknown ply type {}", name))),
+        }
+    }
+
+    fn size(&self) -> usize {
+        match self {
+            ScalarType::Int8 | ScalarType::UInt8 => 1,
+            ScalarType::Int16 | ScalarType::UInt16 => 2,
+            ScalarType::Int32 | ScalarType::UInt32 | ScalarType::Float32 => 4,
+            ScalarType::Float64 => 8,
+        }
+    }
+
+    fn is_float(&self) -> bool {
+        matches!(self, ScalarType::Float32 | ScalarType::Float64)
+    }
+}
+
+#[derive(Debug)]
+enum Property {
+    Scalar(String, ScalarType),
+    List(String, ScalarType, ScalarType), // name, type of the length, type of the items
+}
+
+#[derive(Debug)]
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<Property>,
+}
+
+// reads the values of the body one after another, whatever the encoding
+struct BodyReader<'a> {
+    format: Format,
+    data: &'a [u8],
+    position: usize,
+}
+
+impl BodyReader<'_> {
+    fn read(&mut self, scalar_type: ScalarType) -> Result<f64, LoadError> {
+        if self.format == Format::Ascii {
+            return self.read_ascii();
+        }
+        let bytes = self
+            .data
+            .get(self.position..self.position + scalar_type.size())
+            .ok_or_else(|| LoadError::Parse("ply body is truncated".to_string()))?;
+        self.position += scalar_type.size();
+
+        macro_rules! decode {
+            ($type:ty) => {{
+                let bytes = bytes.try_into().unwrap();
+                (if self.format == Format::BinaryLittleEndian {
+                    <$type>::from_le_bytes(bytes)
+                } else {
+                    <$type>::from_be_bytes(bytes)
+                }) as f64
+            }};
+        }
+        Ok(match scalar_type {
+            ScalarType::Int8 => decode!(i8),
+            ScalarType::UInt8 => decode!(u8),
+            ScalarType::Int16 => decode!(i16),
+            ScalarType::UInt16 => decode!(u16),
+            ScalarType::Int32 => decode!(i32),
+            ScalarType::UInt32 => decode!(u32),
+            ScalarType::Float32 => decode!(f32),
+            ScalarType::Float64 => decode!(f64),
+        })
+    }
+
+    fn read_ascii(&mut self) -> Result<f64, LoadError> {
+        let rest = &self.data[self.position..];
+        let start = rest
+            .iter()
+            .position(|byte| !byte.is_ascii_whitespace())
+            .ok_or_else(|| LoadError::Parse("ply body is truncated".to_string()))?;
+        let length = rest[start..]
+            .iter()
+            .position(|byte| byte.is_ascii_whitespace())
+            .unwrap_or(rest.len() - start);
+        self.position += start + length;
+        let token = std::str::from_utf8(&rest[start..start + length]).unwrap_or_default();
+        token
+            .parse()
+            .map_err(|_| LoadError::Parse(format!("invalid ply value {}", token)))
+    }
+}
+
+// loads the vertex and face elements of a Stanford .ply file (ascii or binary) into a mesh
+// using one material for all faces. Polygons are triangulated as fans, vertex colors become
+// available to textures::vertex_color::VertexColor
+pub fn load_ply<P: AsRef<Path>>(
+    path: P,
+    material: Box<dyn Material>,
+) -> Result<TriangleMesh, LoadError> {
+    parse_ply(&std::fs::read(path)?, material)
+}
+
+fn parse_ply(data: &[u8], material: Box<dyn Material>) -> Result<TriangleMesh, LoadError> {
+    const END_OF_HEADER: &[u8] = b"end_header";
+    let header_end = data
+        .windows(END_OF_HEADER.len())
+        .position(|window| window == END_OF_HEADER)
+        .ok_or_else(|| LoadError::Parse("ply header has no end_header".to_string()))?;
+    let body_start = data[header_end..]
+        .iter()
+        .position(|byte| *byte == b'\n')
+        .map_or(data.len(), |newline| header_end + newline + 1);
+    let header = std::str::from_utf8(&data[..header_end])
+        .map_err(|_| LoadError::Parse("ply header is not text".to_string()))?;
+    let (format, elements) = parse_header(header)?;
+
+    let mut reader = BodyReader {
+        format,
+        data: &data[body_start..],
+        position: 0,
+    };
+    let mut positions = vec![];
+    let mut normals = vec![];
+    let mut uvs = vec![];
+    let mut colors = vec![];
+    let mut faces = vec![];
+    for element in &elements {
+        for _ in 0..element.count {
+            match element.name.as_str() {
+                "vertex" => {
+                    let vertex = read_vertex(&mut reader, &element.properties)?;
+                    positions.push(vertex.position);
+                    normals.extend(vertex.normal);
+                    uvs.extend(vertex.uv);
+                    colors.extend(vertex.color);
+                }
+                "face" => read_face(&mut reader, &element.properties, &mut faces)?,
+                // unknown elements (edges, materials, ...) are read and dropped
+                _ => {
+                    for property in &element.properties {
+                        read_property(&mut reader, property)?;
+                    }
+                }
+            }
+        }
+    }
+
+    // attributes only count when every vertex has them
+    let has_normals = normals.len() == positions.len();
+    let has_uvs = uvs.len() == positions.len();
+    for face in &mut faces {
+        if face.positions.iter().any(|i| *i >= positions.len()) {
+            return Err(LoadError::Parse(format!(
+                "face {:?} references a missing vertex",
+                face.positions
+            )));
+        }
+        face.normals = has_normals.then_some(face.positions);
+        face.uvs = has_uvs.then_some(face.positions);
+    }
+
+    let has_colors = colors.len() == positions.len();
+    let mesh = TriangleMesh::new(
+        positions,
+        if has_normals { normals } else { vec![] },
+        if has_uvs { uvs } else { vec![] },
+        faces,
+        vec![material],
+    );
+    Ok(if has_colors {
+        mesh.with_vertex_colors(colors)
+    } else {
+        mesh
+    })
+}
+
+fn parse_header(header: &str) -> Result<(Format, Vec<Element>), LoadError> {
+    let mut lines = header.lines().map(str::trim);
+    if lines.next() != Some("ply") {
+        return Err(LoadError::Parse("not a ply file".to_string()));
+    }
+
+    let mut format = None;
+    let mut elements: Vec<Element> = vec![];
+    for line in lines {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            ["format", "ascii", _] => format = Some(Format::Ascii),
+            ["format", "binary_little_endian", _] => format = Some(Format::BinaryLittleEndian),
+            ["format", "binary_big_endian", _] => format = Some(Format::BinaryBigEndian),
+            ["element", name, count] => elements.push(Element {
+                name: name.to_string(),
+                count: count
+                    .parse()
+                    .map_err(|_| LoadError::Parse(format!("invalid element count {}", count)))?,
+                properties: vec![],
+            }),
+            ["property", "list", count_type, item_type, name] => {
+                let property = Property::List(
+                    name.to_string(),
+                    ScalarType::parse(count_type)?,
+                    ScalarType::parse(item_type)?,
+                );
+                current_element(&mut elements)?.properties.push(property);
+            }
+            ["property", scalar_type, name] => {
+                let property = Property::Scalar(name.to_string(), ScalarType::parse(scalar_type)?);
+                current_element(&mut elements)?.properties.push(property);
+            }
+            ["comment", ..] | ["obj_info", ..] | [] => {}
+            _ => {
+                return Err(LoadError::Parse(format!(
+                    "invalid ply header line {}",
+                    line
+                )))
+            }
+        }
+    }
+
+    let format = format.ok_or_else(|| LoadError::Parse("ply header has no format".to_string()))?;
+    Ok((format, elements))
+}
+
+fn current_element(elements: &mut [Element]) -> Result<&mut Element, LoadError> {
+    elements
+        .last_mut()
+        .ok_or_else(|| LoadError::Parse("ply property outside of an element".to_string()))
+}
+
+fn read_property(reader: &mut BodyReader, property: &Property) -> Result<Vec<f64>, LoadError> {
+    match property {
+        Property::Scalar(_, scalar_type) => Ok(vec![reader.read(*scalar_type)?]),
+        Property::List(_, count_type, item_type) => {
+            let count = reader.read(*count_type)? as usize;
+            (0..count).map(|_| reader.read(*item_type)).collect()
+        }
+    }
+}
+
+struct Vertex {
+    position: Vec3,
+    normal: Option<Vec3>,
+    uv: Option<(f64, f64)>,
+    color: Option<Vec3>,
+}
+
+fn read_vertex(reader: &mut BodyReader, properties: &[Property]) -> Result<Vertex, LoadError> {
+    let mut position = [None; 3];
+    let mut normal = [None; 3];
+    let mut uv = [None; 2];
+    let mut color = [None; 3];
+    for property in properties {
+        let value = read_property(reader, property)?;
+        let Property::Scalar(name, scalar_type) = property else {
+            continue;
+        };
+        let value = value[0];
+        let slot = match name.as_str() {
+            "x" => &mut position[0],
+            "y" => &mut position[1],
+            "z" => &mut position[2],
+            "nx" => &mut normal[0],
+            "ny" => &mut normal[1],
+            "nz" => &mut normal[2],
+            "u" | "s" | "texture_u" | "texture_s" => &mut uv[0],
+            "v" | "t" | "texture_v" | "texture_t" => &mut uv[1],
+            "red" | "green" | "blue" => {
+                // integer colors are 8 bit sRGB, float colors are already linear
+                let channel = if scalar_type.is_float() {
+                    value
+                } else {
+                    srgb_to_linear(value / 255.0)
+                };
+                let index = ["red", "green", "blue"].iter().position(|c| c == name);
+                color[index.unwrap()] = Some(channel);
+                continue;
+            }
+            _ => continue,
+        };
+        *slot = Some(value);
+    }
+
+    let [Some(x), Some(y), Some(z)] = position else {
+        return Err(LoadError::Parse("ply vertex without position".to_string()));
+    };
+    Ok(Vertex {
+        position: Vec3::new(x, y, z),
+        normal: match normal {
+            [Some(x), Some(y), Some(z)] => Some(Vec3::new(x, y, z)),
+            _ => None,
+        },
+        uv: match uv {
+            [Some(u), Some(v)] => Some((u, v)),
+            _ => None,
+        },
+        color: match color {
+            [Some(r), Some(g), Some(b)] => Some(Vec3::new(r, g, b)),
+            _ => None,
+        },
+    })
+}
+
+fn read_face(
+    reader: &mut BodyReader,
+    properties: &[Property],
+    faces: &mut Vec<MeshFace>,
+) -> Result<(), LoadError> {
+    for property in properties {
+        let values = read_property(reader, property)?;
+        let Property::List(name, _, _) = property else {
+            continue;
+        };
+        if name != "vertex_indices" && name != "vertex_index" {
+            continue;
+        }
+        let indices = values
+            .into_iter()
+            .map(|i| {
+                if i < 0.0 || i.fract() != 0.0 {
+                    return Err(LoadError::Parse(format!("invalid vertex index {}", i)));
+                }
+                Ok(i as usize)
+            })
+            .collect::<Result<Vec<usize>, LoadError>>()?;
+        for corner in 1..indices.len().saturating_sub(1) {
+            faces.push(MeshFace {
+                positions: [indices[0], indices[corner], indices[corner + 1]],
+                normals: None,
+                uvs: None,
+                material: 0,
+            });
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        hittable::{test_material, Hittable},
+        ray::Ray,
+    };
+
+    fn hit_quad_center(mesh: &TriangleMesh) -> Option<Vec3> {
+        let ray = Ray::new(Vec3::new(0.25, 0.25, 1.0), Vec3::new(0.0, 0.0, -1.0));
+        mesh.hit(&ray, 0.001, f64::MAX).unwrap().vertex_color
+    }
+
+    #[test]
+    fn loads_ascii_quad_with_colors() {
+        let ply = "ply\n\
+                   format ascii 1.0\n\
+                   comment unit quad\n\
+                   element vertex 4\n\
+                   property float x\nproperty float y\nproperty float z\n\
+                   property uchar red\nproperty uchar green\nproperty uchar blue\n\
+                   element face 1\n\
+                   property list uchar int vertex_indices\n\
+                   end_header\n\
+                   0 0 0 255 0 0\n1 0 0 255 0 0\n1 1 0 255 0 0\n0 1 0 255 0 0\n\
+                   4 0 1 2 3\n";
+        let mesh = parse_ply(ply.as_bytes(), test_material()).unwrap();
+        assert_eq!(mesh.face_count(), 2);
+        let color = hit_quad_center(&mesh).unwrap();
+        assert!((color - Vec3::new(1.0, 0.0, 0.0)).length() < 1e-9);
+    }
+
+    #[test]
+    fn loads_binary_big_endian() {
+        let mut ply = b"ply\n\
+                        format binary_big_endian 1.0\n\
+                        element vertex 3\n\
+                        property double x\nproperty double y\nproperty double z\n\
+                        element face 1\n\
+                        property list uchar uint vertex_indices\n\
+                        end_header\n"
+            .to_vec();
+        for vertex in [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]] {
+            for coordinate in vertex {
+                ply.extend(f64::to_be_bytes(coordinate));
+            }
+        }
+        ply.push(3);
+        for index in [0u32, 1, 2] {
+            ply.extend(index.to_be_bytes());
+        }
+
+        let mesh = parse_ply(&ply, test_material()).unwrap();
+        assert_eq!(mesh.face_count(), 1);
+        assert_eq!(hit_quad_center(&mesh), None);
+    }
+
+    #[test]
+    fn rejects_missing_vertices() {
+        let ply = "ply\nformat ascii 1.0\nelement vertex 1\nproperty float x\n\
+                   property float y\nproperty float z\nelement face 1\n\
+                   property list uchar int vertex_indices\nend_header\n0 0 0\n3 0 1 2\n";
+        assert!(parse_ply(ply.as_bytes(), test_material()).is_err());
+    }
+
+    #[test]
+    fn rejects_negative_indices() {
+        let ply = "ply\nformat ascii 1.0\nelement vertex 3\nproperty float x\n\
+                   property float y\nproperty float z\nelement face 1\n\
+                   property list uchar int vertex_indices\nend_header\n\
+                   0 0 0\n1 0 0\n0 1 0\n3 0 1 -2\n";
+        assert!(matches!(
+            parse_ply(ply.as_bytes(), test_material()),
+            Err(LoadError::Parse(_))
+        ));
+    }
+}
@@ -0,0 +1,162 @@
+use std::{collections::HashMap, path::Path};
+
+use crate::{
+    material::Material,
+    objects::triangle_mesh::{MeshFace, TriangleMesh},
+    vec3::Vec3,
+};
+
+use super::LoadError;
+
+const BINARY_HEADER_SIZE: usize = 84; // 80 byte comment and the triangle count
+const BINARY_TRIANGLE_SIZE: usize = 50; // normal, three corners and an attribute word
+
+// loads a binary or ascii .stl file into a mesh using one material for all faces.
+// Corners at the same position are merged, the stored facet normals are ignored in favour
+// of the winding order
+pub fn load_stl<P: AsRef<Path>>(
+    path: P,
+    material: Box<dyn Material>,
+) -> Result<TriangleMesh, LoadError> {
+    parse_stl(&std::fs::read(path)?, material)
+}
+
+fn parse_stl(data: &[u8], material: Box<dyn Material>) -> Result<TriangleMesh, LoadError> {
+    // ascii files start with "solid" too in the wild, so the size decides
+    let triangles = if is_binary(data) {
+        parse_binary(data)
+    } else {
+        parse_ascii(data)?
+    };
+
+    let mut positions = vec![];
+    let mut indices = HashMap::new();
+    let faces = triangles
+        .into_iter()
+        .map(|triangle| MeshFace {
+            positions: triangle.map(|corner| {
+                let key = [corner.x, corner.y, corner.z].map(f64::to_bits);
+                *indices.entry(key).or_insert_with(|| {
+                    positions.push(corner);
+                    positions.len() - 1
+                })
+            }),
+            normals: None,
+            uvs: None,
+            material: 0,
+        })
+        .collect();
+
+    Ok(TriangleMesh::new(
+        positions,
+        vec![],
+        vec![],
+        faces,
+        vec![material],
+    ))
+}
+
+fn is_binary(data: &[u8]) -> bool {
+    data.len() >= BINARY_HEADER_SIZE && {
+        let count = u32::from_le_bytes(data[80..84].try_into().unwrap()) as usize;
+        data.len() == BINARY_HEADER_SIZE + count * BINARY_TRIANGLE_SIZE
+    }
+}
+
+fn parse_binary(data: &[u8]) -> Vec<[Vec3; 3]> {
+    let read =
+        |offset: usize| f32::from_le_bytes(data[offset..offset + 4].try_into().unwrap()) as f64;
+    data[BINARY_HEADER_SIZE..]
+        .chunks_exact(BINARY_TRIANGLE_SIZE)
+        .enumerate()
+        .map(|(index, _)| {
+            // skip the 12 bytes of the facet normal
+            let start = BINARY_HEADER_SIZE + index * BINARY_TRIANGLE_SIZE + 12;
+            [0, 1, 2].map(|corner| {
+                let offset = start + corner * 12;
+                Vec3::new(read(offset), read(offset + 4), read(offset + 8))
+            })
+        })
+        .collect()
+}
+
+fn parse_ascii(data: &[u8]) -> Result<Vec<[Vec3; 3]>, LoadError> {
+    let text = std::str::from_utf8(data)
+        .map_err(|_| LoadError::Parse("stl file is neither binary nor text".to_string()))?;
+    let mut triangles = vec![];
+    let mut corners = vec![];
+    for line in text.lines() {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            ["vertex", x, y, z] => {
+                let coordinate = |value: &str| {
+                    value
+                        .parse::<f64>()
+                        .map_err(|_| LoadError::Parse(format!("invalid stl vertex {}", line)))
+                };
+                corners.push(Vec3::new(coordinate(x)?, coordinate(y)?, coordinate(z)?));
+            }
+            ["endfacet"] => {
+                let [a, b, c] = corners[..] else {
+                    return Err(LoadError::Parse(format!(
+                        "stl facet with {} vertices",
+                        corners.len()
+                    )));
+                };
+                triangles.push([a, b, c]);
+                corners.clear();
+            }
+            _ => {}
+        }
+    }
+    Ok(triangles)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        hittable::{test_material, Hittable},
+        ray::Ray,
+    };
+
+    #[test]
+    fn loads_ascii() {
+        let stl = "solid part\n\
+                   facet normal 0 0 1\n outer loop\n\
+                   vertex 0 0 0\n vertex 1 0 0\n vertex 1 1 0\n\
+                   endloop\nendfacet\n\
+                   facet normal 0 0 1\n outer loop\n\
+                   vertex 0 0 0\n vertex 1 1 0\n vertex 0 1 0\n\
+                   endloop\nendfacet\n\
+                   endsolid part\n";
+        let mesh = parse_stl(stl.as_bytes(), test_material()).unwrap();
+        assert_eq!(mesh.face_count(), 2);
+        let ray = Ray::new(Vec3::new(0.25, 0.75, 1.0), Vec3::new(0.0, 0.0, -1.0));
+        assert!(mesh.hit(&ray, 0.001, f64::MAX).unwrap().front_face);
+    }
+
+    #[test]
+    fn loads_binary_starting_with_solid() {
+        let mut stl = b"solid but binary".to_vec();
+        stl.resize(80, 0);
+        stl.extend(1u32.to_le_bytes());
+        for value in [
+            0.0f32, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0,
+        ] {
+            stl.extend(value.to_le_bytes());
+        }
+        stl.extend([0, 0]);
+
+        let mesh = parse_stl(&stl, test_material()).unwrap();
+        assert_eq!(mesh.face_count(), 1);
+        let ray = Ray::new(Vec3::new(0.25, 0.25, 1.0), Vec3::new(0.0, 0.0, -1.0));
+        assert!(mesh.hit(&ray, 0.001, f64::MAX).is_some());
+    }
+
+    #[test]
+    fn rejects_broken_facets() {
+        let stl = "solid part\nfacet normal 0 0 1\nouter loop\nvertex 0 0 0\nendloop\nendfacet\n";
+        assert!(parse_stl(stl.as_bytes(), test_material()).is_err());
+    }
+}
@@ -0,0 +1,89 @@
+use std::path::Path;
+
+use crate::voxel_grid::VoxelGrid;
+
+use super::LoadError;
+
+// loads a dense voxel grid from a text file: the resolution "nx ny nz" followed by
+// nx * ny * nz values separated by whitespace, x running fastest, then y, then z.
+// Everything after a # up to the end of the line is a comment
+pub fn load_voxel_grid<P: AsRef<Path>>(path: P) -> Result<VoxelGrid, LoadError> {
+    parse_voxel_grid(&std::fs::read_to_string(path)?)
+}
+
+fn parse_voxel_grid(text: &str) -> Result<VoxelGrid, LoadError> {
+    let mut tokens = text
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or_default())
+        .flat_map(str::split_whitespace);
+
+    let mut resolution = [0; 3];
+    for count in resolution.iter_mut() {
+        let token = tokens
+            .next()
+            .ok_or_else(|| LoadError::Parse("missing voxel grid resolution".to_string()))?;
+        *count = token
+            .parse()
+            .ok()
+            .filter(|count| *count > 0)
+            .ok_or_else(|| LoadError::Parse(format!("invalid voxel grid resolution {}", token)))?;
+    }
+
+    let expected = resolution.iter().product::<usize>();
+    let values = tokens
+        .map(|token| {
+            token
+                .parse::<f64>()
+                .ok()
+                .filter(|value| value.is_finite() && *value >= 0.0)
+                .ok_or_else(|| LoadError::Parse(format!("invalid voxel value {}", token)))
+        })
+        .collect::<Result<Vec<f64>, LoadError>>()?;
+    if values.len() != expected {
+        return Err(LoadError::Parse(format!(
+            "expected {} voxel values, found {}",
+            expected,
+            values.len()
+        )));
+    }
+    Ok(VoxelGrid::new(resolution, values))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vec3::Vec3;
+
+    #[test]
+    fn parse_with_comments() {
+        let grid =
+            parse_voxel_grid("# a 2x1x2 grid\n2 1 2\n0.0 1.0 # z = 0\n2.0 3.0 # z = 1\n").unwrap();
+        assert_eq!(grid.resolution(), [2, 1, 2]);
+        assert_eq!(grid.maximum(), 3.0);
+        assert!((grid.value(&Vec3::new(0.75, 0.5, 0.25)) - 1.0).abs() < 1e-12);
+        assert!((grid.value(&Vec3::new(0.25, 0.5, 0.75)) - 2.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn rejects_malformed_grids() {
+        assert!(parse_voxel_grid("").is_err());
+        assert!(parse_voxel_grid("2 2 0").is_err());
+        assert!(parse_voxel_grid("2 1 1\n1.0").is_err());
+        assert!(parse_voxel_grid("1 1 1\n-1.0").is_err());
+        assert!(parse_voxel_grid("1 1 1\n1.0 2.0").is_err());
+        assert!(parse_voxel_grid("1 1 1\nfog").is_err());
+    }
+
+    #[test]
+    fn load_voxel_file() {
+        let path = std::env::temp_dir().join("lib-raytracing-voxel-grid-test.vox");
+        std::fs::write(&path, "1 1 1\n0.25\n").unwrap();
+        let grid = load_voxel_grid(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(grid.maximum(), 0.25);
+        assert!(matches!(
+            load_voxel_grid(std::env::temp_dir().join("lib-raytracing-missing.vox")),
+            Err(LoadError::Io(_))
+        ));
+    }
+}
@@ -0,0 +1,207 @@
+use std::ops;
+
+use crate::vec3::Vec3;
+
+// 4x4 matrix for affine transformations of points, directions and normals
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Mat4 {
+    pub columns: [[f64; 4]; 4], // column major like glTF, columns[column][row]
+}
+
+impl Mat4 {
+    pub const IDENTITY: Mat4 = Mat4 {
+        columns: [
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ],
+    };
+
+    pub fn from_columns(columns: [[f64; 4]; 4]) -> Mat4 {
+        Mat4 { columns }
+    }
+
+    pub fn translation(offset: Vec3) -> Mat4 {
+        let mut matrix = Mat4::IDENTITY;
+        matrix.columns[3] = [offset.x, offset.y, offset.z, 1.0];
+        matrix
+    }
+
+    pub fn scaling(factors: Vec3) -> Mat4 {
+        let mut matrix = Mat4::IDENTITY;
+        for axis in 0..3 {
+            matrix.columns[axis][axis] = factors[axis];
+        }
+        matrix
+    }
+
+    // counter clockwise rotation around the axis through the origin (rodrigues' formula)
+    pub fn rotation(axis: Vec3, angle_deg: f64) -> Mat4 {
+        let axis = Vec3::unit_vector(&axis);
+        let angle = crate::degrees_to_radians!(angle_deg);
+        let (sin, cos) = angle.sin_cos();
+        let column = |basis: Vec3| {
+            let rotated = cos * basis
+                + sin * Vec3::cross(&axis, &basis)
+                + (1.0 - cos) * Vec3::dot(&axis, &basis) * axis;
+            [rotated.x, rotated.y, rotated.z, 0.0]
+        };
+        Mat4::from_columns([
+            column(Vec3::new(1.0, 0.0, 0.0)),
+            column(Vec3::new(0.0, 1.0, 0.0)),
+            column(Vec3::new(0.0, 0.0, 1.0)),
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    pub fn transpose(&self) -> Mat4 {
+        let mut result = Mat4::IDENTITY;
+        for (column, result_column) in result.columns.iter_mut().enumerate() {
+            for (row, value) in result_column.iter_mut().enumerate() {
+                *value = self.columns[row][column];
+            }
+        }
+        result
+    }
+
+    // gauss jordan elimination with partial pivoting, None for singular matrices
+    pub fn inverse(&self) -> Option<Mat4> {
+        let mut rows = self.transpose().columns;
+        let mut inverse = Mat4::IDENTITY.columns;
+        for column in 0..4 {
+            let pivot = (column..4)
+                .max_by(|a, b| rows[*a][column].abs().total_cmp(&rows[*b][column].abs()))
+                .unwrap();
+            if rows[pivot][column].abs() < 1e-12 {
+                return None;
+            }
+            rows.swap(column, pivot);
+            inverse.swap(column, pivot);
+
+            let scale = 1.0 / rows[column][column];
+            for k in 0..4 {
+                rows[column][k] *= scale;
+                inverse[column][k] *= scale;
+            }
+            for row in (0..4).filter(|row| *row != column) {
+                let factor = rows[row][column];
+                for k in 0..4 {
+                    rows[row][k] -= factor * rows[column][k];
+                    inverse[row][k] -= factor * inverse[column][k];
+                }
+            }
+        }
+        // the elimination worked on rows, transpose back into columns
+        Some(Mat4::from_columns(inverse).transpose())
+    }
+
+    pub fn transform_point(&self, point: &Vec3) -> Vec3 {
+        let translation = Vec3::new(self.columns[3][0], self.columns[3][1], self.columns[3][2]);
+        self.transform_direction(point) + translation
+    }
+
+    pub fn transform_direction(&self, direction: &Vec3) -> Vec3 {
+        let m = &self.columns;
+        Vec3::new(
+            m[0][0] * direction.x + m[1][0] * direction.y + m[2][0] * direction.z,
+            m[0][1] * direction.x + m[1][1] * direction.y + m[2][1] * direction.z,
+            m[0][2] * direction.x + m[1][2] * direction.y + m[2][2] * direction.z,
+        )
+    }
+
+    // of the linear part, negative for transformations that mirror
+    pub fn determinant(&self) -> f64 {
+        let (x, y, z) = (self.axis(0), self.axis(1), self.axis(2));
+        Vec3::dot(&x, &Vec3::cross(&y, &z))
+    }
+
+    // normals transform with the inverse transpose, the cofactor matrix is proportional to it
+    // and only needs the sign of the determinant to keep mirrored normals pointing outwards.
+    // The result is a unit vector
+    pub fn transform_normal(&self, normal: &Vec3) -> Vec3 {
+        let (x, y, z) = (self.axis(0), self.axis(1), self.axis(2));
+        let cofactor_x = Vec3::cross(&y, &z);
+        let cofactor_y = Vec3::cross(&z, &x);
+        let cofactor_z = Vec3::cross(&x, &y);
+        let determinant = Vec3::dot(&x, &cofactor_x);
+        let transformed = normal.x * cofactor_x + normal.y * cofactor_y + normal.z * cofactor_z;
+        Vec3::unit_vector(&transformed) * determinant.signum()
+    }
+
+    fn axis(&self, i: usize) -> Vec3 {
+        Vec3::new(self.columns[i][0], self.columns[i][1], self.columns[i][2])
+    }
+}
+
+impl Default for Mat4 {
+    fn default() -> Self {
+        Mat4::IDENTITY
+    }
+}
+
+// a * b applies b first, then a
+impl ops::Mul<Mat4> for Mat4 {
+    type Output = Mat4;
+
+    fn mul(self, other: Mat4) -> Self::Output {
+        let mut result = [[0.0; 4]; 4];
+        for (column, result_column) in result.iter_mut().enumerate() {
+            for (row, value) in result_column.iter_mut().enumerate() {
+                *value = (0..4)
+                    .map(|k| self.columns[k][row] * other.columns[column][k])
+                    .sum();
+            }
+        }
+        Mat4::from_columns(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: Vec3, b: Vec3) {
+        assert!((a - b).length() < 1e-9, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn rotation_is_counter_clockwise() {
+        let rotation = Mat4::rotation(Vec3::new(0.0, 0.0, 1.0), 90.0);
+        assert_close(
+            rotation.transform_direction(&Vec3::new(1.0, 0.0, 0.0)),
+            Vec3::new(0.0, 1.0, 0.0),
+        );
+    }
+
+    #[test]
+    fn multiplication_applies_right_first() {
+        let matrix =
+            Mat4::translation(Vec3::new(1.0, 0.0, 0.0)) * Mat4::scaling(Vec3::new(2.0, 2.0, 2.0));
+        assert_close(
+            matrix.transform_point(&Vec3::new(1.0, 1.0, 1.0)),
+            Vec3::new(3.0, 2.0, 2.0),
+        );
+    }
+
+    #[test]
+    fn inverse_undoes_the_matrix() {
+        let matrix = Mat4::translation(Vec3::new(1.0, -2.0, 3.0))
+            * Mat4::rotation(Vec3::new(1.0, 1.0, 0.0), 30.0)
+            * Mat4::scaling(Vec3::new(2.0, 0.5, -1.0));
+        let point = Vec3::new(0.3, 0.7, -1.1);
+        let inverse = matrix.inverse().unwrap();
+        assert_close(
+            inverse.transform_point(&matrix.transform_point(&point)),
+            point,
+        );
+        assert!(Mat4::scaling(Vec3::new(1.0, 0.0, 1.0)).inverse().is_none());
+    }
+
+    #[test]
+    fn normals_follow_non_uniform_scale() {
+        let scale = Mat4::scaling(Vec3::new(2.0, 1.0, 1.0));
+        let normal = scale.transform_normal(&Vec3::new(1.0, 1.0, 0.0));
+        assert_close(normal, Vec3::unit_vector(&Vec3::new(0.5, 1.0, 0.0)));
+    }
+}
@@ -1,5 +1,46 @@
 use crate::{hittable::HitRecord, ray::Ray, vec3::Vec3};
 
-pub trait Material: Sync {
+// a direction picked by Material::sample
+pub struct ScatterSample {
+    pub attenuation: Vec3, // eval / pdf, what the light arriving along the ray is scaled by
+    pub ray: Ray,
+    // solid angle density of the direction, only meaningful for lobes that are not specular
+    pub pdf: f64,
+    // the direction was picked from a delta distribution (a mirror, smooth glass) or by a
+    // material that cannot be evaluated, so eval and pdf are zero for every other direction
+    pub is_specular: bool,
+}
+
+// wo points towards the viewer (against the incoming ray) and wi towards the light, both
+// unit length and away from the hit point
+pub trait Material: Send + Sync {
     fn scatter(&self, ray: &Ray, record: &HitRecord) -> Option<(Vec3, Ray)>;
+
+    // picks a direction to continue the path in. Materials that only implement scatter are
+    // treated as specular
+    fn sample(&self, ray: &Ray, record: &HitRecord) -> Option<ScatterSample> {
+        let (attenuation, ray) = self.scatter(ray, record)?;
+        Some(ScatterSample {
+            attenuation,
+            ray,
+            pdf: 0.0,
+            is_specular: true,
+        })
+    }
+
+    // fraction of the radiance arriving from wi that leaves towards wo, per solid angle:
+    // the bsdf times |cos(theta_i)| on surfaces, the phase function times the albedo in media
+    fn eval(&self, _wi: &Vec3, _wo: &Vec3, _record: &HitRecord) -> Vec3 {
+        Vec3::new(0.0, 0.0, 0.0)
+    }
+
+    // solid angle density with which sample() picks wi, given wo
+    fn pdf(&self, _wi: &Vec3, _wo: &Vec3, _record: &HitRecord) -> f64 {
+        0.0
+    }
+
+    // light given off by the surface itself, black for everything that is not a light source
+    fn emitted(&self, _ray: &Ray, _record: &HitRecord) -> Vec3 {
+        Vec3::new(0.0, 0.0, 0.0)
+    }
 }
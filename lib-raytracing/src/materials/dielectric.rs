@@ -1,15 +1,24 @@
-use crate::{material::Material, ray::Ray, util::random, vec3::Vec3};
+use crate::{
+    hittable::HitRecord,
+    material::{Material, ScatterSample},
+    ray::Ray,
+    util::random,
+    vec3::Vec3,
+};
 
 pub struct Dielectric {
     pub index_of_refraction: f64,
 }
 
 impl Material for Dielectric {
-    fn scatter(
-        &self,
-        ray: &crate::ray::Ray,
-        record: &crate::hittable::HitRecord,
-    ) -> Option<(crate::vec3::Vec3, crate::ray::Ray)> {
+    fn scatter(&self, ray: &Ray, record: &HitRecord) -> Option<(Vec3, Ray)> {
+        self.sample(ray, record)
+            .map(|sample| (sample.attenuation, sample.ray))
+    }
+
+    // smooth glass only reflects and refracts into single directions, so eval and pdf stay
+    // zero
+    fn sample(&self, ray: &Ray, record: &HitRecord) -> Option<ScatterSample> {
         let refraction_ratio = if record.front_face {
             1.0 / self.index_of_refraction
         } else {
@@ -29,9 +38,12 @@ impl Material for Dielectric {
             Vec3::refract(&unit_direction, &record.normal, refraction_ratio)
         };
 
-        let scattered = Ray::new(record.point, direction);
-
-        Some((Vec3::new(1.0, 1.0, 1.0), scattered))
+        Some(ScatterSample {
+            attenuation: Vec3::new(1.0, 1.0, 1.0),
+            ray: Ray::with_time(record.point, direction, ray.time),
+            pdf: 0.0,
+            is_specular: true,
+        })
     }
 }
 
@@ -0,0 +1,33 @@
+use crate::{
+    hittable::HitRecord, material::Material, ray::Ray, texture::Texture,
+    textures::solid_color::SolidColor, vec3::Vec3,
+};
+
+pub struct DiffuseLight {
+    pub texture: Box<dyn Texture>, // emitted radiance, may exceed 1.0 for bright lights
+}
+
+impl DiffuseLight {
+    pub fn new(color: Vec3) -> DiffuseLight {
+        DiffuseLight {
+            texture: Box::new(SolidColor { color }),
+        }
+    }
+
+    pub fn textured(texture: impl Texture + 'static) -> DiffuseLight {
+        DiffuseLight {
+            texture: Box::new(texture),
+        }
+    }
+}
+
+impl Material for DiffuseLight {
+    fn scatter(&self, _ray: &Ray, _record: &HitRecord) -> Option<(Vec3, Ray)> {
+        // lights absorb everything that hits them
+        None
+    }
+
+    fn emitted(&self, _ray: &Ray, record: &HitRecord) -> Vec3 {
+        self.texture.value_at(record)
+    }
+}
@@ -0,0 +1,425 @@
+use crate::{
+    hittable::HitRecord,
+    material::{Material, ScatterSample},
+    microfacet::{fresnel_dielectric, ShadingFrame},
+    ray::Ray,
+    util,
+    util::PI,
+    vec3::Vec3,
+};
+
+const LOBES: usize = 3; // R, TT and TRT, everything after that is lumped into a fourth lobe
+const INDEX_OF_REFRACTION: f64 = 1.55;
+const SCALE_TILT: f64 = 2.0; // degrees the cuticle scales tilt the lobes along the fiber
+
+// scattering from hair fibers, for curves which give their hit records a tangent
+pub struct Hair {
+    absorption: Vec3,       // σa of the fiber interior per fiber diameter
+    beta_m: f64,            // longitudinal roughness in [0, 1]
+    beta_n: f64,            // azimuthal roughness in [0, 1]
+    variances: [f64; 4],    // longitudinal variance of every lobe
+    logistic_scale: f64,    // azimuthal spread
+    sin_2k_alpha: [f64; 3], // scale tilt doubled k times
+    cos_2k_alpha: [f64; 3],
+}
+
+impl Hair {
+    // a fiber whose absorption is chosen so that a blond to black head of hair made of it
+    // roughly has the given color
+    pub fn new(color: Vec3, beta_m: f64, beta_n: f64) -> Hair {
+        let denominator = 5.969 - 0.215 * beta_n + 2.532 * beta_n.powi(2) - 10.73 * beta_n.powi(3)
+            + 5.574 * beta_n.powi(4)
+            + 0.245 * beta_n.powi(5);
+        let absorption = |reflectance: f64| {
+            let log = reflectance.clamp(1e-4, 1.0).ln() / denominator;
+            log * log
+        };
+        Hair::with_absorption(
+            Vec3::new(
+                absorption(color.x),
+                absorption(color.y),
+                absorption(color.z),
+            ),
+            beta_m,
+            beta_n,
+        )
+    }
+
+    // natural hair colors from the concentrations of the two melanin pigments: eumelanin
+    // around 8 gives black hair, 1.3 brown and 0.3 blond, pheomelanin adds red
+    pub fn from_melanin(eumelanin: f64, pheomelanin: f64, beta_m: f64, beta_n: f64) -> Hair {
+        Hair::with_absorption(
+            eumelanin * Vec3::new(0.419, 0.697, 1.37) + pheomelanin * Vec3::new(0.187, 0.4, 1.05),
+            beta_m,
+            beta_n,
+        )
+    }
+
+    pub fn with_absorption(absorption: Vec3, beta_m: f64, beta_n: f64) -> Hair {
+        assert!(
+            (0.0..=1.0).contains(&beta_m) && (0.0..=1.0).contains(&beta_n),
+            "the roughness of hair has to be in [0, 1]"
+        );
+        let variance = (0.726 * beta_m + 0.812 * beta_m.powi(2) + 3.7 * beta_m.powi(20)).powi(2);
+        let logistic_scale =
+            (PI / 8.0).sqrt() * (0.265 * beta_n + 1.194 * beta_n.powi(2) + 5.372 * beta_n.powi(22));
+
+        let mut sin_2k_alpha = [SCALE_TILT.to_radians().sin(); 3];
+        let mut cos_2k_alpha = [(1.0 - sin_2k_alpha[0] * sin_2k_alpha[0]).sqrt(); 3];
+        for k in 1..3 {
+            sin_2k_alpha[k] = 2.0 * cos_2k_alpha[k - 1] * sin_2k_alpha[k - 1];
+            cos_2k_alpha[k] = cos_2k_alpha[k - 1].powi(2) - sin_2k_alpha[k - 1].powi(2);
+        }
+        Hair {
+            absorption,
+            beta_m,
+            beta_n,
+            variances: [variance, 0.25 * variance, 4.0 * variance, 4.0 * variance],
+            logistic_scale: logistic_scale.max(1e-4),
+            sin_2k_alpha,
+            cos_2k_alpha,
+        }
+    }
+
+    pub fn absorption(&self) -> Vec3 {
+        self.absorption
+    }
+
+    pub fn roughness(&self) -> (f64, f64) {
+        (self.beta_m, self.beta_n)
+    }
+
+    // the longitudinal angle of the outgoing direction tilted by the scales for lobe p
+    fn tilted(&self, p: usize, sin_theta: f64, cos_theta: f64) -> (f64, f64) {
+        let (sin, cos) = match p {
+            0 => (-self.sin_2k_alpha[1], self.cos_2k_alpha[1]),
+            1 => (self.sin_2k_alpha[0], self.cos_2k_alpha[0]),
+            2 => (self.sin_2k_alpha[2], self.cos_2k_alpha[2]),
+            _ => return (sin_theta, cos_theta),
+        };
+        (
+            sin_theta * cos + cos_theta * sin,
+            (cos_theta * cos - sin_theta * sin).abs(),
+        )
+    }
+
+    // attenuation of every lobe for light leaving at offset h from the axis
+    fn attenuations(&self, sin_theta_o: f64, cos_theta_o: f64, h: f64) -> [Vec3; LOBES + 1] {
+        let sin_theta_t = sin_theta_o / INDEX_OF_REFRACTION;
+        let cos_theta_t = safe_sqrt(1.0 - sin_theta_t * sin_theta_t);
+        let cos_gamma_t = self.refracted_offset(sin_theta_o, cos_theta_o, h).cos();
+        // once through the fiber
+        let path = 2.0 * cos_gamma_t / cos_theta_t.max(1e-9);
+        let transmittance = Vec3::new(
+            (-self.absorption.x * path).exp(),
+            (-self.absorption.y * path).exp(),
+            (-self.absorption.z * path).exp(),
+        );
+
+        let cos_gamma_o = safe_sqrt(1.0 - h * h);
+        let fresnel = fresnel_dielectric(cos_theta_o * cos_gamma_o, INDEX_OF_REFRACTION);
+        let mut attenuations = [Vec3::new(fresnel, fresnel, fresnel); LOBES + 1];
+        attenuations[1] = transmittance * (1.0 - fresnel).powi(2);
+        for p in 2..LOBES {
+            attenuations[p] = attenuations[p - 1] * transmittance * fresnel;
+        }
+        // the geometric series of all longer paths
+        let remaining = transmittance * fresnel;
+        let series = |r: f64| r / (1.0 - r).max(1e-9);
+        attenuations[LOBES] = attenuations[LOBES - 1]
+            * Vec3::new(
+                series(remaining.x),
+                series(remaining.y),
+                series(remaining.z),
+            );
+        attenuations
+    }
+
+    // brdf times cosine and the density with which sample_direction() picks wi, in the frame
+    // of the fiber: x along it, (y, z) around it. After d'Eon et al. and Chiang et al. (as in
+    // pbrt), the Marschner lobes R (reflected off the surface), TT (passed through the fiber)
+    // and TRT (reflected once inside) are each a longitudinal spread along the fiber times the
+    // attenuation by fresnel and absorption times an azimuthal spread around it
+    fn evaluate(&self, wo: &Vec3, wi: &Vec3, h: f64) -> (Vec3, f64) {
+        let (sin_theta_o, cos_theta_o) = (wo.x, safe_sqrt(1.0 - wo.x * wo.x));
+        let (sin_theta_i, cos_theta_i) = (wi.x, safe_sqrt(1.0 - wi.x * wi.x));
+        let phi = wi.z.atan2(wi.y) - wo.z.atan2(wo.y);
+
+        let gamma_o = h.clamp(-1.0, 1.0).asin();
+        let gamma_t = self.refracted_offset(sin_theta_o, cos_theta_o, h);
+        let attenuations = self.attenuations(sin_theta_o, cos_theta_o, h);
+        let weights = lobe_weights(&attenuations);
+
+        let mut value = Vec3::new(0.0, 0.0, 0.0);
+        let mut pdf = 0.0;
+        for p in 0..=LOBES {
+            let (sin_theta_op, cos_theta_op) = self.tilted(p, sin_theta_o, cos_theta_o);
+            let longitudinal = longitudinal_scattering(
+                cos_theta_i,
+                cos_theta_op,
+                sin_theta_i,
+                sin_theta_op,
+                self.variances[p],
+            );
+            let azimuthal = if p < LOBES {
+                self.azimuthal_scattering(phi, p, gamma_o, gamma_t)
+            } else {
+                1.0 / (2.0 * PI)
+            };
+            value += attenuations[p] * (longitudinal * azimuthal);
+            pdf += weights[p] * longitudinal * azimuthal;
+        }
+        (value, pdf)
+    }
+
+    // angle to the axis of the refracted ray inside the fiber
+    fn refracted_offset(&self, sin_theta_o: f64, cos_theta_o: f64, h: f64) -> f64 {
+        let modified_eta =
+            (INDEX_OF_REFRACTION.powi(2) - sin_theta_o.powi(2)).sqrt() / cos_theta_o.max(1e-9);
+        (h / modified_eta).clamp(-1.0, 1.0).asin()
+    }
+
+    fn azimuthal_scattering(&self, phi: f64, p: usize, gamma_o: f64, gamma_t: f64) -> f64 {
+        let mut difference = phi - azimuth(p, gamma_o, gamma_t);
+        while difference > PI {
+            difference -= 2.0 * PI;
+        }
+        while difference < -PI {
+            difference += 2.0 * PI;
+        }
+        trimmed_logistic(difference, self.logistic_scale)
+    }
+
+    // scattered direction in the fiber frame for the outgoing direction wo
+    fn sample_direction(&self, wo: &Vec3, h: f64) -> Vec3 {
+        let (sin_theta_o, cos_theta_o) = (wo.x, safe_sqrt(1.0 - wo.x * wo.x));
+        let attenuations = self.attenuations(sin_theta_o, cos_theta_o, h);
+        let weights = lobe_weights(&attenuations);
+        let mut choice = util::random();
+        let mut p = 0;
+        while p < LOBES && choice >= weights[p] {
+            choice -= weights[p];
+            p += 1;
+        }
+
+        // longitudinal angle around the mirror direction of the tilted outgoing one
+        let (sin_theta_op, cos_theta_op) = self.tilted(p, sin_theta_o, cos_theta_o);
+        let variance = self.variances[p];
+        let random = util::random().max(1e-5);
+        let cos_theta = 1.0 + variance * (random + (1.0 - random) * (-2.0 / variance).exp()).ln();
+        let sin_theta = safe_sqrt(1.0 - cos_theta * cos_theta);
+        let cos_phi = (2.0 * PI * util::random()).cos();
+        let sin_theta_i =
+            (-cos_theta * sin_theta_op + sin_theta * cos_phi * cos_theta_op).clamp(-1.0, 1.0);
+        let cos_theta_i = safe_sqrt(1.0 - sin_theta_i * sin_theta_i);
+
+        let gamma_o = h.clamp(-1.0, 1.0).asin();
+        let gamma_t = self.refracted_offset(sin_theta_o, cos_theta_o, h);
+        let phi_difference = if p < LOBES {
+            azimuth(p, gamma_o, gamma_t) + sample_trimmed_logistic(self.logistic_scale)
+        } else {
+            2.0 * PI * util::random()
+        };
+        let phi_i = wo.z.atan2(wo.y) + phi_difference;
+        Vec3::new(
+            sin_theta_i,
+            cos_theta_i * phi_i.cos(),
+            cos_theta_i * phi_i.sin(),
+        )
+    }
+
+    // the fiber frame at a hit: x along the fiber, z across it towards the outgoing direction
+    // and y along the offset h
+    fn frame(outgoing: &Vec3, record: &HitRecord) -> ShadingFrame {
+        let x = record
+            .tangent
+            .unwrap_or_else(|| Vec3::orthonormal_basis(&record.normal).0);
+        let facing = *outgoing - Vec3::dot(outgoing, &x) * x;
+        let z = if facing.near_zero() {
+            Vec3::orthonormal_basis(&x).0
+        } else {
+            Vec3::unit_vector(&facing)
+        };
+        ShadingFrame {
+            tangent: x,
+            bitangent: Vec3::cross(&z, &x),
+            normal: z,
+        }
+    }
+
+    // evaluate() for world directions
+    fn evaluate_at(&self, wi: &Vec3, wo: &Vec3, record: &HitRecord) -> (Vec3, f64) {
+        let frame = Hair::frame(wo, record);
+        let h = 2.0 * record.v - 1.0;
+        self.evaluate(&frame.to_local(wo), &frame.to_local(wi), h)
+    }
+}
+
+impl Material for Hair {
+    fn scatter(&self, ray: &Ray, record: &HitRecord) -> Option<(Vec3, Ray)> {
+        self.sample(ray, record)
+            .map(|sample| (sample.attenuation, sample.ray))
+    }
+
+    fn sample(&self, ray: &Ray, record: &HitRecord) -> Option<ScatterSample> {
+        let outgoing = -1.0 * Vec3::unit_vector(&ray.direction);
+        let frame = Hair::frame(&outgoing, record);
+        let wo = frame.to_local(&outgoing);
+        let h = 2.0 * record.v - 1.0; // v runs across the curve, h is the offset from its axis
+        let wi = self.sample_direction(&wo, h);
+        let (value, pdf) = self.evaluate(&wo, &wi, h);
+        if pdf <= 0.0 {
+            return None;
+        }
+        Some(ScatterSample {
+            attenuation: value / pdf,
+            ray: Ray::with_time(record.point, frame.to_world(&wi), ray.time),
+            pdf,
+            is_specular: false,
+        })
+    }
+
+    fn eval(&self, wi: &Vec3, wo: &Vec3, record: &HitRecord) -> Vec3 {
+        self.evaluate_at(wi, wo, record).0
+    }
+
+    fn pdf(&self, wi: &Vec3, wo: &Vec3, record: &HitRecord) -> f64 {
+        self.evaluate_at(wi, wo, record).1
+    }
+}
+
+fn safe_sqrt(x: f64) -> f64 {
+    x.max(0.0).sqrt()
+}
+
+// how likely each lobe is sampled: by its share of the total attenuation
+fn lobe_weights(attenuations: &[Vec3; LOBES + 1]) -> [f64; LOBES + 1] {
+    let total: f64 = attenuations.iter().map(Vec3::luminance).sum();
+    if total <= 0.0 {
+        return [1.0 / (LOBES + 1) as f64; LOBES + 1];
+    }
+    attenuations.map(|attenuation| attenuation.luminance() / total)
+}
+
+// modified bessel function of the first kind and order zero
+fn bessel_i0(x: f64) -> f64 {
+    let mut value = 0.0;
+    let mut term = 1.0;
+    let square = x * x / 4.0;
+    for i in 0..10 {
+        value += term;
+        term *= square / ((i + 1) * (i + 1)) as f64;
+    }
+    value
+}
+
+fn log_bessel_i0(x: f64) -> f64 {
+    if x > 12.0 {
+        x + 0.5 * (-(2.0 * PI).ln() + (1.0 / x).ln() + 1.0 / (8.0 * x))
+    } else {
+        bessel_i0(x).ln()
+    }
+}
+
+// normalized spread of the longitudinal angle, computed in log space for small variances
+fn longitudinal_scattering(
+    cos_theta_i: f64,
+    cos_theta_o: f64,
+    sin_theta_i: f64,
+    sin_theta_o: f64,
+    variance: f64,
+) -> f64 {
+    let a = cos_theta_i * cos_theta_o / variance;
+    let b = sin_theta_i * sin_theta_o / variance;
+    if variance <= 0.1 {
+        (log_bessel_i0(a) - b - 1.0 / variance
+            + std::f64::consts::LN_2
+            + (1.0 / (2.0 * variance)).ln())
+        .exp()
+    } else {
+        (-b).exp() * bessel_i0(a) / ((1.0 / variance).sinh() * 2.0 * variance)
+    }
+}
+
+// azimuthal angle by which lobe p leaves relative to the outgoing direction
+fn azimuth(p: usize, gamma_o: f64, gamma_t: f64) -> f64 {
+    let p = p as f64;
+    2.0 * p * gamma_t - 2.0 * gamma_o + p * PI
+}
+
+fn logistic(x: f64, scale: f64) -> f64 {
+    let x = x.abs();
+    (-x / scale).exp() / (scale * (1.0 + (-x / scale).exp()).powi(2))
+}
+
+fn logistic_cdf(x: f64, scale: f64) -> f64 {
+    1.0 / (1.0 + (-x / scale).exp())
+}
+
+// logistic distribution normalized over [-π, π]
+fn trimmed_logistic(x: f64, scale: f64) -> f64 {
+    logistic(x, scale) / (logistic_cdf(PI, scale) - logistic_cdf(-PI, scale))
+}
+
+fn sample_trimmed_logistic(scale: f64) -> f64 {
+    let range = logistic_cdf(PI, scale) - logistic_cdf(-PI, scale);
+    let x = -scale * (1.0 / (util::random() * range + logistic_cdf(-PI, scale)) - 1.0).ln();
+    x.clamp(-PI, PI)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn random_direction() -> Vec3 {
+        Vec3::random_unit_vector()
+    }
+
+    #[test]
+    fn white_hair_in_a_white_furnace() {
+        // without absorption all light arriving at a fiber leaves it again
+        for (beta_m, beta_n) in [(0.2, 0.3), (0.5, 0.5), (0.8, 0.9)] {
+            let hair = Hair::with_absorption(Vec3::new(0.0, 0.0, 0.0), beta_m, beta_n);
+            let samples = 100000;
+            let mut total = Vec3::new(0.0, 0.0, 0.0);
+            for _ in 0..samples {
+                let wo = random_direction();
+                let h = util::random_range(-1.0, 1.0);
+                let wi = hair.sample_direction(&wo, h);
+                let (value, pdf) = hair.evaluate(&wo, &wi, h);
+                total += value / pdf / samples as f64;
+            }
+            assert!(
+                (total.y - 1.0).abs() < 0.05,
+                "β = ({}, {}): {:?}",
+                beta_m,
+                beta_n,
+                total
+            );
+        }
+    }
+
+    #[test]
+    fn sampling_density_integrates_to_one() {
+        let hair = Hair::from_melanin(1.3, 0.2, 0.3, 0.3);
+        let wo = Vec3::unit_vector(&Vec3::new(0.3, 0.2, 0.9));
+        let h = 0.4;
+        // uniformly over the sphere, the density of the sampled lobes integrates to one
+        let samples = 400000;
+        let mut integral = 0.0;
+        for _ in 0..samples {
+            let wi = random_direction();
+            integral += hair.evaluate(&wo, &wi, h).1 * 4.0 * PI / samples as f64;
+        }
+        assert!((integral - 1.0).abs() < 0.05, "{}", integral);
+    }
+
+    #[test]
+    fn darker_melanin_absorbs_more() {
+        let blond = Hair::from_melanin(0.3, 0.0, 0.3, 0.3);
+        let black = Hair::from_melanin(8.0, 0.0, 0.3, 0.3);
+        assert!(black.absorption().x > blond.absorption().x);
+        let brown = Hair::new(Vec3::new(0.3, 0.2, 0.1), 0.3, 0.3);
+        assert!(brown.absorption().z > brown.absorption().x);
+    }
+}
@@ -0,0 +1,128 @@
+use crate::{
+    hittable::HitRecord,
+    material::{Material, ScatterSample},
+    ray::Ray,
+    texture::Texture,
+    textures::solid_color::SolidColor,
+    util,
+    util::PI,
+    vec3::Vec3,
+};
+
+// phase function of participating media with a preferred direction: anisotropy g in (-1, 1)
+// is the mean cosine between the incoming and the scattered direction, positive values
+// scatter forward (clouds), negative ones back, 0 is isotropic.
+// The texture is the albedo of the medium
+pub struct HenyeyGreenstein {
+    pub texture: Box<dyn Texture>,
+    pub anisotropy: f64,
+}
+
+impl HenyeyGreenstein {
+    pub fn new(color: Vec3, anisotropy: f64) -> HenyeyGreenstein {
+        HenyeyGreenstein::textured(SolidColor { color }, anisotropy)
+    }
+
+    pub fn textured(texture: impl Texture + 'static, anisotropy: f64) -> HenyeyGreenstein {
+        assert!(
+            anisotropy.abs() < 1.0,
+            "the anisotropy of a phase function has to be in (-1, 1)"
+        );
+        HenyeyGreenstein {
+            texture: Box::new(texture),
+            anisotropy,
+        }
+    }
+
+    // density per solid angle for the cosine between the ray and the scattered direction
+    pub fn phase(&self, cos_theta: f64) -> f64 {
+        let g = self.anisotropy;
+        let denominator = 1.0 + g * g - 2.0 * g * cos_theta;
+        (1.0 - g * g) / (4.0 * PI * denominator * denominator.sqrt())
+    }
+
+    // inverts the cdf of the phase function over the cosine
+    fn sample_cos_theta(&self) -> f64 {
+        let g = self.anisotropy;
+        let random = util::random();
+        if g.abs() < 1e-3 {
+            return 1.0 - 2.0 * random;
+        }
+        let square = (1.0 - g * g) / (1.0 - g + 2.0 * g * random);
+        ((1.0 + g * g - square * square) / (2.0 * g)).clamp(-1.0, 1.0)
+    }
+}
+
+impl Material for HenyeyGreenstein {
+    fn scatter(&self, ray: &Ray, record: &HitRecord) -> Option<(Vec3, Ray)> {
+        self.sample(ray, record)
+            .map(|sample| (sample.attenuation, sample.ray))
+    }
+
+    fn sample(&self, ray: &Ray, record: &HitRecord) -> Option<ScatterSample> {
+        let forward = Vec3::unit_vector(&ray.direction);
+        let (tangent, bitangent) = Vec3::orthonormal_basis(&forward);
+        let cos_theta = self.sample_cos_theta();
+        let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+        let phi = 2.0 * PI * util::random();
+        let direction = tangent * (sin_theta * phi.cos())
+            + bitangent * (sin_theta * phi.sin())
+            + forward * cos_theta;
+        Some(ScatterSample {
+            attenuation: self.texture.value_at(record),
+            ray: Ray::with_time(record.point, direction, ray.time),
+            pdf: self.phase(cos_theta),
+            is_specular: false,
+        })
+    }
+
+    // wo points back along the ray, so the scattering angle is between -wo and wi
+    fn eval(&self, wi: &Vec3, wo: &Vec3, record: &HitRecord) -> Vec3 {
+        self.texture.value_at(record) * self.pdf(wi, wo, record)
+    }
+
+    fn pdf(&self, wi: &Vec3, wo: &Vec3, _record: &HitRecord) -> f64 {
+        self.phase(-Vec3::dot(wo, wi))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn phase_integrates_to_one() {
+        for g in [-0.7, 0.0, 0.3, 0.9] {
+            let phase_function = HenyeyGreenstein::new(Vec3::new(1.0, 1.0, 1.0), g);
+            // 2π ∫ p(cos) dcos over [-1, 1] with the midpoint rule
+            let steps = 100000;
+            let integral: f64 = (0..steps)
+                .map(|i| {
+                    let cos_theta = -1.0 + (i as f64 + 0.5) * 2.0 / steps as f64;
+                    2.0 * PI * phase_function.phase(cos_theta) * 2.0 / steps as f64
+                })
+                .sum();
+            assert!((integral - 1.0).abs() < 1e-3, "g = {}: {}", g, integral);
+        }
+    }
+
+    #[test]
+    fn mean_cosine_is_the_anisotropy() {
+        let phase_function = HenyeyGreenstein::new(Vec3::new(1.0, 1.0, 1.0), 0.6);
+        let record = HitRecord::for_test(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(1.0, 0.0, 0.0),
+            true,
+            &phase_function,
+        );
+        let ray = Ray::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 2.0, 0.0));
+        let samples = 20000;
+        let mut mean = 0.0;
+        for _ in 0..samples {
+            let (_, scattered) = phase_function.scatter(&ray, &record).unwrap();
+            assert!((scattered.direction.length() - 1.0).abs() < 1e-9);
+            mean += scattered.direction.y / samples as f64;
+        }
+        assert!((mean - 0.6).abs() < 0.02);
+    }
+}
@@ -0,0 +1,53 @@
+use crate::{
+    hittable::HitRecord,
+    material::{Material, ScatterSample},
+    ray::Ray,
+    texture::Texture,
+    textures::solid_color::SolidColor,
+    util::PI,
+    vec3::Vec3,
+};
+
+// phase function of participating media that scatters equally into all directions,
+// the texture is the albedo of the medium
+pub struct Isotropic {
+    pub texture: Box<dyn Texture>,
+}
+
+impl Isotropic {
+    pub fn new(color: Vec3) -> Isotropic {
+        Isotropic {
+            texture: Box::new(SolidColor { color }),
+        }
+    }
+
+    pub fn textured(texture: impl Texture + 'static) -> Isotropic {
+        Isotropic {
+            texture: Box::new(texture),
+        }
+    }
+}
+
+impl Material for Isotropic {
+    fn scatter(&self, ray: &Ray, record: &HitRecord) -> Option<(Vec3, Ray)> {
+        self.sample(ray, record)
+            .map(|sample| (sample.attenuation, sample.ray))
+    }
+
+    fn sample(&self, ray: &Ray, record: &HitRecord) -> Option<ScatterSample> {
+        Some(ScatterSample {
+            attenuation: self.texture.value_at(record),
+            ray: Ray::with_time(record.point, Vec3::random_unit_vector(), ray.time),
+            pdf: 1.0 / (4.0 * PI),
+            is_specular: false,
+        })
+    }
+
+    fn eval(&self, _wi: &Vec3, _wo: &Vec3, record: &HitRecord) -> Vec3 {
+        self.texture.value_at(record) / (4.0 * PI)
+    }
+
+    fn pdf(&self, _wi: &Vec3, _wo: &Vec3, _record: &HitRecord) -> f64 {
+        1.0 / (4.0 * PI)
+    }
+}
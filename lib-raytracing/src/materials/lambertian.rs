@@ -1,25 +1,85 @@
-use crate::{material::Material, ray::Ray, vec3::Vec3};
+use crate::{
+    hittable::HitRecord,
+    material::{Material, ScatterSample},
+    ray::Ray,
+    texture::Texture,
+    textures::solid_color::SolidColor,
+    util::PI,
+    vec3::Vec3,
+};
 
 pub struct Lambertian {
-    pub color: Vec3,
+    pub texture: Box<dyn Texture>,
+}
+
+impl Lambertian {
+    pub fn new(color: Vec3) -> Lambertian {
+        Lambertian {
+            texture: Box::new(SolidColor { color }),
+        }
+    }
+
+    pub fn textured(texture: impl Texture + 'static) -> Lambertian {
+        Lambertian {
+            texture: Box::new(texture),
+        }
+    }
 }
 
 impl Material for Lambertian {
-    fn scatter(
-        &self,
-        _ray: &crate::ray::Ray,
-        record: &crate::hittable::HitRecord,
-    ) -> Option<(Vec3, crate::ray::Ray)> {
-        // let target = record.point + record.normal + Vec3::random_unit_vector();
-        // let target = record.point + record.normal + Vec3::random_in_unit_sphere();
+    fn scatter(&self, ray: &Ray, record: &HitRecord) -> Option<(Vec3, Ray)> {
+        self.sample(ray, record)
+            .map(|sample| (sample.attenuation, sample.ray))
+    }
 
+    fn sample(&self, ray: &Ray, record: &HitRecord) -> Option<ScatterSample> {
+        // normal + random_unit_vector() is distributed proportional to cos(theta), which
+        // cancels the cosine of the brdf
         let mut scatter_direction = record.normal + Vec3::random_unit_vector();
         scatter_direction = if scatter_direction.near_zero() {
             record.normal
         } else {
             scatter_direction
         };
-        let scattered_ray = Ray::new(record.point, scatter_direction);
-        Some((self.color, scattered_ray))
+        let wi = Vec3::unit_vector(&scatter_direction);
+        Some(ScatterSample {
+            attenuation: self.texture.value_at(record),
+            ray: Ray::with_time(record.point, scatter_direction, ray.time),
+            pdf: Vec3::dot(&record.normal, &wi).max(0.0) / PI,
+            is_specular: false,
+        })
+    }
+
+    fn eval(&self, wi: &Vec3, _wo: &Vec3, record: &HitRecord) -> Vec3 {
+        self.texture.value_at(record) * (Vec3::dot(&record.normal, wi).max(0.0) / PI)
+    }
+
+    fn pdf(&self, wi: &Vec3, _wo: &Vec3, record: &HitRecord) -> f64 {
+        Vec3::dot(&record.normal, wi).max(0.0) / PI
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn samples_match_eval_and_pdf() {
+        let material = Lambertian::new(Vec3::new(0.5, 0.25, 1.0));
+        let normal = Vec3::new(0.0, 0.0, 1.0);
+        let record = HitRecord::for_test(Vec3::new(0.0, 0.0, 0.0), normal, true, &material);
+        let ray = Ray::new(Vec3::new(1.0, 0.0, 1.0), Vec3::new(-1.0, 0.0, -1.0));
+        let wo = Vec3::unit_vector(&(-1.0 * ray.direction));
+        for _ in 0..100 {
+            let sample = material.sample(&ray, &record).unwrap();
+            let wi = Vec3::unit_vector(&sample.ray.direction);
+            assert!(!sample.is_specular);
+            assert!((sample.pdf - material.pdf(&wi, &wo, &record)).abs() < 1e-9);
+            let weight = material.eval(&wi, &wo, &record) / sample.pdf;
+            assert!((weight - sample.attenuation).length() < 1e-9);
+        }
+        // nothing arrives from below the surface
+        let below = Vec3::new(0.0, 0.0, -1.0);
+        assert_eq!(material.pdf(&below, &wo, &record), 0.0);
     }
 }
@@ -1,24 +1,137 @@
-use crate::{hittable::HitRecord, material::Material, ray::Ray, vec3::Vec3};
+use crate::{
+    hittable::HitRecord,
+    material::{Material, ScatterSample},
+    ray::Ray,
+    texture::Texture,
+    textures::solid_color::SolidColor,
+    util::PI,
+    vec3::Vec3,
+};
 
 pub struct Metal {
-    pub color: Vec3,
+    pub texture: Box<dyn Texture>,
     pub fuzz: f64,
 }
 
+impl Metal {
+    pub fn new(color: Vec3, fuzz: f64) -> Metal {
+        Metal {
+            texture: Box::new(SolidColor { color }),
+            fuzz,
+        }
+    }
+
+    pub fn textured(texture: impl Texture + 'static, fuzz: f64) -> Metal {
+        Metal {
+            texture: Box::new(texture),
+            fuzz,
+        }
+    }
+}
+
+impl Metal {
+    // solid angle density of the direction of reflected + fuzz * (a point uniformly
+    // distributed in the unit ball), found by integrating along the ray in direction wi
+    // through the ball of radius fuzz around the unit length mirror direction
+    fn fuzz_density(&self, wi: &Vec3, reflected: &Vec3) -> f64 {
+        let cos_alpha = Vec3::dot(wi, reflected);
+        let discriminant = self.fuzz * self.fuzz - (1.0 - cos_alpha * cos_alpha);
+        if discriminant <= 0.0 {
+            return 0.0;
+        }
+        let far = cos_alpha + discriminant.sqrt();
+        let near = (cos_alpha - discriminant.sqrt()).max(0.0);
+        if far <= 0.0 {
+            return 0.0;
+        }
+        (far.powi(3) - near.powi(3)) / (4.0 * PI * self.fuzz.powi(3))
+    }
+}
+
 impl Material for Metal {
     fn scatter(&self, ray: &Ray, record: &HitRecord) -> Option<(Vec3, Ray)> {
-        // let target = record.point + record.normal + Vec3::random_unit_vector();
-        // let target = record.point + record.normal + Vec3::random_in_unit_sphere();
+        self.sample(ray, record)
+            .map(|sample| (sample.attenuation, sample.ray))
+    }
 
+    fn sample(&self, ray: &Ray, record: &HitRecord) -> Option<ScatterSample> {
         let reflected = Vec3::reflect(&Vec3::unit_vector(&ray.direction), &record.normal);
-        let scattered = Ray::new(
+        let scattered = Ray::with_time(
             record.point,
             reflected + self.fuzz * Vec3::random_in_unit_sphere(),
+            ray.time,
         );
-        if Vec3::dot(&scattered.direction, &record.normal) > 0.0 {
-            Some((self.color, scattered))
+        // directions fuzzed into the surface are absorbed
+        if Vec3::dot(&scattered.direction, &record.normal) <= 0.0 {
+            return None;
+        }
+        let is_specular = self.fuzz <= 0.0;
+        let pdf = if is_specular {
+            0.0
         } else {
-            None
+            self.fuzz_density(&Vec3::unit_vector(&scattered.direction), &reflected)
+        };
+        Some(ScatterSample {
+            attenuation: self.texture.value_at(record),
+            ray: scattered,
+            pdf,
+            is_specular,
+        })
+    }
+
+    fn eval(&self, wi: &Vec3, wo: &Vec3, record: &HitRecord) -> Vec3 {
+        self.texture.value_at(record) * self.pdf(wi, wo, record)
+    }
+
+    fn pdf(&self, wi: &Vec3, wo: &Vec3, record: &HitRecord) -> f64 {
+        if self.fuzz <= 0.0 || Vec3::dot(wi, &record.normal) <= 0.0 {
+            return 0.0;
+        }
+        self.fuzz_density(wi, &Vec3::reflect(&(-1.0 * *wo), &record.normal))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fuzz_density_integrates_to_one() {
+        for fuzz in [0.1, 0.5, 1.0, 1.5] {
+            let metal = Metal::new(Vec3::new(1.0, 1.0, 1.0), fuzz);
+            let reflected = Vec3::new(0.0, 0.0, 1.0);
+            // the density only depends on the angle to the mirror direction:
+            // 2π ∫ density(cos) dcos over [-1, 1] with the midpoint rule
+            let steps = 100000;
+            let integral: f64 = (0..steps)
+                .map(|i| {
+                    let cos_alpha = -1.0 + (i as f64 + 0.5) * 2.0 / steps as f64;
+                    let sin_alpha = (1.0 - cos_alpha * cos_alpha).sqrt();
+                    let wi = Vec3::new(sin_alpha, 0.0, cos_alpha);
+                    2.0 * PI * metal.fuzz_density(&wi, &reflected) * 2.0 / steps as f64
+                })
+                .sum();
+            assert!((integral - 1.0).abs() < 1e-3, "{}: {}", fuzz, integral);
+        }
+    }
+
+    #[test]
+    fn samples_match_eval_and_pdf() {
+        let metal = Metal::new(Vec3::new(0.9, 0.5, 0.1), 0.3);
+        let up = Vec3::new(0.0, 1.0, 0.0);
+        let record = HitRecord::for_test(Vec3::new(0.0, 0.0, 0.0), up, true, &metal);
+        let ray = Ray::new(Vec3::new(-1.0, 1.0, 0.0), Vec3::new(1.0, -1.0, 0.0));
+        let wo = Vec3::unit_vector(&(-1.0 * ray.direction));
+        for _ in 0..100 {
+            let sample = metal.sample(&ray, &record).unwrap();
+            let wi = Vec3::unit_vector(&sample.ray.direction);
+            assert!(!sample.is_specular);
+            assert!((sample.pdf - metal.pdf(&wi, &wo, &record)).abs() < 1e-9);
+            let weight = metal.eval(&wi, &wo, &record) / sample.pdf;
+            assert!((weight - sample.attenuation).length() < 1e-9);
         }
+        // a perfect mirror cannot be evaluated
+        let mirror = Metal::new(Vec3::new(1.0, 1.0, 1.0), 0.0);
+        assert!(mirror.sample(&ray, &record).unwrap().is_specular);
     }
 }
@@ -1,3 +1,10 @@
 pub mod dielectric;
+pub mod diffuse_light;
+pub mod hair;
+pub mod henyey_greenstein;
+pub mod isotropic;
 pub mod lambertian;
 pub mod metal;
+pub mod principled;
+pub mod rough_conductor;
+pub mod rough_dielectric;
@@ -0,0 +1,458 @@
+use crate::{
+    hittable::HitRecord,
+    material::{Material, ScatterSample},
+    microfacet::{fresnel_dielectric, fresnel_schlick, reflect, ShadingFrame, TrowbridgeReitz},
+    ray::Ray,
+    texture::Texture,
+    textures::solid_color::SolidColor,
+    util::{random, PI},
+    vec3::Vec3,
+};
+
+use super::{
+    rough_conductor::{eval_conductor, sample_conductor},
+    rough_dielectric::{dielectric_pdf, eval_dielectric, relative_eta, sample_dielectric},
+};
+
+const CLEARCOAT_INDEX_OF_REFRACTION: f64 = 1.5;
+
+// base color / metallic / roughness material after the Disney and glTF models. Every input
+// is a texture, inputs that are single numbers read the red channel (x)
+pub struct Principled {
+    pub base_color: Box<dyn Texture>,
+    pub metallic: Box<dyn Texture>,  // 0 dielectric, 1 metal
+    pub roughness: Box<dyn Texture>, // 0 polished, 1 matte
+    pub specular: Box<dyn Texture>,  // reflectance at normal incidence / 0.08, 0.5 is 4%
+    pub clearcoat: Box<dyn Texture>, // strength of the clearcoat in [0, 1]
+    pub clearcoat_roughness: Box<dyn Texture>,
+    pub sheen: Box<dyn Texture>, // color of the sheen, black for none
+    pub transmission: Box<dyn Texture>, // 0 opaque, 1 clear
+    pub index_of_refraction: f64, // of transmissive surfaces
+    pub emission: Box<dyn Texture>, // emitted radiance, black for none
+}
+
+impl Principled {
+    pub fn new(base_color: Vec3, metallic: f64, roughness: f64) -> Principled {
+        Principled {
+            base_color: Box::new(SolidColor { color: base_color }),
+            metallic: constant(metallic),
+            roughness: constant(roughness),
+            ..Default::default()
+        }
+    }
+
+    pub fn textured(
+        base_color: impl Texture + 'static,
+        metallic: f64,
+        roughness: f64,
+    ) -> Principled {
+        Principled {
+            base_color: Box::new(base_color),
+            ..Principled::new(Vec3::default(), metallic, roughness)
+        }
+    }
+}
+
+impl Default for Principled {
+    // white plastic
+    fn default() -> Self {
+        Principled {
+            base_color: Box::new(SolidColor {
+                color: Vec3::new(0.8, 0.8, 0.8),
+            }),
+            metallic: constant(0.0),
+            roughness: constant(0.5),
+            specular: constant(0.5),
+            clearcoat: constant(0.0),
+            clearcoat_roughness: constant(0.03),
+            sheen: constant(0.0),
+            transmission: constant(0.0),
+            index_of_refraction: 1.5,
+            emission: constant(0.0),
+        }
+    }
+}
+
+// texture of a single number
+pub fn constant(value: f64) -> Box<dyn Texture> {
+    Box::new(SolidColor {
+        color: Vec3::new(value, value, value),
+    })
+}
+
+// a direction around the normal distributed by cos(theta), in the local frame
+fn cosine_direction() -> Vec3 {
+    let direction = Vec3::new(0.0, 0.0, 1.0) + Vec3::random_unit_vector();
+    if direction.near_zero() {
+        Vec3::new(0.0, 0.0, 1.0)
+    } else {
+        Vec3::unit_vector(&direction)
+    }
+}
+
+// the inputs at one hit and the odds of picking each lobe. The odds only depend on the
+// outgoing direction, so eval and pdf agree with what sample does: the clearcoat reflects
+// its fresnel reflectance towards the normal, the layers below share what it lets through
+struct Layers {
+    base_color: Vec3,
+    sheen: Vec3, // at most 1 in every channel
+    sheen_strength: f64,
+    clearcoat: f64, // strength, 0 from inside
+    specular: f64,  // reflectance at normal incidence
+    eta: f64,       // relative index of refraction of the transmissive lobe
+    base: TrowbridgeReitz,
+    coat: TrowbridgeReitz,
+    clearcoat_odds: f64,
+    metallic_odds: f64,
+    transmission_odds: f64,
+    specular_odds: f64,
+    diffuse_odds: f64,
+}
+
+impl Layers {
+    // the layers from the top: a clearcoat reflecting off a second, smooth GGX surface, then
+    // the base, which is metallic (a RoughConductor tinted by the base color), transmissive
+    // (a RoughDielectric tinted by the base color) or else a GGX specular layer over a
+    // diffuse base color that turns into the sheen color at grazing angles. Every layer lets
+    // through what it does not reflect, so hardly any energy is created or lost
+    fn new(material: &Principled, record: &HitRecord, wo: &Vec3) -> Layers {
+        let scalar = |texture: &dyn Texture| texture.value_at(record).x.clamp(0.0, 1.0);
+        let sheen = material.sheen.value_at(record);
+        let sheen = sheen / sheen.x.max(sheen.y).max(sheen.z).max(1.0);
+        let clearcoat = if record.front_face {
+            scalar(material.clearcoat.as_ref())
+        } else {
+            0.0 // the coat only covers the outside
+        };
+        let specular = 0.08 * scalar(material.specular.as_ref());
+        let metallic = scalar(material.metallic.as_ref());
+        let transmission = scalar(material.transmission.as_ref());
+
+        let clearcoat_odds = clearcoat * fresnel_dielectric(wo.z, CLEARCOAT_INDEX_OF_REFRACTION);
+        let below = 1.0 - clearcoat_odds;
+        let plastic = below * (1.0 - metallic) * (1.0 - transmission);
+        let specular_odds = plastic * schlick(wo.z, specular);
+        Layers {
+            base_color: material.base_color.value_at(record),
+            sheen,
+            sheen_strength: sheen.x.max(sheen.y).max(sheen.z),
+            clearcoat,
+            specular,
+            eta: relative_eta(record, material.index_of_refraction),
+            base: TrowbridgeReitz::new(scalar(material.roughness.as_ref())),
+            coat: TrowbridgeReitz::new(scalar(material.clearcoat_roughness.as_ref())),
+            clearcoat_odds,
+            metallic_odds: below * metallic,
+            transmission_odds: below * (1.0 - metallic) * transmission,
+            specular_odds,
+            diffuse_odds: plastic - specular_odds,
+        }
+    }
+
+    // base color towards wi, turning into the sheen color where wi and the half vector part
+    fn diffuse_color(&self, wo: &Vec3, wi: &Vec3) -> Vec3 {
+        if self.sheen_strength <= 0.0 {
+            return self.base_color;
+        }
+        let half_vector = Vec3::unit_vector(&(*wo + *wi));
+        let grazing = (1.0 - Vec3::dot(wi, &half_vector).clamp(0.0, 1.0)).powi(5);
+        self.base_color * (1.0 - self.sheen_strength * grazing) + self.sheen * grazing
+    }
+
+    fn tint(&self, wi: &Vec3) -> Vec3 {
+        if wi.z < 0.0 {
+            self.base_color // the transmission is tinted on its way through
+        } else {
+            Vec3::new(1.0, 1.0, 1.0)
+        }
+    }
+
+    // sum of the lobes that can be evaluated
+    fn eval(&self, wo: &Vec3, wi: &Vec3) -> Vec3 {
+        let mut value = Vec3::new(0.0, 0.0, 0.0);
+        if self.clearcoat_odds > 0.0 && !self.coat.is_smooth() {
+            let fresnel =
+                |cos: f64| self.clearcoat * fresnel_dielectric(cos, CLEARCOAT_INDEX_OF_REFRACTION);
+            value += gray(eval_specular(&self.coat, wo, wi, fresnel));
+        }
+        if !self.base.is_smooth() {
+            if self.metallic_odds > 0.0 {
+                value += eval_conductor(&self.base, wo, wi, self.base_color) * self.metallic_odds;
+            }
+            if self.transmission_odds > 0.0 {
+                let transmitted = eval_dielectric(&self.base, wo, wi, self.eta);
+                value += self.tint(wi) * (transmitted * self.transmission_odds);
+            }
+            if self.specular_odds > 0.0 {
+                // the specular odds already hold the fresnel reflectance towards the normal
+                let scale = self.specular_odds / schlick(wo.z, self.specular);
+                let fresnel = |cos: f64| scale * schlick(cos, self.specular);
+                value += gray(eval_specular(&self.base, wo, wi, fresnel));
+            }
+        }
+        if self.diffuse_odds > 0.0 && wi.z > 0.0 {
+            value += self.diffuse_color(wo, wi) * (self.diffuse_odds * wi.z / PI);
+        }
+        value
+    }
+
+    fn pdf(&self, wo: &Vec3, wi: &Vec3) -> f64 {
+        let mut pdf = 0.0;
+        if !self.coat.is_smooth() {
+            pdf += self.clearcoat_odds * self.coat.reflection_pdf(wo, wi);
+        }
+        if !self.base.is_smooth() {
+            pdf += self.metallic_odds * self.base.reflection_pdf(wo, wi);
+            pdf += self.transmission_odds * dielectric_pdf(&self.base, wo, wi, self.eta);
+            pdf += self.specular_odds * self.base.reflection_pdf(wo, wi);
+        }
+        if wi.z > 0.0 {
+            pdf += self.diffuse_odds * wi.z / PI;
+        }
+        pdf
+    }
+}
+
+fn gray(value: f64) -> Vec3 {
+    Vec3::new(value, value, value)
+}
+
+fn schlick(cos_theta: f64, reflectance: f64) -> f64 {
+    fresnel_schlick(cos_theta, gray(reflectance)).x
+}
+
+// a white GGX reflection whose fresnel term the caller decides, picking wi with the
+// visible normals and the attenuation that leaves for the fresnel at the chosen normal
+fn sample_specular(
+    distribution: &TrowbridgeReitz,
+    wo: &Vec3,
+    fresnel: impl Fn(f64) -> f64,
+) -> Option<(Vec3, f64)> {
+    let microfacet_normal = distribution.sample_visible_normal(wo);
+    let wi = reflect(wo, &microfacet_normal);
+    if wi.z <= 0.0 {
+        return None;
+    }
+    Some((
+        wi,
+        fresnel(Vec3::dot(wo, &microfacet_normal)) * specular_weight(distribution, wo, &wi),
+    ))
+}
+
+fn eval_specular(
+    distribution: &TrowbridgeReitz,
+    wo: &Vec3,
+    wi: &Vec3,
+    fresnel: impl Fn(f64) -> f64,
+) -> f64 {
+    let pdf = distribution.reflection_pdf(wo, wi);
+    if pdf <= 0.0 {
+        return 0.0;
+    }
+    let microfacet_normal = Vec3::unit_vector(&(*wo + *wi));
+    fresnel(Vec3::dot(wo, &microfacet_normal)) * specular_weight(distribution, wo, wi) * pdf
+}
+
+fn specular_weight(distribution: &TrowbridgeReitz, wo: &Vec3, wi: &Vec3) -> f64 {
+    distribution.g(wo, wi) / distribution.g1(wo) / distribution.conductor_albedo(wo.z)
+}
+
+impl Material for Principled {
+    fn scatter(&self, ray: &Ray, record: &HitRecord) -> Option<(Vec3, Ray)> {
+        self.sample(ray, record)
+            .map(|sample| (sample.attenuation, sample.ray))
+    }
+
+    fn sample(&self, ray: &Ray, record: &HitRecord) -> Option<ScatterSample> {
+        let frame = ShadingFrame::new(&record.normal);
+        let wo = frame.to_local(&(-1.0 * Vec3::unit_vector(&ray.direction)));
+        if wo.z <= 0.0 {
+            return None;
+        }
+        let layers = Layers::new(self, record, &wo);
+        let scattered = |wi: &Vec3| Ray::with_time(record.point, frame.to_world(wi), ray.time);
+        let specular = |wi: Vec3, attenuation: Vec3| {
+            Some(ScatterSample {
+                attenuation,
+                ray: scattered(&wi),
+                pdf: 0.0,
+                is_specular: true,
+            })
+        };
+
+        // a sharp lobe is returned on its own, otherwise the direction is weighed against
+        // all lobes that could have picked it
+        let choice = random();
+        let metallic_end = layers.clearcoat_odds + layers.metallic_odds;
+        let transmission_end = metallic_end + layers.transmission_odds;
+        let specular_end = transmission_end + layers.specular_odds;
+        let wi = if choice < layers.clearcoat_odds {
+            let fresnel = |cos: f64| fresnel_dielectric(cos, CLEARCOAT_INDEX_OF_REFRACTION);
+            let (wi, weight) = sample_specular(&layers.coat, &wo, fresnel)?;
+            if layers.coat.is_smooth() {
+                return specular(wi, gray(weight / fresnel(wo.z)));
+            }
+            wi
+        } else if choice < metallic_end {
+            let (wi, attenuation) = sample_conductor(&layers.base, &wo, layers.base_color)?;
+            if layers.base.is_smooth() {
+                return specular(wi, attenuation);
+            }
+            wi
+        } else if choice < transmission_end {
+            let (wi, weight) = sample_dielectric(&layers.base, &wo, layers.eta)?;
+            if layers.base.is_smooth() {
+                return specular(wi, layers.tint(&wi) * weight);
+            }
+            wi
+        } else if choice < specular_end {
+            let fresnel = |cos: f64| schlick(cos, layers.specular);
+            let (wi, weight) = sample_specular(&layers.base, &wo, fresnel)?;
+            if layers.base.is_smooth() {
+                return specular(wi, gray(weight / fresnel(wo.z)));
+            }
+            wi
+        } else {
+            cosine_direction()
+        };
+
+        let pdf = layers.pdf(&wo, &wi);
+        if pdf <= 0.0 {
+            return None;
+        }
+        Some(ScatterSample {
+            attenuation: layers.eval(&wo, &wi) / pdf,
+            ray: scattered(&wi),
+            pdf,
+            is_specular: false,
+        })
+    }
+
+    fn eval(&self, wi: &Vec3, wo: &Vec3, record: &HitRecord) -> Vec3 {
+        let frame = ShadingFrame::new(&record.normal);
+        let (wo, wi) = (frame.to_local(wo), frame.to_local(wi));
+        if wo.z <= 0.0 {
+            return Vec3::new(0.0, 0.0, 0.0);
+        }
+        Layers::new(self, record, &wo).eval(&wo, &wi)
+    }
+
+    fn pdf(&self, wi: &Vec3, wo: &Vec3, record: &HitRecord) -> f64 {
+        let frame = ShadingFrame::new(&record.normal);
+        let (wo, wi) = (frame.to_local(wo), frame.to_local(wi));
+        if wo.z <= 0.0 {
+            return 0.0;
+        }
+        Layers::new(self, record, &wo).pdf(&wo, &wi)
+    }
+
+    fn emitted(&self, _ray: &Ray, record: &HitRecord) -> Vec3 {
+        self.emission.value_at(record)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::textures::checker::Checker;
+
+    // mean attenuation of rays arriving at 45 degrees
+    fn albedo(material: &dyn Material, x: f64) -> Vec3 {
+        let record = HitRecord::for_test(
+            Vec3::new(x, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            true,
+            material,
+        );
+        let ray = Ray::new(Vec3::new(-1.0, 1.0, 0.0), Vec3::new(1.0, -1.0, 0.0));
+        let samples = 50000;
+        (0..samples)
+            .filter_map(|_| material.scatter(&ray, &record))
+            .fold(Vec3::new(0.0, 0.0, 0.0), |total, (attenuation, _)| {
+                total + attenuation / samples as f64
+            })
+    }
+
+    #[test]
+    fn white_layers_keep_all_the_light() {
+        let white = Vec3::new(1.0, 1.0, 1.0);
+        let materials = [
+            Principled::new(white, 0.0, 0.5),
+            Principled::new(white, 1.0, 0.8),
+            Principled {
+                transmission: constant(1.0),
+                ..Principled::new(white, 0.0, 0.3)
+            },
+            Principled {
+                clearcoat: constant(1.0),
+                sheen: constant(1.0),
+                specular: constant(1.0),
+                ..Principled::new(white, 0.3, 0.9)
+            },
+        ];
+        for material in materials {
+            let albedo = albedo(&material, 0.0);
+            assert!((albedo.y - 1.0).abs() < 0.03, "{:?}", albedo);
+        }
+    }
+
+    #[test]
+    fn inputs_are_textured() {
+        // metal in the even cells, glowing plastic in the odd ones
+        let material = Principled {
+            metallic: Box::new(Checker::new(
+                Vec3::new(1.0, 1.0, 1.0),
+                Vec3::new(0.0, 0.0, 0.0),
+                1.0,
+            )),
+            emission: Box::new(Checker::new(
+                Vec3::new(0.0, 0.0, 0.0),
+                Vec3::new(2.0, 2.0, 2.0),
+                1.0,
+            )),
+            ..Principled::new(Vec3::new(1.0, 0.0, 0.0), 0.0, 0.2)
+        };
+        let (metal, plastic) = (0.5, 1.5);
+        // red metal reflects hardly any green, red plastic does through its white specular
+        assert!(albedo(&material, metal).y < 0.01);
+        assert!(albedo(&material, plastic).y > 0.03);
+        let ray = Ray::new(Vec3::new(0.0, 1.0, 0.0), Vec3::new(0.0, -1.0, 0.0));
+        let record = |x: f64| {
+            HitRecord::for_test(
+                Vec3::new(x, 0.0, 0.0),
+                Vec3::new(0.0, 1.0, 0.0),
+                true,
+                &material,
+            )
+        };
+        assert_eq!(material.emitted(&ray, &record(metal)).x, 0.0);
+        assert_eq!(material.emitted(&ray, &record(plastic)).x, 2.0);
+    }
+
+    #[test]
+    fn samples_match_eval_and_pdf() {
+        let material = Principled {
+            clearcoat: constant(0.8),
+            clearcoat_roughness: constant(0.2),
+            sheen: constant(0.5),
+            metallic: constant(0.3),
+            transmission: constant(0.5),
+            ..Principled::new(Vec3::new(0.8, 0.4, 0.2), 0.0, 0.4)
+        };
+        let up = Vec3::new(0.0, 1.0, 0.0);
+        let record = HitRecord::for_test(Vec3::new(0.0, 0.0, 0.0), up, true, &material);
+        let ray = Ray::new(Vec3::new(-1.0, 1.0, 0.0), Vec3::new(1.0, -0.6, 0.3));
+        let wo = Vec3::unit_vector(&(-1.0 * ray.direction));
+        for _ in 0..1000 {
+            let Some(sample) = material.sample(&ray, &record) else {
+                continue;
+            };
+            let wi = Vec3::unit_vector(&sample.ray.direction);
+            assert!(!sample.is_specular);
+            let pdf = material.pdf(&wi, &wo, &record);
+            assert!((sample.pdf / pdf - 1.0).abs() < 1e-6);
+            let weight = material.eval(&wi, &wo, &record) / sample.pdf;
+            assert!((weight - sample.attenuation).length() < 1e-6);
+        }
+    }
+}
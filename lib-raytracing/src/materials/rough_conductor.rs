@@ -0,0 +1,196 @@
+use crate::{
+    hittable::HitRecord,
+    material::{Material, ScatterSample},
+    microfacet::{fresnel_schlick, reflect, ShadingFrame, TrowbridgeReitz},
+    ray::Ray,
+    texture::Texture,
+    textures::solid_color::SolidColor,
+    vec3::Vec3,
+};
+
+// metal made of GGX microfacets, each a perfect mirror. The texture is the reflectance at
+// normal incidence, roughness goes from 0 (polished) to 1 (matte). Only the microfacets
+// facing the ray are sampled, which keeps the weights bounded for every roughness
+pub struct RoughConductor {
+    pub texture: Box<dyn Texture>,
+    pub roughness: f64,
+}
+
+impl RoughConductor {
+    pub fn new(color: Vec3, roughness: f64) -> RoughConductor {
+        RoughConductor {
+            texture: Box::new(SolidColor { color }),
+            roughness,
+        }
+    }
+
+    pub fn textured(texture: impl Texture + 'static, roughness: f64) -> RoughConductor {
+        RoughConductor {
+            texture: Box::new(texture),
+            roughness,
+        }
+    }
+}
+
+impl Material for RoughConductor {
+    fn scatter(&self, ray: &Ray, record: &HitRecord) -> Option<(Vec3, Ray)> {
+        self.sample(ray, record)
+            .map(|sample| (sample.attenuation, sample.ray))
+    }
+
+    fn sample(&self, ray: &Ray, record: &HitRecord) -> Option<ScatterSample> {
+        let frame = ShadingFrame::new(&record.normal);
+        let wo = frame.to_local(&(-1.0 * Vec3::unit_vector(&ray.direction)));
+        let distribution = TrowbridgeReitz::new(self.roughness);
+        let (wi, attenuation) =
+            sample_conductor(&distribution, &wo, self.texture.value_at(record))?;
+        Some(ScatterSample {
+            attenuation,
+            ray: Ray::with_time(record.point, frame.to_world(&wi), ray.time),
+            pdf: distribution.reflection_pdf(&wo, &wi),
+            is_specular: distribution.is_smooth(),
+        })
+    }
+
+    fn eval(&self, wi: &Vec3, wo: &Vec3, record: &HitRecord) -> Vec3 {
+        let distribution = TrowbridgeReitz::new(self.roughness);
+        if distribution.is_smooth() {
+            return Vec3::new(0.0, 0.0, 0.0);
+        }
+        let frame = ShadingFrame::new(&record.normal);
+        eval_conductor(
+            &distribution,
+            &frame.to_local(wo),
+            &frame.to_local(wi),
+            self.texture.value_at(record),
+        )
+    }
+
+    fn pdf(&self, wi: &Vec3, wo: &Vec3, record: &HitRecord) -> f64 {
+        let distribution = TrowbridgeReitz::new(self.roughness);
+        if distribution.is_smooth() {
+            return 0.0;
+        }
+        let frame = ShadingFrame::new(&record.normal);
+        distribution.reflection_pdf(&frame.to_local(wo), &frame.to_local(wi))
+    }
+}
+
+// reflection of wo (in the local frame) off GGX mirrors with the given reflectance at normal
+// incidence, the direction and its attenuation. Shared with the metallic part of Principled
+pub(crate) fn sample_conductor(
+    distribution: &TrowbridgeReitz,
+    wo: &Vec3,
+    color: Vec3,
+) -> Option<(Vec3, Vec3)> {
+    if wo.z <= 0.0 {
+        return None;
+    }
+    let microfacet_normal = distribution.sample_visible_normal(wo);
+    let wi = reflect(wo, &microfacet_normal);
+    if wi.z <= 0.0 {
+        return None; // reflected into the surface by a microfacet
+    }
+    Some((wi, conductor_weight(distribution, wo, &wi, color)))
+}
+
+// brdf times cos(theta_i) of the same mirrors, in the local frame
+pub(crate) fn eval_conductor(
+    distribution: &TrowbridgeReitz,
+    wo: &Vec3,
+    wi: &Vec3,
+    color: Vec3,
+) -> Vec3 {
+    let pdf = distribution.reflection_pdf(wo, wi);
+    if pdf <= 0.0 {
+        return Vec3::new(0.0, 0.0, 0.0);
+    }
+    conductor_weight(distribution, wo, wi, color) * pdf
+}
+
+// brdf * cos / pdf, where D and most of G cancel against the density of the normals.
+// Light scattered more than once between microfacets is added back by scaling with the
+// albedo, tinted once more by the color (Turquin 2019)
+fn conductor_weight(distribution: &TrowbridgeReitz, wo: &Vec3, wi: &Vec3, color: Vec3) -> Vec3 {
+    let microfacet_normal = Vec3::unit_vector(&(*wo + *wi));
+    let albedo = distribution.conductor_albedo(wo.z);
+    let multiple_scattering = Vec3::new(1.0, 1.0, 1.0) + color * ((1.0 - albedo) / albedo);
+    fresnel_schlick(Vec3::dot(wo, &microfacet_normal), color)
+        * multiple_scattering
+        * (distribution.g(wo, wi) / distribution.g1(wo))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // mean attenuation of rays arriving at the given angle to the normal
+    fn albedo(material: &dyn Material, cos_theta: f64) -> f64 {
+        let record = HitRecord::for_test(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            true,
+            material,
+        );
+        let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
+        let ray = Ray::new(
+            Vec3::new(-sin_theta, cos_theta, 0.0),
+            Vec3::new(sin_theta, -cos_theta, 0.0),
+        );
+        let samples = 50000;
+        (0..samples)
+            .filter_map(|_| material.scatter(&ray, &record))
+            .map(|(attenuation, scattered)| {
+                assert!(scattered.direction.y > 0.0);
+                attenuation.y / samples as f64
+            })
+            .sum()
+    }
+
+    #[test]
+    fn white_conductor_keeps_all_the_light() {
+        // rough metal is not darker than polished metal
+        for roughness in [0.0, 0.3, 0.7, 1.0] {
+            let metal = RoughConductor::new(Vec3::new(1.0, 1.0, 1.0), roughness);
+            for cos_theta in [1.0, 0.5] {
+                let albedo = albedo(&metal, cos_theta);
+                assert!((albedo - 1.0).abs() < 0.03, "{}: {}", roughness, albedo);
+            }
+        }
+    }
+
+    #[test]
+    fn smooth_conductor_is_a_mirror() {
+        let metal = RoughConductor::new(Vec3::new(0.9, 0.5, 0.1), 0.0);
+        let record = HitRecord::for_test(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            true,
+            &metal,
+        );
+        let ray = Ray::new(Vec3::new(-1.0, 1.0, 0.0), Vec3::new(1.0, -1.0, 0.0));
+        let (attenuation, scattered) = metal.scatter(&ray, &record).unwrap();
+        let expected = Vec3::unit_vector(&Vec3::new(1.0, 1.0, 0.0));
+        assert!((Vec3::unit_vector(&scattered.direction) - expected).length() < 1e-3);
+        assert!(attenuation.z > 0.1 && attenuation.z < 0.15);
+    }
+
+    #[test]
+    fn samples_match_eval_and_pdf() {
+        let metal = RoughConductor::new(Vec3::new(0.9, 0.5, 0.1), 0.4);
+        let up = Vec3::new(0.0, 1.0, 0.0);
+        let record = HitRecord::for_test(Vec3::new(0.0, 0.0, 0.0), up, true, &metal);
+        let ray = Ray::new(Vec3::new(-1.0, 1.0, 0.0), Vec3::new(1.0, -1.0, 0.0));
+        let wo = Vec3::unit_vector(&(-1.0 * ray.direction));
+        for _ in 0..100 {
+            let Some(sample) = metal.sample(&ray, &record) else {
+                continue;
+            };
+            let wi = Vec3::unit_vector(&sample.ray.direction);
+            assert!(!sample.is_specular);
+            assert!((sample.pdf / metal.pdf(&wi, &wo, &record) - 1.0).abs() < 1e-6);
+            let weight = metal.eval(&wi, &wo, &record) / sample.pdf;
+            assert!((weight - sample.attenuation).length() < 1e-6);
+        }
+    }
+}
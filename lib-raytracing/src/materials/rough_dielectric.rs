@@ -0,0 +1,252 @@
+use crate::{
+    hittable::HitRecord,
+    material::{Material, ScatterSample},
+    microfacet::{fresnel_dielectric, reflect, refract, ShadingFrame, TrowbridgeReitz},
+    ray::Ray,
+    util::random,
+    vec3::Vec3,
+};
+
+// frosted glass: GGX microfacets that each reflect or refract like the smooth Dielectric.
+// A microfacet facing the ray is sampled, then fresnel picks between reflection and
+// refraction through it
+pub struct RoughDielectric {
+    pub index_of_refraction: f64,
+    pub roughness: f64,
+}
+
+impl RoughDielectric {
+    pub fn new(index_of_refraction: f64, roughness: f64) -> RoughDielectric {
+        RoughDielectric {
+            index_of_refraction,
+            roughness,
+        }
+    }
+}
+
+impl Material for RoughDielectric {
+    fn scatter(&self, ray: &Ray, record: &HitRecord) -> Option<(Vec3, Ray)> {
+        self.sample(ray, record)
+            .map(|sample| (sample.attenuation, sample.ray))
+    }
+
+    fn sample(&self, ray: &Ray, record: &HitRecord) -> Option<ScatterSample> {
+        let frame = ShadingFrame::new(&record.normal);
+        let wo = frame.to_local(&(-1.0 * Vec3::unit_vector(&ray.direction)));
+        let distribution = TrowbridgeReitz::new(self.roughness);
+        let eta = relative_eta(record, self.index_of_refraction);
+        let (wi, attenuation) = sample_dielectric(&distribution, &wo, eta)?;
+        Some(ScatterSample {
+            attenuation: Vec3::new(attenuation, attenuation, attenuation),
+            ray: Ray::with_time(record.point, frame.to_world(&wi), ray.time),
+            pdf: dielectric_pdf(&distribution, &wo, &wi, eta),
+            is_specular: distribution.is_smooth(),
+        })
+    }
+
+    fn eval(&self, wi: &Vec3, wo: &Vec3, record: &HitRecord) -> Vec3 {
+        let distribution = TrowbridgeReitz::new(self.roughness);
+        if distribution.is_smooth() {
+            return Vec3::new(0.0, 0.0, 0.0);
+        }
+        let frame = ShadingFrame::new(&record.normal);
+        let eta = relative_eta(record, self.index_of_refraction);
+        let value = eval_dielectric(&distribution, &frame.to_local(wo), &frame.to_local(wi), eta);
+        Vec3::new(value, value, value)
+    }
+
+    fn pdf(&self, wi: &Vec3, wo: &Vec3, record: &HitRecord) -> f64 {
+        let distribution = TrowbridgeReitz::new(self.roughness);
+        if distribution.is_smooth() {
+            return 0.0;
+        }
+        let frame = ShadingFrame::new(&record.normal);
+        let eta = relative_eta(record, self.index_of_refraction);
+        dielectric_pdf(&distribution, &frame.to_local(wo), &frame.to_local(wi), eta)
+    }
+}
+
+// index of refraction on the side of the ray over the one on the other side
+pub(crate) fn relative_eta(record: &HitRecord, index_of_refraction: f64) -> f64 {
+    if record.front_face {
+        1.0 / index_of_refraction
+    } else {
+        index_of_refraction
+    }
+}
+
+// reflection off or refraction through GGX microfacets of clear glass for wo in the local
+// frame, the direction and its attenuation. Shared with the transmissive part of Principled
+pub(crate) fn sample_dielectric(
+    distribution: &TrowbridgeReitz,
+    wo: &Vec3,
+    eta: f64,
+) -> Option<(Vec3, f64)> {
+    if wo.z <= 0.0 {
+        return None;
+    }
+    let microfacet_normal = distribution.sample_visible_normal(wo);
+    let cos_theta = Vec3::dot(wo, &microfacet_normal);
+
+    let reflectance = fresnel_dielectric(cos_theta, 1.0 / eta);
+    let wi = match refract(wo, &microfacet_normal, eta) {
+        Some(refracted) if random() >= reflectance => {
+            if refracted.z >= 0.0 {
+                return None; // refracted back out by a steep microfacet
+            }
+            refracted
+        }
+        _ => {
+            let reflected = reflect(wo, &microfacet_normal);
+            if reflected.z <= 0.0 {
+                return None;
+            }
+            reflected
+        }
+    };
+    Some((wi, dielectric_weight(distribution, wo, &wi, eta)))
+}
+
+// bsdf times |cos(theta_i)| of the same glass, in the local frame
+pub(crate) fn eval_dielectric(
+    distribution: &TrowbridgeReitz,
+    wo: &Vec3,
+    wi: &Vec3,
+    eta: f64,
+) -> f64 {
+    let pdf = dielectric_pdf(distribution, wo, wi, eta);
+    if pdf <= 0.0 {
+        return 0.0;
+    }
+    dielectric_weight(distribution, wo, wi, eta) * pdf
+}
+
+// solid angle density with which sample_dielectric picks wi: the density of the microfacet
+// normal that reflects or refracts wo into wi, times the odds fresnel gives that choice,
+// times the change of variables from normals to directions (Walter 2007)
+pub(crate) fn dielectric_pdf(
+    distribution: &TrowbridgeReitz,
+    wo: &Vec3,
+    wi: &Vec3,
+    eta: f64,
+) -> f64 {
+    if wo.z <= 0.0 || wi.z == 0.0 {
+        return 0.0;
+    }
+    if wi.z > 0.0 {
+        let microfacet_normal = Vec3::unit_vector(&(*wo + *wi));
+        let reflectance = fresnel_dielectric(Vec3::dot(wo, &microfacet_normal), 1.0 / eta);
+        return reflectance * distribution.reflection_pdf(wo, wi);
+    }
+
+    // the normal that bends wo into wi, facing the outside
+    let half_vector = -1.0 * (eta * *wo + *wi);
+    if half_vector.near_zero() {
+        return 0.0;
+    }
+    let mut microfacet_normal = Vec3::unit_vector(&half_vector);
+    if microfacet_normal.z < 0.0 {
+        microfacet_normal = -1.0 * microfacet_normal;
+    }
+    let cos_theta_o = Vec3::dot(wo, &microfacet_normal);
+    let cos_theta_i = Vec3::dot(wi, &microfacet_normal);
+    if cos_theta_o <= 0.0 || cos_theta_i >= 0.0 {
+        return 0.0;
+    }
+    let transmittance = 1.0 - fresnel_dielectric(cos_theta_o, 1.0 / eta);
+    let denominator = eta * cos_theta_o + cos_theta_i;
+    distribution.visible_pdf(wo, &microfacet_normal) * transmittance * -cos_theta_i
+        / (denominator * denominator)
+}
+
+// fresnel is accounted for by the choice, D and the visibility from wo cancel against the
+// density of the sampled normals. Dividing by the albedo adds back the light that would
+// leave after scattering between microfacets more than once
+fn dielectric_weight(distribution: &TrowbridgeReitz, wo: &Vec3, wi: &Vec3, eta: f64) -> f64 {
+    distribution.g(wo, wi) / distribution.g1(wo) / distribution.dielectric_albedo(wo.z, eta)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rough_glass_keeps_all_the_light() {
+        for roughness in [0.0, 0.2, 0.5, 1.0] {
+            let glass = RoughDielectric::new(1.5, roughness);
+            for front_face in [true, false] {
+                let record = HitRecord::for_test(
+                    Vec3::new(0.0, 0.0, 0.0),
+                    Vec3::new(0.0, 1.0, 0.0),
+                    front_face,
+                    &glass,
+                );
+                let ray = Ray::new(Vec3::new(-1.0, 1.0, 0.0), Vec3::new(1.0, -1.0, 0.0));
+                let samples = 50000;
+                let (mut reflected, mut total) = (0.0, 0.0);
+                for _ in 0..samples {
+                    if let Some((attenuation, scattered)) = glass.scatter(&ray, &record) {
+                        total += attenuation.x / samples as f64;
+                        if scattered.direction.y > 0.0 {
+                            reflected += 1.0 / samples as f64;
+                        }
+                    }
+                }
+                assert!((total - 1.0).abs() < 0.03, "{}: {}", roughness, total);
+                if roughness == 0.0 {
+                    // 5 percent reflected from outside, totally reflected from inside
+                    let expected = if front_face { 0.05 } else { 1.0 };
+                    assert!((reflected - expected).abs() < 0.01, "{}", reflected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn smooth_glass_bends_like_snell() {
+        let glass = RoughDielectric::new(1.5, 0.0);
+        let record = HitRecord::for_test(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            true,
+            &glass,
+        );
+        let ray = Ray::new(Vec3::new(-1.0, 1.0, 0.0), Vec3::new(1.0, -1.0, 0.0));
+        let refracted = (0..100)
+            .filter_map(|_| glass.scatter(&ray, &record))
+            .map(|(_, scattered)| Vec3::unit_vector(&scattered.direction))
+            .find(|direction| direction.y < 0.0)
+            .unwrap();
+        let sin_theta_t = 0.5_f64.sqrt() / 1.5;
+        assert!((refracted.x - sin_theta_t).abs() < 1e-3);
+    }
+
+    #[test]
+    fn samples_match_eval_and_pdf() {
+        let glass = RoughDielectric::new(1.5, 0.4);
+        for front_face in [true, false] {
+            let up = Vec3::new(0.0, 1.0, 0.0);
+            let record = HitRecord::for_test(Vec3::new(0.0, 0.0, 0.0), up, front_face, &glass);
+            let ray = Ray::new(Vec3::new(-1.0, 1.0, 0.0), Vec3::new(1.0, -0.7, 0.2));
+            let wo = Vec3::unit_vector(&(-1.0 * ray.direction));
+            let mut refracted = 0;
+            for _ in 0..1000 {
+                let Some(sample) = glass.sample(&ray, &record) else {
+                    continue;
+                };
+                let wi = Vec3::unit_vector(&sample.ray.direction);
+                refracted += (wi.y < 0.0) as usize;
+                let pdf = glass.pdf(&wi, &wo, &record);
+                assert!(
+                    (sample.pdf / pdf - 1.0).abs() < 1e-6,
+                    "{} {}",
+                    sample.pdf,
+                    pdf
+                );
+                let weight = glass.eval(&wi, &wo, &record) / sample.pdf;
+                assert!((weight - sample.attenuation).length() < 1e-6);
+            }
+            assert!(refracted > 0);
+        }
+    }
+}
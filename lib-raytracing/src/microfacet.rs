@@ -0,0 +1,391 @@
+// the GGX (Trowbridge-Reitz) distribution of microfacet normals and fresnel terms shared by
+// the rough materials. Directions are given in a local frame whose z axis is the surface
+// normal, see ShadingFrame
+
+use std::sync::OnceLock;
+
+use crate::{util, util::PI, vec3::Vec3, voxel_grid::VoxelGrid};
+
+const MIN_ALPHA: f64 = 1e-4; // below this the distribution gets numerically unstable
+const SMOOTH_ALPHA: f64 = 1e-3; // sharper lobes are left to the materials' own sampling
+const INFINITE_LAMBDA: f64 = 1e30; // everything is hidden at grazing angles
+const ALBEDO_RESOLUTION: usize = 16; // table entries per parameter
+const ALBEDO_STRATA: usize = 16; // microfacet normals per axis for every table entry
+const MAX_INDEX_OF_REFRACTION: f64 = 3.0; // range of the dielectric tables, from 1
+
+// orthonormal frame around a normal, for going back and forth between world and local
+// directions
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShadingFrame {
+    pub tangent: Vec3,
+    pub bitangent: Vec3,
+    pub normal: Vec3,
+}
+
+impl ShadingFrame {
+    pub fn new(normal: &Vec3) -> ShadingFrame {
+        let normal = Vec3::unit_vector(normal);
+        let (tangent, bitangent) = Vec3::orthonormal_basis(&normal);
+        ShadingFrame {
+            tangent,
+            bitangent,
+            normal,
+        }
+    }
+
+    pub fn to_local(&self, direction: &Vec3) -> Vec3 {
+        Vec3::new(
+            Vec3::dot(direction, &self.tangent),
+            Vec3::dot(direction, &self.bitangent),
+            Vec3::dot(direction, &self.normal),
+        )
+    }
+
+    pub fn to_world(&self, direction: &Vec3) -> Vec3 {
+        direction.x * self.tangent + direction.y * self.bitangent + direction.z * self.normal
+    }
+}
+
+// isotropic GGX, alpha is the square of the perceptual roughness in [0, 1] so that the
+// roughness feels linear like in other renderers
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrowbridgeReitz {
+    pub alpha: f64,
+}
+
+impl TrowbridgeReitz {
+    pub fn new(roughness: f64) -> TrowbridgeReitz {
+        TrowbridgeReitz {
+            alpha: (roughness.clamp(0.0, 1.0) * roughness.clamp(0.0, 1.0)).max(MIN_ALPHA),
+        }
+    }
+
+    // too sharp to be evaluated towards lights, the rough materials report their samples as
+    // specular then
+    pub fn is_smooth(&self) -> bool {
+        self.alpha < SMOOTH_ALPHA
+    }
+
+    // density of microfacet normals per solid angle, projected onto the surface it integrates
+    // to 1
+    pub fn d(&self, normal: &Vec3) -> f64 {
+        if normal.z <= 0.0 {
+            return 0.0;
+        }
+        let cos2 = normal.z * normal.z;
+        let alpha2 = self.alpha * self.alpha;
+        let denominator = cos2 * (alpha2 - 1.0) + 1.0;
+        alpha2 / (PI * denominator * denominator)
+    }
+
+    // smith's auxiliary function: microfacet area hidden from w per visible area
+    pub fn lambda(&self, w: &Vec3) -> f64 {
+        let cos2 = w.z * w.z;
+        if cos2 <= 0.0 {
+            return INFINITE_LAMBDA;
+        }
+        let tan2 = (1.0 - cos2).max(0.0) / cos2;
+        0.5 * ((1.0 + self.alpha * self.alpha * tan2).sqrt() - 1.0)
+    }
+
+    // fraction of the microfacets visible from w
+    pub fn g1(&self, w: &Vec3) -> f64 {
+        1.0 / (1.0 + self.lambda(w))
+    }
+
+    // fraction visible from both directions, correlated through their heights
+    pub fn g(&self, wo: &Vec3, wi: &Vec3) -> f64 {
+        1.0 / (1.0 + self.lambda(wo) + self.lambda(wi))
+    }
+
+    // density of the normals seen from w, weighted by their projected area towards w
+    pub fn visible_pdf(&self, w: &Vec3, normal: &Vec3) -> f64 {
+        if w.z == 0.0 {
+            return 0.0;
+        }
+        self.g1(w) / w.z.abs() * self.d(normal) * Vec3::dot(w, normal).max(0.0)
+    }
+
+    // solid angle density of wi when wo is reflected at a normal distributed by visible_pdf
+    pub fn reflection_pdf(&self, wo: &Vec3, wi: &Vec3) -> f64 {
+        if wo.z <= 0.0 || wi.z <= 0.0 {
+            return 0.0;
+        }
+        let half_vector = Vec3::unit_vector(&(*wo + *wi));
+        self.visible_pdf(wo, &half_vector) / (4.0 * Vec3::dot(wo, &half_vector))
+    }
+
+    // a normal distributed by visible_pdf. w has to be above the surface
+    pub fn sample_visible_normal(&self, w: &Vec3) -> Vec3 {
+        self.visible_normal(w, util::random(), util::random())
+    }
+
+    // maps two numbers in [0, 1) to a visible normal by stretching the distribution into a
+    // hemisphere and picking a point on its projected disk as seen from w (Heitz 2018)
+    fn visible_normal(&self, w: &Vec3, random_radius: f64, random_angle: f64) -> Vec3 {
+        let stretched = Vec3::unit_vector(&Vec3::new(self.alpha * w.x, self.alpha * w.y, w.z));
+        let length2 = stretched.x * stretched.x + stretched.y * stretched.y;
+        let tangent = if length2 > 0.0 {
+            Vec3::new(-stretched.y, stretched.x, 0.0) / length2.sqrt()
+        } else {
+            Vec3::new(1.0, 0.0, 0.0)
+        };
+        let bitangent = Vec3::cross(&stretched, &tangent);
+
+        // uniform in the disk, squeezed onto the part of it not hidden by the hemisphere
+        let radius = random_radius.sqrt();
+        let phi = 2.0 * PI * random_angle;
+        let x = radius * phi.cos();
+        let mut y = radius * phi.sin();
+        let s = 0.5 * (1.0 + stretched.z);
+        y = (1.0 - s) * (1.0 - x * x).max(0.0).sqrt() + s * y;
+        let z = (1.0 - x * x - y * y).max(0.0).sqrt();
+
+        let normal = x * tangent + y * bitangent + z * stretched;
+        Vec3::unit_vector(&Vec3::new(
+            self.alpha * normal.x,
+            self.alpha * normal.y,
+            normal.z.max(1e-6),
+        ))
+    }
+
+    // fraction of the light arriving at the given angle that a white mirroring surface
+    // reflects directly, the rest is reflected more than once between microfacets
+    pub fn conductor_albedo(&self, cos_theta_o: f64) -> f64 {
+        let tables = ALBEDO_TABLES.get_or_init(AlbedoTables::new);
+        tables.conductor.value(&Vec3::new(
+            table_coordinate(cos_theta_o),
+            table_coordinate(self.alpha.sqrt()),
+            0.5,
+        ))
+    }
+
+    // the same for a clear dielectric, summed over reflection and refraction. eta is the
+    // index of refraction on the side of the light over the one on the other side
+    pub fn dielectric_albedo(&self, cos_theta_o: f64, eta: f64) -> f64 {
+        let tables = ALBEDO_TABLES.get_or_init(AlbedoTables::new);
+        let (table, index_of_refraction) = if eta < 1.0 {
+            (&tables.entering, 1.0 / eta)
+        } else {
+            (&tables.leaving, eta)
+        };
+        table.value(&Vec3::new(
+            table_coordinate(cos_theta_o),
+            table_coordinate(self.alpha.sqrt()),
+            table_coordinate((index_of_refraction - 1.0) / (MAX_INDEX_OF_REFRACTION - 1.0)),
+        ))
+    }
+
+    // mean of the weights of a scattering model over stratified visible normals
+    fn albedo(&self, cos_theta_o: f64, weight: impl Fn(&Vec3, &Vec3) -> f64) -> f64 {
+        let wo = Vec3::new(
+            (1.0 - cos_theta_o * cos_theta_o).max(0.0).sqrt(),
+            0.0,
+            cos_theta_o,
+        );
+        let stratum = |i: usize| (i as f64 + 0.5) / ALBEDO_STRATA as f64;
+        let mut total = 0.0;
+        for i in 0..ALBEDO_STRATA {
+            for j in 0..ALBEDO_STRATA {
+                let normal = self.visible_normal(&wo, stratum(i), stratum(j));
+                total += weight(&wo, &normal);
+            }
+        }
+        total / (ALBEDO_STRATA * ALBEDO_STRATA) as f64
+    }
+}
+
+// mirror direction of w at a surface with the given normal
+pub fn reflect(w: &Vec3, normal: &Vec3) -> Vec3 {
+    2.0 * Vec3::dot(w, normal) * *normal - *w
+}
+
+// direction of w refracted through a surface with the given normal on its side, eta is the
+// index of refraction on the side of w over the one on the other side. None for total
+// internal reflection
+pub fn refract(w: &Vec3, normal: &Vec3, eta: f64) -> Option<Vec3> {
+    let cos_theta_i = Vec3::dot(w, normal);
+    let sin2_theta_t = eta * eta * (1.0 - cos_theta_i * cos_theta_i).max(0.0);
+    if sin2_theta_t >= 1.0 {
+        return None;
+    }
+    let cos_theta_t = (1.0 - sin2_theta_t).sqrt();
+    Some(-eta * *w + (eta * cos_theta_i - cos_theta_t) * *normal)
+}
+
+// directional albedos for every angle (x), roughness (y) and index of refraction (z), with
+// the first and last voxel centers at the ends of the parameter ranges. The rough materials
+// divide by them to give back the light single scattering misses, so a white furnace stays
+// white at every roughness
+struct AlbedoTables {
+    conductor: VoxelGrid,
+    entering: VoxelGrid, // dielectrics seen from outside
+    leaving: VoxelGrid,  // and from inside
+}
+
+static ALBEDO_TABLES: OnceLock<AlbedoTables> = OnceLock::new();
+
+impl AlbedoTables {
+    fn new() -> AlbedoTables {
+        let parameter = |i: usize| i as f64 / (ALBEDO_RESOLUTION - 1) as f64;
+        let distributions: Vec<(f64, TrowbridgeReitz)> = (0..ALBEDO_RESOLUTION)
+            .flat_map(|j| {
+                (0..ALBEDO_RESOLUTION).map(move |i| {
+                    // light arriving exactly along the surface never hits it
+                    (parameter(i).max(1e-3), TrowbridgeReitz::new(parameter(j)))
+                })
+            })
+            .collect();
+
+        let conductor = distributions
+            .iter()
+            .map(|(cos_theta_o, distribution)| {
+                distribution.albedo(*cos_theta_o, |wo, normal| {
+                    let wi = reflect(wo, normal);
+                    if wi.z <= 0.0 {
+                        return 0.0;
+                    }
+                    distribution.g(wo, &wi) / distribution.g1(wo)
+                })
+            })
+            .collect();
+
+        let dielectric = |entering: bool| {
+            let mut values = Vec::with_capacity(ALBEDO_RESOLUTION.pow(3));
+            for k in 0..ALBEDO_RESOLUTION {
+                let index_of_refraction = 1.0 + parameter(k) * (MAX_INDEX_OF_REFRACTION - 1.0);
+                let eta = if entering {
+                    1.0 / index_of_refraction
+                } else {
+                    index_of_refraction
+                };
+                values.extend(distributions.iter().map(|(cos_theta_o, distribution)| {
+                    distribution.albedo(*cos_theta_o, |wo, normal| {
+                        let visible = |wi: &Vec3| distribution.g(wo, wi) / distribution.g1(wo);
+                        let reflectance = fresnel_dielectric(Vec3::dot(wo, normal), 1.0 / eta);
+                        let reflected = reflect(wo, normal);
+                        let mut weight = 0.0;
+                        if reflected.z > 0.0 {
+                            weight += reflectance * visible(&reflected);
+                        }
+                        if let Some(refracted) = refract(wo, normal, eta) {
+                            if refracted.z < 0.0 {
+                                weight += (1.0 - reflectance) * visible(&refracted);
+                            }
+                        }
+                        weight
+                    })
+                }));
+            }
+            VoxelGrid::new([ALBEDO_RESOLUTION; 3], values)
+        };
+
+        AlbedoTables {
+            conductor: VoxelGrid::new([ALBEDO_RESOLUTION, ALBEDO_RESOLUTION, 1], conductor),
+            entering: dielectric(true),
+            leaving: dielectric(false),
+        }
+    }
+}
+
+// where a parameter in [0, 1] is looked up in the albedo tables
+fn table_coordinate(parameter: f64) -> f64 {
+    (parameter.clamp(0.0, 1.0) * (ALBEDO_RESOLUTION - 1) as f64 + 0.5) / ALBEDO_RESOLUTION as f64
+}
+
+// unpolarized fresnel reflectance of a dielectric with index of refraction eta inside and 1
+// outside, cos_theta_i is negative for light arriving from inside
+pub fn fresnel_dielectric(cos_theta_i: f64, eta: f64) -> f64 {
+    let (cos_theta_i, eta) = if cos_theta_i < 0.0 {
+        (-cos_theta_i, 1.0 / eta)
+    } else {
+        (cos_theta_i, eta)
+    };
+    let cos_theta_i = cos_theta_i.min(1.0);
+    let sin_theta_t = (1.0 - cos_theta_i * cos_theta_i).max(0.0).sqrt() / eta;
+    if sin_theta_t >= 1.0 {
+        return 1.0; // total internal reflection
+    }
+    let cos_theta_t = (1.0 - sin_theta_t * sin_theta_t).max(0.0).sqrt();
+    let parallel = (eta * cos_theta_i - cos_theta_t) / (eta * cos_theta_i + cos_theta_t);
+    let perpendicular = (cos_theta_i - eta * cos_theta_t) / (cos_theta_i + eta * cos_theta_t);
+    0.5 * (parallel * parallel + perpendicular * perpendicular)
+}
+
+// schlick's approximation for surfaces described by their reflectance at normal incidence,
+// like metals given by a color
+pub fn fresnel_schlick(cos_theta_i: f64, reflectance: Vec3) -> Vec3 {
+    let weight = (1.0 - cos_theta_i.clamp(0.0, 1.0)).powi(5);
+    reflectance + (Vec3::new(1.0, 1.0, 1.0) - reflectance) * weight
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // uniform directions on the sphere estimate the integral of a function over it
+    fn integrate(samples: usize, function: impl Fn(&Vec3) -> f64) -> f64 {
+        (0..samples)
+            .map(|_| function(&Vec3::random_unit_vector()) * 4.0 * PI / samples as f64)
+            .sum()
+    }
+
+    #[test]
+    fn projected_normals_cover_the_surface() {
+        for roughness in [0.3, 0.6, 1.0] {
+            let distribution = TrowbridgeReitz::new(roughness);
+            // D only depends on cos(theta), 2π ∫ D cos dcos over [0, 1] with the midpoint rule
+            let steps = 100000;
+            let area: f64 = (0..steps)
+                .map(|i| {
+                    let cos_theta = (i as f64 + 0.5) / steps as f64;
+                    let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
+                    let normal = Vec3::new(sin_theta, 0.0, cos_theta);
+                    2.0 * PI * distribution.d(&normal) * cos_theta / steps as f64
+                })
+                .sum();
+            assert!((area - 1.0).abs() < 1e-3, "{}: {}", roughness, area);
+        }
+    }
+
+    #[test]
+    fn visible_normals_are_sampled_by_their_density() {
+        let distribution = TrowbridgeReitz::new(0.7);
+        let w = Vec3::unit_vector(&Vec3::new(0.6, -0.2, 0.5));
+        let total = integrate(400000, |normal| distribution.visible_pdf(&w, normal));
+        assert!((total - 1.0).abs() < 0.03, "{}", total);
+
+        // the mean normal of the samples matches the one of the density
+        let samples = 100000;
+        let mut sampled = Vec3::new(0.0, 0.0, 0.0);
+        for _ in 0..samples {
+            let normal = distribution.sample_visible_normal(&w);
+            assert!(normal.z > 0.0);
+            sampled += normal / samples as f64;
+        }
+        let expected_x = integrate(400000, |normal| {
+            normal.x * distribution.visible_pdf(&w, normal)
+        });
+        assert!((sampled.x - expected_x).abs() < 0.02);
+    }
+
+    #[test]
+    fn rougher_surfaces_scatter_less_light_directly() {
+        let albedo = |roughness: f64| TrowbridgeReitz::new(roughness).conductor_albedo(0.8);
+        assert!((albedo(0.0) - 1.0).abs() < 1e-3);
+        assert!(albedo(0.3) < albedo(0.0) && albedo(1.0) < albedo(0.3));
+        // refraction into a denser medium is seldom shadowed
+        let glass = TrowbridgeReitz::new(0.6);
+        assert!(glass.dielectric_albedo(0.8, 1.0 / 1.5) > glass.dielectric_albedo(0.8, 1.5));
+    }
+
+    #[test]
+    fn fresnel_limits() {
+        assert!((fresnel_dielectric(1.0, 1.5) - 0.04).abs() < 1e-9);
+        assert!((fresnel_dielectric(-1.0, 1.5) - 0.04).abs() < 1e-9);
+        assert_eq!(fresnel_dielectric(-0.1, 1.5), 1.0);
+        assert!((fresnel_dielectric(1e-9, 1.5) - 1.0).abs() < 1e-6);
+        let white = Vec3::new(1.0, 1.0, 1.0);
+        assert_eq!(fresnel_schlick(0.3, white), white);
+    }
+}
@@ -0,0 +1,92 @@
+use crate::{
+    aabb::Aabb,
+    animated_transform::AnimatedTransform,
+    hittable::{HitRecord, Hittable},
+    ray::Ray,
+};
+
+use super::transformed::record_to_world;
+
+// like objects::transformed::Transformed with a transform that depends on the time of the
+// ray, so objects moving, turning or growing during the shutter interval are blurred
+pub struct Animated<H: Hittable> {
+    pub object: H,
+    pub transform: AnimatedTransform,
+}
+
+impl<H: Hittable> Animated<H> {
+    pub fn new(object: H, transform: AnimatedTransform) -> Animated<H> {
+        Animated { object, transform }
+    }
+}
+
+impl<H: Hittable> Hittable for Animated<H> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
+        let transform = self.transform.at(ray.time);
+        let record = self
+            .object
+            .hit(&transform.ray_to_object(ray), t_min, t_max)?;
+        Some(record_to_world(&transform, record))
+    }
+
+    fn hit_all(&self, ray: &Ray, t_min: f64, t_max: f64) -> Vec<HitRecord<'_>> {
+        let transform = self.transform.at(ray.time);
+        self.object
+            .hit_all(&transform.ray_to_object(ray), t_min, t_max)
+            .into_iter()
+            .map(|record| record_to_world(&transform, record))
+            .collect()
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let object_box = self.object.bounding_box()?;
+        Some(self.transform.bounding_box(&object_box))
+    }
+
+    fn transmittance(&self, ray: &Ray, t_min: f64, t_max: f64) -> f64 {
+        let transform = self.transform.at(ray.time);
+        self.object
+            .transmittance(&transform.ray_to_object(ray), t_min, t_max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        materials::lambertian::Lambertian, objects::sphere::Sphere, transform::Transform,
+        util::INFTY, vec3::Vec3,
+    };
+
+    #[test]
+    fn follows_the_transform_over_time() {
+        let sphere = Sphere {
+            center: Vec3::new(0.0, 0.0, 0.0),
+            radius: 1.0,
+            material: Box::new(Lambertian::new(Vec3::new(0.5, 0.5, 0.5))),
+        };
+        let animated = Animated::new(
+            sphere,
+            AnimatedTransform::new(
+                Transform::default(),
+                Transform::default()
+                    .scale(Vec3::new(2.0, 2.0, 2.0))
+                    .translate(Vec3::new(10.0, 0.0, 0.0)),
+                0.0,
+                1.0,
+            ),
+        );
+        let direction = Vec3::new(0.0, 0.0, 1.0);
+        let ray = Ray::with_time(Vec3::new(5.0, 0.0, -10.0), direction, 0.5);
+        let record = animated.hit(&ray, 0.001, INFTY).unwrap();
+        // halfway the sphere has a radius of 1.5 around x = 5
+        assert!((record.point - Vec3::new(5.0, 0.0, -1.5)).length() < 1e-9);
+        assert!((record.normal - Vec3::new(0.0, 0.0, -1.0)).length() < 1e-9);
+        assert!(animated
+            .hit(&Ray::with_time(ray.origin, direction, 0.0), 0.001, INFTY)
+            .is_none());
+
+        let bounding_box = animated.bounding_box().unwrap();
+        assert!(bounding_box.minimum.x <= -1.0 && bounding_box.maximum.x >= 12.0);
+    }
+}
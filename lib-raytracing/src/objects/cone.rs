@@ -0,0 +1,147 @@
+use crate::{
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable},
+    material::Material,
+    polynomial::solve_quadratic,
+    ray::Ray,
+    vec3::Vec3,
+};
+
+use super::{disk, sphere::Sphere};
+
+// cone standing on the disk around base with its apex height up along +y, other
+// orientations are a Transformed<Cone>. u wraps around the axis like on a sphere,
+// v runs from the base to the apex on the side and from the axis to the rim on the cap
+pub struct Cone {
+    pub base: Vec3,
+    pub radius: f64,
+    pub height: f64,
+    pub capped: bool,
+    pub material: Box<dyn Material>,
+}
+
+impl Cone {
+    pub fn new(base: Vec3, radius: f64, height: f64, material: Box<dyn Material>) -> Cone {
+        Cone {
+            base,
+            radius,
+            height,
+            capped: true,
+            material,
+        }
+    }
+
+    pub fn without_cap(mut self) -> Cone {
+        self.capped = false;
+        self
+    }
+}
+
+impl Hittable for Cone {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
+        let local = Ray::with_time(ray.origin - self.base, ray.direction, ray.time);
+        let (origin, direction) = (local.origin, local.direction);
+        let mut closest = None; // distance, outward normal and uv of the nearest hit
+        let mut closest_so_far = t_max;
+
+        // side: x² + z² = (slope (height - y))² with the slope radius / height
+        let slope = self.radius / self.height;
+        let slope2 = slope * slope;
+        let to_apex = self.height - origin.y;
+        let a = direction.x * direction.x + direction.z * direction.z
+            - slope2 * direction.y * direction.y;
+        let b = 2.0
+            * (origin.x * direction.x + origin.z * direction.z + slope2 * to_apex * direction.y);
+        let c = origin.x * origin.x + origin.z * origin.z - slope2 * to_apex * to_apex;
+        if let Some((t0, t1)) = solve_quadratic(a, b, c) {
+            for t in [t0, t1] {
+                let point = local.at(t);
+                if t < t_min || t > closest_so_far || !(0.0..=self.height).contains(&point.y) {
+                    continue;
+                }
+                // gradient of the implicit surface
+                let outward_normal = Vec3::unit_vector(&Vec3::new(
+                    point.x,
+                    slope2 * (self.height - point.y),
+                    point.z,
+                ));
+                let (u, _) = Sphere::get_sphere_uv(&Vec3::new(point.x, 0.0, point.z));
+                closest = Some((t, outward_normal, u, point.y / self.height));
+                closest_so_far = t;
+                break;
+            }
+        }
+
+        if self.capped {
+            let center = Vec3::new(0.0, 0.0, 0.0);
+            let normal = Vec3::new(0.0, -1.0, 0.0);
+            if let Some(t) =
+                disk::intersect(&center, &normal, self.radius, &local, t_min, closest_so_far)
+            {
+                let point = local.at(t);
+                let (u, _) = Sphere::get_sphere_uv(&Vec3::new(point.x, 0.0, point.z));
+                closest = Some((t, normal, u, point.length() / self.radius));
+            }
+        }
+
+        let (t, outward_normal, u, v) = closest?;
+        let mut record = HitRecord {
+            point: ray.at(t),
+            normal: outward_normal,
+            distance: t,
+            u,
+            v,
+            front_face: false,
+            vertex_color: None,
+            tangent: None,
+            material: self.material.as_ref(),
+        };
+        record.set_face_normal(ray, outward_normal);
+        Some(record)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let extent = Vec3::new(self.radius, 0.0, self.radius);
+        let apex = self.base + Vec3::new(0.0, self.height, 0.0);
+        Some(Aabb::new(self.base - extent, apex + extent))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::materials::lambertian::Lambertian;
+
+    fn cone() -> Cone {
+        Cone::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            1.0,
+            1.0,
+            Box::new(Lambertian::new(Vec3::new(0.5, 0.5, 0.5))),
+        )
+    }
+
+    #[test]
+    fn side_normal_leans_up() {
+        let cone = cone();
+        let ray = Ray::new(Vec3::new(5.0, 0.5, 0.0), Vec3::new(-1.0, 0.0, 0.0));
+        let record = cone.hit(&ray, 0.001, f64::MAX).unwrap();
+        assert!((record.distance - 4.5).abs() < 1e-12);
+        let expected = Vec3::unit_vector(&Vec3::new(1.0, 1.0, 0.0));
+        assert!((record.normal - expected).length() < 1e-12);
+        assert!((record.v - 0.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn cap_and_the_upper_nappe() {
+        let cone = cone();
+        let from_below = Ray::new(Vec3::new(0.5, -2.0, 0.0), Vec3::new(0.0, 1.0, 0.0));
+        let record = cone.hit(&from_below, 0.001, f64::MAX).unwrap();
+        assert!((record.distance - 2.0).abs() < 1e-12);
+        assert_eq!(record.normal, Vec3::new(0.0, -1.0, 0.0));
+
+        // the mirrored cone above the apex is not part of the object
+        let above = Ray::new(Vec3::new(5.0, 1.5, 0.0), Vec3::new(-1.0, 0.0, 0.0));
+        assert!(cone.hit(&above, 0.001, f64::MAX).is_none());
+    }
+}
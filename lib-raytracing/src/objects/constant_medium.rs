@@ -0,0 +1,142 @@
+use crate::{
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable},
+    material::Material,
+    materials::isotropic::Isotropic,
+    ray::Ray,
+    util::{self, INFTY},
+    vec3::Vec3,
+};
+
+// fog or smoke of uniform density filling a closed boundary object. Rays passing through
+// scatter after an exponentially distributed distance, the phase function is the material
+// used at the scattering points (usually Isotropic)
+pub struct ConstantMedium {
+    pub boundary: Box<dyn Hittable>,
+    pub density: f64, // probability of scattering per unit length
+    pub phase_function: Box<dyn Material>,
+}
+
+impl ConstantMedium {
+    pub fn new(boundary: impl Hittable + 'static, density: f64, color: Vec3) -> ConstantMedium {
+        ConstantMedium {
+            boundary: Box::new(boundary),
+            density,
+            phase_function: Box::new(Isotropic::new(color)),
+        }
+    }
+
+    // the part of the ray inside the boundary that starts at or after t_min
+    fn inside_interval(&self, ray: &Ray, t_min: f64) -> Option<(f64, f64)> {
+        let crossings = self.boundary.hit_all(ray, t_min, INFTY);
+        let mut crossings = crossings
+            .iter()
+            .map(|record| (record.distance, record.front_face));
+        match crossings.next()? {
+            // the ray starts inside
+            (exit, false) => Some((t_min, exit)),
+            // unbounded boundaries (e.g. planes) may not be left again
+            (entry, true) => Some((entry, crossings.next().map_or(INFTY, |(exit, _)| exit))),
+        }
+    }
+}
+
+impl Hittable for ConstantMedium {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
+        let (entry, exit) = self.inside_interval(ray, t_min)?;
+        let exit = exit.min(t_max);
+        if entry >= exit {
+            return None;
+        }
+
+        let length = ray.direction.length();
+        let distance_inside = (exit - entry) * length;
+        let scatter_distance = -(1.0 - util::random()).ln() / self.density;
+        if scatter_distance > distance_inside {
+            return None;
+        }
+
+        let t = entry + scatter_distance / length;
+        Some(HitRecord {
+            point: ray.at(t),
+            normal: Vec3::new(1.0, 0.0, 0.0), // arbitrary, phase functions do not use it
+            distance: t,
+            u: 0.0,
+            v: 0.0,
+            front_face: true,
+            vertex_color: None,
+            tangent: None,
+            material: self.phase_function.as_ref(),
+        })
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.boundary.bounding_box()
+    }
+
+    // known in closed form for a uniform density
+    fn transmittance(&self, ray: &Ray, t_min: f64, t_max: f64) -> f64 {
+        match self.inside_interval(ray, t_min) {
+            Some((entry, exit)) if entry < exit.min(t_max) => {
+                let distance_inside = (exit.min(t_max) - entry) * ray.direction.length();
+                (-self.density * distance_inside).exp()
+            }
+            _ => 1.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{materials::lambertian::Lambertian, objects::sphere::Sphere};
+
+    fn fog(density: f64) -> ConstantMedium {
+        ConstantMedium::new(
+            Sphere {
+                center: Vec3::new(0.0, 0.0, 0.0),
+                radius: 1.0,
+                material: Box::new(Lambertian::new(Vec3::new(0.5, 0.5, 0.5))),
+            },
+            density,
+            Vec3::new(1.0, 1.0, 1.0),
+        )
+    }
+
+    #[test]
+    fn hits_stay_inside_the_boundary() {
+        let fog = fog(2.0);
+        let ray = Ray::new(Vec3::new(0.0, 0.0, -5.0), Vec3::new(0.0, 0.0, 2.0));
+        for _ in 0..100 {
+            if let Some(record) = fog.hit(&ray, 0.001, INFTY) {
+                assert!(record.point.length() <= 1.0 + 1e-9);
+                assert!((ray.at(record.distance) - record.point).length() < 1e-12);
+            }
+        }
+        let beside = Ray::new(Vec3::new(2.0, 0.0, -5.0), Vec3::new(0.0, 0.0, 1.0));
+        assert!(fog.hit(&beside, 0.001, INFTY).is_none());
+    }
+
+    #[test]
+    fn transmittance_follows_beer_lambert() {
+        // a ray through the center crosses 2 units of medium: e^(-0.5 * 2) pass through
+        let fog = fog(0.5);
+        let ray = Ray::new(Vec3::new(0.0, 0.0, -5.0), Vec3::new(0.0, 0.0, 1.0));
+        let samples = 20000;
+        let passed = (0..samples)
+            .filter(|_| fog.hit(&ray, 0.001, INFTY).is_none())
+            .count();
+        let expected = (-1.0_f64).exp();
+        assert!((passed as f64 / samples as f64 - expected).abs() < 0.02);
+        assert!((fog.transmittance(&ray, 0.001, INFTY) - expected).abs() < 1e-9);
+        assert_eq!(fog.transmittance(&ray, 0.001, 3.0), 1.0);
+    }
+
+    #[test]
+    fn rays_starting_inside_scatter_too() {
+        let fog = fog(1000.0);
+        let ray = Ray::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        let record = fog.hit(&ray, 0.001, INFTY).unwrap();
+        assert!(record.distance < 0.1);
+    }
+}
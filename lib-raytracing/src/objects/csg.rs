@@ -0,0 +1,199 @@
+use crate::{
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable},
+    ray::Ray,
+    util::INFTY,
+    vec3::Vec3,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Operation {
+    Union,
+    Intersection,
+    Difference, // left without right
+}
+
+impl Operation {
+    fn contains(&self, inside_left: bool, inside_right: bool) -> bool {
+        match self {
+            Operation::Union => inside_left || inside_right,
+            Operation::Intersection => inside_left && inside_right,
+            Operation::Difference => inside_left && !inside_right,
+        }
+    }
+}
+
+// constructive solid geometry of two closed objects. Both keep their own materials, the
+// surface of the result is made of the crossings where the ray enters or leaves the
+// combined volume. Csg objects can be nested
+pub struct Csg {
+    pub left: Box<dyn Hittable>,
+    pub right: Box<dyn Hittable>,
+    pub operation: Operation,
+}
+
+impl Csg {
+    pub fn new(
+        left: impl Hittable + 'static,
+        right: impl Hittable + 'static,
+        operation: Operation,
+    ) -> Csg {
+        Csg {
+            left: Box::new(left),
+            right: Box::new(right),
+            operation,
+        }
+    }
+
+    pub fn union(left: impl Hittable + 'static, right: impl Hittable + 'static) -> Csg {
+        Csg::new(left, right, Operation::Union)
+    }
+
+    pub fn intersection(left: impl Hittable + 'static, right: impl Hittable + 'static) -> Csg {
+        Csg::new(left, right, Operation::Intersection)
+    }
+
+    pub fn difference(left: impl Hittable + 'static, right: impl Hittable + 'static) -> Csg {
+        Csg::new(left, right, Operation::Difference)
+    }
+}
+
+impl Hittable for Csg {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
+        self.hit_all(ray, t_min, t_max).into_iter().next()
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let left = self.left.bounding_box();
+        let right = self.right.bounding_box();
+        match self.operation {
+            Operation::Union => Some(Aabb::surrounding_box(&left?, &right?)),
+            Operation::Intersection => match (left, right) {
+                (Some(left), Some(right)) => Some(Aabb::new(
+                    Vec3::component_max(&left.minimum, &right.minimum),
+                    Vec3::component_min(&left.maximum, &right.maximum),
+                )),
+                (left, right) => left.or(right),
+            },
+            Operation::Difference => left,
+        }
+    }
+
+    fn hit_all(&self, ray: &Ray, t_min: f64, t_max: f64) -> Vec<HitRecord<'_>> {
+        // whether the ray starts inside an operand is only known from its first crossing,
+        // so the operands are queried up to infinity and the result is cut at t_max
+        let mut left = self.left.hit_all(ray, t_min, INFTY).into_iter().peekable();
+        let mut right = self.right.hit_all(ray, t_min, INFTY).into_iter().peekable();
+        let mut inside_left = left.peek().is_some_and(|record| !record.front_face);
+        let mut inside_right = right.peek().is_some_and(|record| !record.front_face);
+        let mut inside = self.operation.contains(inside_left, inside_right);
+
+        let mut crossings = vec![];
+        loop {
+            let from_left = match (left.peek(), right.peek()) {
+                (Some(l), Some(r)) => l.distance <= r.distance,
+                (Some(_), None) => true,
+                (None, Some(_)) => false,
+                (None, None) => break,
+            };
+            let mut record = if from_left {
+                let record = left.next().unwrap();
+                inside_left = record.front_face;
+                record
+            } else {
+                let record = right.next().unwrap();
+                inside_right = record.front_face;
+                record
+            };
+            if record.distance > t_max {
+                break;
+            }
+
+            let now_inside = self.operation.contains(inside_left, inside_right);
+            if now_inside != inside {
+                // the normal already faces the ray, only the side of the result changes,
+                // e.g. leaving the subtracted object means entering the difference
+                inside = now_inside;
+                record.front_face = now_inside;
+                crossings.push(record);
+            }
+        }
+        crossings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{materials::lambertian::Lambertian, objects::sphere::Sphere};
+
+    fn sphere(x: f64, radius: f64) -> Sphere {
+        Sphere {
+            center: Vec3::new(x, 0.0, 0.0),
+            radius,
+            material: Box::new(Lambertian::new(Vec3::new(0.5, 0.5, 0.5))),
+        }
+    }
+
+    // along the x axis from the left
+    fn distances(object: &Csg) -> Vec<(f64, bool)> {
+        let ray = Ray::new(Vec3::new(-10.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        object
+            .hit_all(&ray, 0.001, INFTY)
+            .iter()
+            .map(|record| ((record.distance - 10.0), record.front_face))
+            .collect()
+    }
+
+    fn assert_crossings(actual: Vec<(f64, bool)>, expected: &[(f64, bool)]) {
+        assert_eq!(actual.len(), expected.len(), "{:?}", actual);
+        for ((x, front), (expected_x, expected_front)) in actual.iter().zip(expected) {
+            assert!((x - expected_x).abs() < 1e-6, "{:?}", actual);
+            assert_eq!(front, expected_front);
+        }
+    }
+
+    #[test]
+    fn union_drops_inner_surfaces() {
+        let union = Csg::union(sphere(0.0, 1.0), sphere(1.0, 1.0));
+        assert_crossings(distances(&union), &[(-1.0, true), (2.0, false)]);
+    }
+
+    #[test]
+    fn intersection_is_a_lens() {
+        let lens = Csg::intersection(sphere(0.0, 1.0), sphere(1.0, 1.0));
+        assert_crossings(distances(&lens), &[(0.0, true), (1.0, false)]);
+        let bounding_box = lens.bounding_box().unwrap();
+        assert!((bounding_box.minimum.x - 0.0).abs() < 1e-12);
+        assert!((bounding_box.maximum.x - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn difference_turns_the_hole_inside_out() {
+        let carved = Csg::difference(sphere(0.0, 2.0), sphere(0.0, 1.0));
+        assert_crossings(
+            distances(&carved),
+            &[(-2.0, true), (-1.0, false), (1.0, true), (2.0, false)],
+        );
+
+        // the nearest hit from inside the hole is where the shell starts again
+        let ray = Ray::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        let record = carved.hit(&ray, 0.001, INFTY).unwrap();
+        assert!((record.distance - 1.0).abs() < 1e-9);
+        assert!(record.front_face);
+        assert!((record.normal - Vec3::new(-1.0, 0.0, 0.0)).length() < 1e-9);
+    }
+
+    #[test]
+    fn nested_operations() {
+        let lens = Csg::intersection(sphere(0.0, 1.0), sphere(1.0, 1.0));
+        let with_ball = Csg::union(lens, sphere(5.0, 1.0));
+        assert_crossings(
+            distances(&with_ball),
+            &[(0.0, true), (1.0, false), (4.0, true), (6.0, false)],
+        );
+        let ray = Ray::new(Vec3::new(-10.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        assert!(with_ball.hit(&ray, 0.001, 10.5).is_some());
+        assert!(with_ball.hit(&ray, 0.001, 9.5).is_none());
+    }
+}
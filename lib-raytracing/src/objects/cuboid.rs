@@ -0,0 +1,107 @@
+use crate::{
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable},
+    material::Material,
+    ray::Ray,
+    vec3::Vec3,
+};
+
+use super::quad::{hit_record, intersect};
+
+// axis aligned box made of six quads sharing one material, each side has its own 0..1
+// surface coordinates. Rotated boxes are a Transformed<Cuboid>
+pub struct Cuboid {
+    sides: [(Vec3, Vec3, Vec3); 6], // corner, u and v of each side, facing outwards
+    material: Box<dyn Material>,
+}
+
+impl Cuboid {
+    // a and b are any two opposite corners
+    pub fn new(a: Vec3, b: Vec3, material: Box<dyn Material>) -> Cuboid {
+        let minimum = Vec3::component_min(&a, &b);
+        let maximum = Vec3::component_max(&a, &b);
+        let dx = Vec3::new(maximum.x - minimum.x, 0.0, 0.0);
+        let dy = Vec3::new(0.0, maximum.y - minimum.y, 0.0);
+        let dz = Vec3::new(0.0, 0.0, maximum.z - minimum.z);
+
+        let sides = [
+            (Vec3::new(minimum.x, minimum.y, maximum.z), dx, dy), // front
+            (Vec3::new(maximum.x, minimum.y, maximum.z), -1.0 * dz, dy), // right
+            (Vec3::new(maximum.x, minimum.y, minimum.z), -1.0 * dx, dy), // back
+            (Vec3::new(minimum.x, minimum.y, minimum.z), dz, dy), // left
+            (Vec3::new(minimum.x, maximum.y, maximum.z), dx, -1.0 * dz), // top
+            (Vec3::new(minimum.x, minimum.y, minimum.z), dx, dz), // bottom
+        ];
+        Cuboid { sides, material }
+    }
+}
+
+impl Hittable for Cuboid {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
+        let mut closest = None;
+        let mut closest_so_far = t_max;
+        for (corner, u, v) in &self.sides {
+            if let Some(hit) = intersect(corner, u, v, ray, t_min, closest_so_far) {
+                closest_so_far = hit.0;
+                closest = Some((u, v, hit));
+            }
+        }
+        let (u, v, hit) = closest?;
+        Some(hit_record(u, v, ray, hit, self.material.as_ref()))
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let corners: Vec<Vec3> = self
+            .sides
+            .iter()
+            .flat_map(|(corner, u, v)| [*corner, *corner + *u + *v])
+            .collect();
+        Some(Aabb::from_points(&corners).padded(0.0001))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::materials::lambertian::Lambertian;
+
+    fn cuboid() -> Cuboid {
+        Cuboid::new(
+            Vec3::new(1.0, 2.0, 3.0),
+            Vec3::new(-1.0, 0.0, 0.0),
+            Box::new(Lambertian::new(Vec3::new(0.5, 0.5, 0.5))),
+        )
+    }
+
+    #[test]
+    fn all_sides_face_outwards() {
+        let cuboid = cuboid();
+        let center = Vec3::new(0.0, 1.0, 1.5);
+        for direction in [
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(-1.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            Vec3::new(0.0, -1.0, 0.0),
+            Vec3::new(0.0, 0.0, 1.0),
+            Vec3::new(0.0, 0.0, -1.0),
+        ] {
+            // shoot from outside back at the center
+            let ray = Ray::new(center + 10.0 * direction, -1.0 * direction);
+            let record = cuboid.hit(&ray, 0.001, f64::MAX).unwrap();
+            assert!(record.front_face);
+            assert!((record.normal - direction).length() < 1e-12);
+
+            // and from the inside out
+            let ray = Ray::new(center, direction);
+            let record = cuboid.hit(&ray, 0.001, f64::MAX).unwrap();
+            assert!(!record.front_face);
+        }
+    }
+
+    #[test]
+    fn bounding_box_matches_corners() {
+        let bounding_box = cuboid().bounding_box().unwrap();
+        assert!((bounding_box.minimum - Vec3::new(-1.0, 0.0, 0.0)).length() < 0.001);
+        assert!((bounding_box.maximum - Vec3::new(1.0, 2.0, 3.0)).length() < 0.001);
+    }
+}
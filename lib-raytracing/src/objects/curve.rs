@@ -0,0 +1,452 @@
+use crate::{
+    aabb::Aabb,
+    bvh::BvhTree,
+    hittable::{HitRecord, Hittable},
+    material::Material,
+    ray::Ray,
+    vec3::Vec3,
+};
+
+const MAX_SUBDIVISIONS: u32 = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CurveShape {
+    // round strands like hair and fur, shaded as a tube from whichever side they are seen
+    Cylinder,
+    // flat strips like grass blades facing the normals given at the two ends
+    Ribbon { normals: [Vec3; 2] },
+}
+
+// cubic bézier curve swept with a width changing linearly from the first to the last
+// control point. u runs along the curve, v across it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BezierCurve {
+    pub control_points: [Vec3; 4],
+    pub widths: [f64; 2],
+    pub shape: CurveShape,
+}
+
+impl BezierCurve {
+    pub fn new(control_points: [Vec3; 4], widths: [f64; 2], shape: CurveShape) -> BezierCurve {
+        let shape = match shape {
+            CurveShape::Ribbon { normals } => CurveShape::Ribbon {
+                normals: normals.map(|normal| Vec3::unit_vector(&normal)),
+            },
+            CurveShape::Cylinder => CurveShape::Cylinder,
+        };
+        BezierCurve {
+            control_points,
+            widths,
+            shape,
+        }
+    }
+
+    pub fn point(&self, u: f64) -> Vec3 {
+        evaluate(&self.control_points, u)
+    }
+
+    // derivative along u, not normalized
+    pub fn tangent(&self, u: f64) -> Vec3 {
+        let [p0, p1, p2, p3] = self.control_points;
+        let a = p1 - p0;
+        let b = p2 - p1;
+        let c = p3 - p2;
+        3.0 * ((1.0 - u) * (1.0 - u) * a + 2.0 * u * (1.0 - u) * b + u * u * c)
+    }
+
+    pub fn width(&self, u: f64) -> f64 {
+        self.widths[0] + (self.widths[1] - self.widths[0]) * u
+    }
+
+    // the curve stays inside the hull of its control points
+    pub fn bounding_box(&self) -> Aabb {
+        let half_width = 0.5 * self.widths[0].max(self.widths[1]);
+        Aabb::from_points(&self.control_points).padded(half_width)
+    }
+
+    fn ribbon_normal(&self, u: f64) -> Option<Vec3> {
+        match self.shape {
+            CurveShape::Ribbon {
+                normals: [start, end],
+            } => {
+                let normal = start * (1.0 - u) + end * u;
+                Some(if normal.near_zero() {
+                    start
+                } else {
+                    Vec3::unit_vector(&normal)
+                })
+            }
+            CurveShape::Cylinder => None,
+        }
+    }
+
+    // the closest crossing of the ray with the curve treated as a flat band facing the ray
+    // (or oriented by the normals for ribbons). The curve is split in halves until the
+    // pieces are nearly straight, working in a space where the ray runs along +z from the
+    // origin so a piece is hit where it passes the origin closer than half its width
+    pub fn intersect<'a>(
+        &self,
+        ray: &Ray,
+        t_min: f64,
+        t_max: f64,
+        material: &'a dyn Material,
+    ) -> Option<HitRecord<'a>> {
+        let length = ray.direction.length();
+        let forward = ray.direction / length;
+        let (side, up) = Vec3::orthonormal_basis(&forward);
+        let points = self.control_points.map(|point| {
+            let offset = point - ray.origin;
+            Vec3::new(
+                Vec3::dot(&offset, &side),
+                Vec3::dot(&offset, &up),
+                Vec3::dot(&offset, &forward),
+            )
+        });
+
+        // enough halvings that the pieces deviate from their chord by a fraction of the width
+        let bend = (0..2)
+            .map(|i| {
+                let second_difference = points[i] - 2.0 * points[i + 1] + points[i + 2];
+                (0..3).fold(0.0_f64, |bend, axis| {
+                    bend.max(second_difference[axis].abs())
+                })
+            })
+            .fold(0.0, f64::max);
+        let tolerance = 0.05 * self.widths[0].max(self.widths[1]);
+        let subdivisions = if bend > 0.0 && tolerance > 0.0 {
+            let halvings = (std::f64::consts::SQRT_2 * 6.0 * bend / (8.0 * tolerance)).log2();
+            ((halvings.max(0.0) / 2.0) as u32).min(MAX_SUBDIVISIONS)
+        } else {
+            0
+        };
+
+        let mut closest = None;
+        self.intersect_piece(
+            &points,
+            (0.0, 1.0),
+            subdivisions,
+            &forward,
+            t_min * length,
+            &mut (t_max * length),
+            &mut closest,
+        );
+        let (z, u) = closest?;
+        Some(self.record(ray, z / length, u, &forward, material))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn intersect_piece(
+        &self,
+        points: &[Vec3; 4],
+        (u0, u1): (f64, f64),
+        subdivisions: u32,
+        forward: &Vec3,
+        z_min: f64,
+        z_max: &mut f64,
+        closest: &mut Option<(f64, f64)>,
+    ) {
+        let half_width = 0.5 * self.width(u0).max(self.width(u1));
+        let bounds = Aabb::from_points(points).padded(half_width);
+        if bounds.minimum.x > 0.0
+            || bounds.maximum.x < 0.0
+            || bounds.minimum.y > 0.0
+            || bounds.maximum.y < 0.0
+            || bounds.maximum.z < z_min
+            || bounds.minimum.z > *z_max
+        {
+            return;
+        }
+
+        if subdivisions > 0 {
+            let (first, second) = split(points);
+            let middle = 0.5 * (u0 + u1);
+            for (piece, range) in [(first, (u0, middle)), (second, (middle, u1))] {
+                self.intersect_piece(
+                    &piece,
+                    range,
+                    subdivisions - 1,
+                    forward,
+                    z_min,
+                    z_max,
+                    closest,
+                );
+            }
+            return;
+        }
+
+        // the ray has to pass between the planes through the ends perpendicular to the piece
+        let [p0, p1, p2, p3] = *points;
+        if (p1.y - p0.y) * -p0.y + p0.x * (p0.x - p1.x) < 0.0
+            || (p2.y - p3.y) * -p3.y + p3.x * (p3.x - p2.x) < 0.0
+        {
+            return;
+        }
+        // parameter of the point on the chord closest to the ray
+        let chord = (p3.x - p0.x, p3.y - p0.y);
+        let chord_length2 = chord.0 * chord.0 + chord.1 * chord.1;
+        if chord_length2 == 0.0 {
+            return;
+        }
+        let w = (-p0.x * chord.0 - p0.y * chord.1) / chord_length2;
+        let u = (u0 + w * (u1 - u0)).clamp(u0, u1);
+
+        let mut hit_width = self.width(u);
+        if let Some(normal) = self.ribbon_normal(u) {
+            // ribbons seen at a grazing angle get thinner
+            hit_width *= Vec3::dot(&normal, forward).abs();
+        }
+        let closest_point = evaluate(points, w.clamp(0.0, 1.0));
+        let distance2 = closest_point.x * closest_point.x + closest_point.y * closest_point.y;
+        if distance2 > 0.25 * hit_width * hit_width
+            || closest_point.z < z_min
+            || closest_point.z > *z_max
+        {
+            return;
+        }
+        *z_max = closest_point.z;
+        *closest = Some((closest_point.z, u));
+    }
+
+    fn record<'a>(
+        &self,
+        ray: &Ray,
+        t: f64,
+        u: f64,
+        forward: &Vec3,
+        material: &'a dyn Material,
+    ) -> HitRecord<'a> {
+        let point = ray.at(t);
+        let derivative = self.tangent(u);
+        let tangent = if derivative.near_zero() {
+            Vec3::unit_vector(&(self.control_points[3] - self.control_points[0]))
+        } else {
+            Vec3::unit_vector(&derivative)
+        };
+        // towards the ray, perpendicular to the curve
+        let towards_ray = -1.0 * *forward + Vec3::dot(forward, &tangent) * tangent;
+        let facing = if towards_ray.near_zero() {
+            Vec3::orthonormal_basis(&tangent).0
+        } else {
+            Vec3::unit_vector(&towards_ray)
+        };
+        let offset = point - self.point(u);
+        let width = self.width(u).max(1e-12);
+
+        let (outward_normal, across) = match self.ribbon_normal(u) {
+            Some(normal) => (normal, Vec3::cross(&normal, &tangent)),
+            None => {
+                // the tube bulges towards the ray, the further off its axis the more the
+                // normal tilts sideways
+                let across = Vec3::cross(&facing, &tangent);
+                let sine = (2.0 * Vec3::dot(&offset, &across) / width).clamp(-1.0, 1.0);
+                let cosine = (1.0 - sine * sine).sqrt();
+                (sine * across + cosine * facing, across)
+            }
+        };
+        let mut record = HitRecord {
+            point,
+            normal: outward_normal,
+            distance: t,
+            u,
+            v: (0.5 + Vec3::dot(&offset, &across) / width).clamp(0.0, 1.0),
+            front_face: false,
+            vertex_color: None,
+            tangent: Some(tangent),
+            material,
+        };
+        record.set_face_normal(ray, outward_normal);
+        record
+    }
+}
+
+fn evaluate(points: &[Vec3; 4], u: f64) -> Vec3 {
+    let v = 1.0 - u;
+    v * v * v * points[0]
+        + 3.0 * v * v * u * points[1]
+        + 3.0 * v * u * u * points[2]
+        + u * u * u * points[3]
+}
+
+// de casteljau at the middle, both halves are again cubic bézier curves
+fn split(points: &[Vec3; 4]) -> ([Vec3; 4], [Vec3; 4]) {
+    let [p0, p1, p2, p3] = *points;
+    let a = 0.5 * (p0 + p1);
+    let b = 0.5 * (p1 + p2);
+    let c = 0.5 * (p2 + p3);
+    let d = 0.5 * (a + b);
+    let e = 0.5 * (b + c);
+    let middle = 0.5 * (d + e);
+    ([p0, a, d, middle], [middle, e, c, p3])
+}
+
+// a single curve with its own material
+pub struct Curve {
+    pub curve: BezierCurve,
+    pub material: Box<dyn Material>,
+}
+
+impl Curve {
+    pub fn new(curve: BezierCurve, material: Box<dyn Material>) -> Curve {
+        Curve { curve, material }
+    }
+}
+
+impl Hittable for Curve {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
+        self.curve
+            .intersect(ray, t_min, t_max, self.material.as_ref())
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(self.curve.bounding_box())
+    }
+}
+
+// many curves sharing one material, like the strands of a fur coat or a lawn, with their
+// own bvh so millions of them stay cheap to trace
+pub struct Curves {
+    curves: Vec<BezierCurve>,
+    material: Box<dyn Material>,
+    tree: BvhTree,
+}
+
+impl Curves {
+    pub fn new(curves: Vec<BezierCurve>, material: Box<dyn Material>) -> Curves {
+        let bounding_boxes: Vec<Aabb> = curves.iter().map(BezierCurve::bounding_box).collect();
+        let (tree, order) = BvhTree::build(&bounding_boxes);
+        Curves {
+            curves: order.into_iter().map(|index| curves[index]).collect(),
+            material,
+            tree,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.curves.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.curves.is_empty()
+    }
+}
+
+impl Hittable for Curves {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
+        self.tree.hit(ray, t_min, t_max, |index, closest_so_far| {
+            self.curves[index].intersect(ray, t_min, closest_so_far, self.material.as_ref())
+        })
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.tree.bounding_box()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        hittable::{test_material, HittableList},
+        util,
+        util::INFTY,
+    };
+
+    fn straight(shape: CurveShape) -> BezierCurve {
+        BezierCurve::new(
+            [0.0, 1.0, 2.0, 3.0].map(|x| Vec3::new(x, 0.0, 0.0)),
+            [0.2, 0.2],
+            shape,
+        )
+    }
+
+    #[test]
+    fn cylinder_normals_bulge_towards_the_ray() {
+        let curve = Curve::new(straight(CurveShape::Cylinder), test_material());
+        let ray = Ray::new(Vec3::new(1.5, 0.05, -5.0), Vec3::new(0.0, 0.0, 2.0));
+        let record = curve.hit(&ray, 0.001, INFTY).unwrap();
+        assert!((record.distance - 2.5).abs() < 1e-9);
+        assert!((record.u - 0.5).abs() < 1e-9);
+        let expected = Vec3::new(0.0, 0.5, -(0.75_f64.sqrt()));
+        assert!((record.normal - expected).length() < 1e-9);
+        assert!((record.tangent.unwrap() - Vec3::new(1.0, 0.0, 0.0)).length() < 1e-9);
+        assert!(record.front_face);
+
+        let above = Ray::new(Vec3::new(1.5, 0.15, -5.0), Vec3::new(0.0, 0.0, 1.0));
+        assert!(curve.hit(&above, 0.001, INFTY).is_none());
+        let past_the_end = Ray::new(Vec3::new(3.1, 0.0, -5.0), Vec3::new(0.0, 0.0, 1.0));
+        assert!(curve.hit(&past_the_end, 0.001, INFTY).is_none());
+    }
+
+    #[test]
+    fn ribbons_vanish_edge_on() {
+        let ribbon = CurveShape::Ribbon {
+            normals: [Vec3::new(0.0, 1.0, 0.0); 2],
+        };
+        let curve = Curve::new(straight(ribbon), test_material());
+        let from_above = Ray::new(Vec3::new(1.0, 5.0, 0.05), Vec3::new(0.0, -1.0, 0.0));
+        let record = curve.hit(&from_above, 0.001, INFTY).unwrap();
+        assert!((record.normal - Vec3::new(0.0, 1.0, 0.0)).length() < 1e-9);
+        assert!((record.v - 0.25).abs() < 1e-9 || (record.v - 0.75).abs() < 1e-9);
+        let edge_on = Ray::new(Vec3::new(1.0, 0.0, -5.0), Vec3::new(0.0, 0.0, 1.0));
+        assert!(curve.hit(&edge_on, 0.001, INFTY).is_none());
+    }
+
+    #[test]
+    fn bent_curves_are_hit_where_they_pass() {
+        let curve = Curve::new(
+            BezierCurve::new(
+                [
+                    Vec3::new(0.0, 0.0, 0.0),
+                    Vec3::new(1.0, 2.0, 0.0),
+                    Vec3::new(2.0, -2.0, 1.0),
+                    Vec3::new(3.0, 0.0, 0.0),
+                ],
+                [0.1, 0.02],
+                CurveShape::Cylinder,
+            ),
+            test_material(),
+        );
+        for _ in 0..200 {
+            let u = util::random_range(0.05, 0.95);
+            let target = curve.curve.point(u);
+            let towards_origin = Vec3::random_unit_vector();
+            let tangent = Vec3::unit_vector(&curve.curve.tangent(u));
+            if Vec3::dot(&towards_origin, &tangent).abs() > 0.9 {
+                // looking almost along the curve, where its pieces are seen flattened
+                continue;
+            }
+            let origin = target + 10.0 * towards_origin;
+            let ray = Ray::new(origin, target - origin);
+            let record = curve.hit(&ray, 0.001, INFTY).unwrap();
+            // the curve may pass in front of itself, never behind the target by more than a
+            // fraction of its width (the ray has a length of 10)
+            assert!(record.distance <= 1.0 + 0.003, "{}", record.distance);
+            assert!((record.point - curve.curve.point(record.u)).length() < 0.1);
+        }
+    }
+
+    #[test]
+    fn curves_match_a_list() {
+        let curves: Vec<BezierCurve> = (0..300)
+            .map(|_| {
+                let root = Vec3::random_range(-5.0, 5.0);
+                let control_points = [0.0, 1.0, 2.0, 3.0]
+                    .map(|step| root + Vec3::new(0.0, 0.3 * step, 0.0) + 0.1 * Vec3::random());
+                BezierCurve::new(control_points, [0.05, 0.01], CurveShape::Cylinder)
+            })
+            .collect();
+        let mut list: HittableList = Default::default();
+        for curve in &curves {
+            list.add(Curve::new(*curve, test_material()));
+        }
+        let fur = Curves::new(curves, test_material());
+        assert_eq!(fur.len(), 300);
+        for _ in 0..500 {
+            let origin = Vec3::random_range(-8.0, 8.0);
+            let ray = Ray::new(origin, Vec3::random_range(-5.0, 5.0) - origin);
+            let expected = list.hit(&ray, 0.001, INFTY).map(|record| record.distance);
+            let actual = fur.hit(&ray, 0.001, INFTY).map(|record| record.distance);
+            assert_eq!(expected, actual);
+        }
+    }
+}
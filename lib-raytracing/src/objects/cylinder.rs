@@ -0,0 +1,145 @@
+use crate::{
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable},
+    material::Material,
+    polynomial::solve_quadratic,
+    ray::Ray,
+    vec3::Vec3,
+};
+
+use super::{disk, sphere::Sphere};
+
+// cylinder standing on the disk around base and reaching height up along +y, other
+// orientations are a Transformed<Cylinder>. u wraps around the axis like on a sphere,
+// v runs from the bottom to the top on the side and from the axis to the rim on the caps
+pub struct Cylinder {
+    pub base: Vec3,
+    pub radius: f64,
+    pub height: f64,
+    pub capped: bool, // open cylinders are tubes
+    pub material: Box<dyn Material>,
+}
+
+impl Cylinder {
+    pub fn new(base: Vec3, radius: f64, height: f64, material: Box<dyn Material>) -> Cylinder {
+        Cylinder {
+            base,
+            radius,
+            height,
+            capped: true,
+            material,
+        }
+    }
+
+    pub fn without_caps(mut self) -> Cylinder {
+        self.capped = false;
+        self
+    }
+}
+
+impl Hittable for Cylinder {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
+        let local = Ray::with_time(ray.origin - self.base, ray.direction, ray.time);
+        let (origin, direction) = (local.origin, local.direction);
+        let mut closest = None; // distance, outward normal and uv of the nearest hit
+        let mut closest_so_far = t_max;
+
+        // side: (ox + t dx)² + (oz + t dz)² = r²
+        let a = direction.x * direction.x + direction.z * direction.z;
+        let b = 2.0 * (origin.x * direction.x + origin.z * direction.z);
+        let c = origin.x * origin.x + origin.z * origin.z - self.radius * self.radius;
+        if let Some((t0, t1)) = solve_quadratic(a, b, c) {
+            for t in [t0, t1] {
+                let point = local.at(t);
+                if t < t_min || t > closest_so_far || !(0.0..=self.height).contains(&point.y) {
+                    continue;
+                }
+                let outward_normal = Vec3::new(point.x, 0.0, point.z) / self.radius;
+                let (u, _) = Sphere::get_sphere_uv(&outward_normal);
+                closest = Some((t, outward_normal, u, point.y / self.height));
+                closest_so_far = t;
+                break;
+            }
+        }
+
+        if self.capped {
+            for (y, normal) in [(0.0, -1.0), (self.height, 1.0)] {
+                let center = Vec3::new(0.0, y, 0.0);
+                let normal = Vec3::new(0.0, normal, 0.0);
+                if let Some(t) =
+                    disk::intersect(&center, &normal, self.radius, &local, t_min, closest_so_far)
+                {
+                    let point = local.at(t);
+                    let (u, _) = Sphere::get_sphere_uv(&Vec3::new(point.x, 0.0, point.z));
+                    let v = (point - center).length() / self.radius;
+                    closest = Some((t, normal, u, v));
+                    closest_so_far = t;
+                }
+            }
+        }
+
+        let (t, outward_normal, u, v) = closest?;
+        let mut record = HitRecord {
+            point: ray.at(t),
+            normal: outward_normal,
+            distance: t,
+            u,
+            v,
+            front_face: false,
+            vertex_color: None,
+            tangent: None,
+            material: self.material.as_ref(),
+        };
+        record.set_face_normal(ray, outward_normal);
+        Some(record)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let extent = Vec3::new(self.radius, 0.0, self.radius);
+        let top = self.base + Vec3::new(0.0, self.height, 0.0);
+        Some(Aabb::new(self.base - extent, top + extent))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::materials::lambertian::Lambertian;
+
+    fn cylinder() -> Cylinder {
+        Cylinder::new(
+            Vec3::new(0.0, 1.0, 0.0),
+            1.0,
+            2.0,
+            Box::new(Lambertian::new(Vec3::new(0.5, 0.5, 0.5))),
+        )
+    }
+
+    #[test]
+    fn hits_side_and_caps() {
+        let cylinder = cylinder();
+        let side = Ray::new(Vec3::new(5.0, 2.5, 0.0), Vec3::new(-1.0, 0.0, 0.0));
+        let record = cylinder.hit(&side, 0.001, f64::MAX).unwrap();
+        assert!((record.distance - 4.0).abs() < 1e-12);
+        assert_eq!(record.normal, Vec3::new(1.0, 0.0, 0.0));
+        assert!((record.v - 0.75).abs() < 1e-12);
+
+        let top = Ray::new(Vec3::new(0.5, 10.0, 0.0), Vec3::new(0.0, -1.0, 0.0));
+        let record = cylinder.hit(&top, 0.001, f64::MAX).unwrap();
+        assert!((record.distance - 7.0).abs() < 1e-12);
+        assert_eq!(record.normal, Vec3::new(0.0, 1.0, 0.0));
+        assert!(record.front_face);
+    }
+
+    #[test]
+    fn open_cylinder_is_seen_from_inside() {
+        let tube = cylinder().without_caps();
+        let ray = Ray::new(Vec3::new(0.5, 10.0, 0.0), Vec3::new(0.0, -1.0, 0.0));
+        assert!(tube.hit(&ray, 0.001, f64::MAX).is_none());
+
+        let slanted = Ray::new(Vec3::new(0.0, 3.5, 0.0), Vec3::new(1.0, -1.0, 0.0));
+        let record = tube.hit(&slanted, 0.001, f64::MAX).unwrap();
+        assert!(!record.front_face);
+        assert_eq!(record.normal, Vec3::new(-1.0, 0.0, 0.0));
+    }
+}
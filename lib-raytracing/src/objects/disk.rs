@@ -0,0 +1,120 @@
+use crate::{
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable},
+    material::Material,
+    ray::Ray,
+    util::PI,
+    vec3::Vec3,
+};
+
+// flat circle around center, the outward side is the one normal points to.
+// u is the angle around the center, v the distance from it relative to the radius
+pub struct Disk {
+    pub center: Vec3,
+    pub normal: Vec3,
+    pub radius: f64,
+    pub material: Box<dyn Material>,
+}
+
+impl Disk {
+    pub fn new(center: Vec3, normal: Vec3, radius: f64, material: Box<dyn Material>) -> Disk {
+        Disk {
+            center,
+            normal: Vec3::unit_vector(&normal),
+            radius,
+            material,
+        }
+    }
+}
+
+// distance along the ray to a hit on a disk with a unit normal, shared with the caps of
+// cylinders and cones
+pub fn intersect(
+    center: &Vec3,
+    normal: &Vec3,
+    radius: f64,
+    ray: &Ray,
+    t_min: f64,
+    t_max: f64,
+) -> Option<f64> {
+    let denominator = Vec3::dot(normal, &ray.direction);
+    if denominator.abs() < 1e-12 {
+        return None;
+    }
+    let t = Vec3::dot(normal, &(*center - ray.origin)) / denominator;
+    if t < t_min || t > t_max || (ray.at(t) - *center).length_squared() > radius * radius {
+        return None;
+    }
+    Some(t)
+}
+
+impl Hittable for Disk {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
+        let t = intersect(&self.center, &self.normal, self.radius, ray, t_min, t_max)?;
+        let point = ray.at(t);
+        let (tangent_u, tangent_v) = Vec3::orthonormal_basis(&self.normal);
+        let offset = point - self.center;
+        let angle = f64::atan2(
+            Vec3::dot(&offset, &tangent_v),
+            Vec3::dot(&offset, &tangent_u),
+        );
+        let mut record = HitRecord {
+            point,
+            normal: self.normal,
+            distance: t,
+            u: (angle + PI) / (2.0 * PI),
+            v: offset.length() / self.radius,
+            front_face: false,
+            vertex_color: None,
+            tangent: None,
+            material: self.material.as_ref(),
+        };
+        record.set_face_normal(ray, self.normal);
+        Some(record)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        // the extent along each axis is the radius times the sine of the angle to the normal
+        let extent = Vec3::new(
+            (1.0 - self.normal.x * self.normal.x).max(0.0).sqrt(),
+            (1.0 - self.normal.y * self.normal.y).max(0.0).sqrt(),
+            (1.0 - self.normal.z * self.normal.z).max(0.0).sqrt(),
+        ) * self.radius;
+        Some(Aabb::new(self.center - extent, self.center + extent).padded(0.0001))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::materials::lambertian::Lambertian;
+
+    fn disk() -> Disk {
+        Disk::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 0.0, 1.0),
+            2.0,
+            Box::new(Lambertian::new(Vec3::new(0.5, 0.5, 0.5))),
+        )
+    }
+
+    #[test]
+    fn hits_inside_the_radius() {
+        let disk = disk();
+        let ray = Ray::new(Vec3::new(1.0, 0.0, 3.0), Vec3::new(0.0, 0.0, -1.0));
+        let record = disk.hit(&ray, 0.001, f64::MAX).unwrap();
+        assert!((record.distance - 3.0).abs() < 1e-12);
+        assert!((record.v - 0.5).abs() < 1e-12);
+        assert!(record.front_face);
+
+        let outside = Ray::new(Vec3::new(1.5, 1.5, 3.0), Vec3::new(0.0, 0.0, -1.0));
+        assert!(disk.hit(&outside, 0.001, f64::MAX).is_none());
+    }
+
+    #[test]
+    fn bounding_box_is_flat_along_the_normal() {
+        let bounding_box = disk().bounding_box().unwrap();
+        assert!((bounding_box.maximum.x - 2.0).abs() < 0.001);
+        assert!(bounding_box.maximum.z < 0.001);
+    }
+}
@@ -0,0 +1,358 @@
+use std::path::Path;
+
+use image::{
+    error::{ParameterError, ParameterErrorKind},
+    ImageError, ImageResult,
+};
+
+use crate::{
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable},
+    material::Material,
+    ray::Ray,
+    util::INFTY,
+    vec3::Vec3,
+};
+
+use super::triangle::{intersect, set_shading_normal};
+
+// terrain from a grid of height samples spread evenly over a rectangle in the xz plane
+pub struct Heightfield {
+    width: usize,       // samples along x
+    depth: usize,       // samples along z
+    heights: Vec<f64>,  // row after row along x, the first row at corner.z
+    normals: Vec<Vec3>, // shading normal at every sample
+    corner: Vec3,       // where the first sample is at height 0
+    size: Vec3,         // extent along x and z, y is the height of a sample of 1
+    bounding_box: Aabb,
+    pub material: Box<dyn Material>,
+}
+
+impl Heightfield {
+    pub fn new(
+        width: usize,
+        depth: usize,
+        heights: Vec<f64>,
+        corner: Vec3,
+        size: Vec3,
+        material: Box<dyn Material>,
+    ) -> Heightfield {
+        assert!(
+            width >= 2 && depth >= 2,
+            "a heightfield needs at least 2 samples along x and z"
+        );
+        assert_eq!(
+            heights.len(),
+            width * depth,
+            "a heightfield needs width * depth heights"
+        );
+        let (lowest, highest) = heights
+            .iter()
+            .fold((INFTY, -INFTY), |(lowest, highest), height| {
+                (lowest.min(*height), highest.max(*height))
+            });
+        let bounding_box = Aabb::new(
+            corner + Vec3::new(0.0, lowest * size.y, 0.0),
+            corner + Vec3::new(size.x, highest * size.y, size.z),
+        )
+        .padded(0.0001);
+
+        let mut heightfield = Heightfield {
+            width,
+            depth,
+            heights,
+            normals: vec![],
+            corner,
+            size,
+            bounding_box,
+            material,
+        };
+        heightfield.normals = (0..depth)
+            .flat_map(|j| (0..width).map(move |i| (i, j)))
+            .map(|(i, j)| heightfield.slope_normal(i, j))
+            .collect();
+        heightfield
+    }
+
+    // loads the heights from the brightness of an image, black is 0 and white 1. The top
+    // row of the image lies at corner.z. Images need at least 2 pixels in both directions
+    pub fn load<P: AsRef<Path>>(
+        path: P,
+        corner: Vec3,
+        size: Vec3,
+        material: Box<dyn Material>,
+    ) -> ImageResult<Heightfield> {
+        let image = image::open(path)?.to_luma32f();
+        let (width, depth) = image.dimensions();
+        if width < 2 || depth < 2 {
+            return Err(ImageError::Parameter(ParameterError::from_kind(
+                ParameterErrorKind::Generic(format!(
+                    "a heightfield needs at least 2x2 pixels, the image has {}x{}",
+                    width, depth
+                )),
+            )));
+        }
+        let heights = image.pixels().map(|pixel| pixel[0] as f64).collect();
+        Ok(Heightfield::new(
+            width as usize,
+            depth as usize,
+            heights,
+            corner,
+            size,
+            material,
+        ))
+    }
+
+    fn height(&self, i: usize, j: usize) -> f64 {
+        self.heights[j * self.width + i]
+    }
+
+    fn position(&self, i: usize, j: usize) -> Vec3 {
+        self.corner
+            + Vec3::new(
+                i as f64 / (self.width - 1) as f64 * self.size.x,
+                self.height(i, j) * self.size.y,
+                j as f64 / (self.depth - 1) as f64 * self.size.z,
+            )
+    }
+
+    // central differences inside the grid, one sided at its border
+    fn slope_normal(&self, i: usize, j: usize) -> Vec3 {
+        let (left, right) = (i.saturating_sub(1), (i + 1).min(self.width - 1));
+        let (back, front) = (j.saturating_sub(1), (j + 1).min(self.depth - 1));
+        let along_x = self.position(right, j) - self.position(left, j);
+        let along_z = self.position(i, front) - self.position(i, back);
+        Vec3::unit_vector(&Vec3::cross(&along_z, &along_x))
+    }
+
+    // the two triangles of the cell whose first corner is sample (i, j)
+    fn hit_cell(
+        &self,
+        ray: &Ray,
+        i: usize,
+        j: usize,
+        t_min: f64,
+        t_max: f64,
+    ) -> Option<HitRecord<'_>> {
+        // the two triangles of the cell are only built here, counter clockwise seen from
+        // above so the geometric normals point up
+        let corners = [(i, j), (i + 1, j + 1), (i + 1, j), (i, j + 1)];
+        let triangles = [[0, 1, 2], [0, 3, 1]];
+        let positions = corners.map(|(i, j)| self.position(i, j));
+
+        let mut closest: Option<(f64, [usize; 3], _)> = None;
+        for triangle in triangles {
+            let [a, b, c] = triangle.map(|corner| positions[corner]);
+            let t_max = closest.map_or(t_max, |(t, _, _)| t);
+            if let Some((t, barycentric)) = intersect(&a, &b, &c, ray, t_min, t_max) {
+                closest = Some((t, triangle, barycentric));
+            }
+        }
+        let (t, triangle, barycentric) = closest?;
+
+        let [a, b, c] = triangle.map(|corner| positions[corner]);
+        let [normal_a, normal_b, normal_c] = triangle.map(|corner| {
+            let (i, j) = corners[corner];
+            self.normals[j * self.width + i]
+        });
+        let point = ray.at(t);
+        // u along x and v against z, so a texture of the same image lines up with the heights
+        let mut record = HitRecord {
+            point,
+            normal: Vec3::new(0.0, 1.0, 0.0),
+            distance: t,
+            u: ((point.x - self.corner.x) / self.size.x).clamp(0.0, 1.0),
+            v: 1.0 - ((point.z - self.corner.z) / self.size.z).clamp(0.0, 1.0),
+            front_face: false,
+            vertex_color: None,
+            tangent: None,
+            material: self.material.as_ref(),
+        };
+        let geometric_normal = Vec3::unit_vector(&Vec3::cross(&(b - a), &(c - a)));
+        let shading_normal = barycentric.interpolate(normal_a, normal_b, normal_c);
+        set_shading_normal(&mut record, ray, geometric_normal, Some(shading_normal));
+        Some(record)
+    }
+}
+
+// where the ray crosses into the next cell along one axis of the grid: the step between
+// cell indices, the distance of the first crossing and the distance between crossings
+fn crossings(origin: f64, direction: f64, cell: usize) -> (isize, f64, f64) {
+    if direction > 0.0 {
+        (1, (cell as f64 + 1.0 - origin) / direction, 1.0 / direction)
+    } else if direction < 0.0 {
+        (-1, (cell as f64 - origin) / direction, -1.0 / direction)
+    } else {
+        (0, INFTY, INFTY)
+    }
+}
+
+impl Hittable for Heightfield {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
+        let (start, end) = self.bounding_box.hit_interval(ray, t_min, t_max)?;
+
+        // the ray in grid units, where cell (i, j) covers [i, i + 1] x [j, j + 1]
+        let cells = (self.width - 1, self.depth - 1);
+        let scale_x = cells.0 as f64 / self.size.x;
+        let scale_z = cells.1 as f64 / self.size.z;
+        let (origin_x, direction_x) = (
+            (ray.origin.x - self.corner.x) * scale_x,
+            ray.direction.x * scale_x,
+        );
+        let (origin_z, direction_z) = (
+            (ray.origin.z - self.corner.z) * scale_z,
+            ray.direction.z * scale_z,
+        );
+        let cell = |entry: f64, count: usize| (entry.floor().max(0.0) as usize).min(count - 1);
+        let mut i = cell(origin_x + start * direction_x, cells.0);
+        let mut j = cell(origin_z + start * direction_z, cells.1);
+        let (step_i, mut next_x, delta_x) = crossings(origin_x, direction_x, i);
+        let (step_j, mut next_z, delta_z) = crossings(origin_z, direction_z, j);
+
+        // 2D DDA: visit the cells under the ray front to back, the first hit is the closest
+        loop {
+            if let Some(record) = self.hit_cell(ray, i, j, t_min, t_max) {
+                return Some(record);
+            }
+            if next_x < next_z {
+                if next_x > end {
+                    return None;
+                }
+                i = i.checked_add_signed(step_i).filter(|i| *i < cells.0)?;
+                next_x += delta_x;
+            } else {
+                if next_z > end {
+                    return None;
+                }
+                j = j.checked_add_signed(step_j).filter(|j| *j < cells.1)?;
+                next_z += delta_z;
+            }
+        }
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(self.bounding_box)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        hittable::{test_material, HittableList},
+        objects::triangle::Triangle,
+        util,
+    };
+
+    fn bumps() -> Heightfield {
+        let (width, depth) = (7, 5);
+        let heights = (0..width * depth)
+            .map(|index| {
+                let (i, j) = ((index % width) as f64, (index / width) as f64);
+                0.5 + 0.5 * (i * 1.3).sin() * (j * 0.9).cos()
+            })
+            .collect();
+        Heightfield::new(
+            width,
+            depth,
+            heights,
+            Vec3::new(-3.0, -1.0, -2.0),
+            Vec3::new(6.0, 2.0, 4.0),
+            test_material(),
+        )
+    }
+
+    #[test]
+    fn matches_the_triangles_of_every_cell() {
+        let heightfield = bumps();
+        let mut triangles: HittableList = Default::default();
+        for j in 0..heightfield.depth - 1 {
+            for i in 0..heightfield.width - 1 {
+                let [a, b, c, d] = [(i, j), (i + 1, j + 1), (i + 1, j), (i, j + 1)]
+                    .map(|(i, j)| heightfield.position(i, j));
+                triangles.add(Triangle::new(a, b, c, test_material()));
+                triangles.add(Triangle::new(a, d, b, test_material()));
+            }
+        }
+
+        for _ in 0..2000 {
+            let origin = Vec3::random_range(-5.0, 5.0);
+            let target = Vec3::new(
+                util::random_range(-3.0, 3.0),
+                util::random_range(-1.0, 1.0),
+                util::random_range(-2.0, 2.0),
+            );
+            let ray = Ray::new(origin, target - origin);
+            let expected = triangles.hit(&ray, 0.001, INFTY);
+            let actual = heightfield.hit(&ray, 0.001, INFTY);
+            match (expected, actual) {
+                (Some(expected), Some(actual)) => {
+                    assert!((expected.distance - actual.distance).abs() < 1e-9);
+                    assert_eq!(expected.front_face, actual.front_face);
+                }
+                (None, None) => {}
+                (expected, actual) => panic!(
+                    "{:?} != {:?}",
+                    expected.map(|record| record.distance),
+                    actual.map(|record| record.distance)
+                ),
+            }
+        }
+    }
+
+    #[test]
+    fn straight_down_on_a_slope() {
+        // a ramp rising along x: the normal leans back against x, uvs follow x and z
+        let heights = vec![0.0, 0.5, 1.0, 0.0, 0.5, 1.0];
+        let ramp = Heightfield::new(
+            3,
+            2,
+            heights,
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(2.0, 2.0, 1.0),
+            test_material(),
+        );
+        let ray = Ray::new(Vec3::new(1.5, 5.0, 0.25), Vec3::new(0.0, -1.0, 0.0));
+        let record = ramp.hit(&ray, 0.001, INFTY).unwrap();
+        assert!((record.point.y - 1.5).abs() < 1e-9);
+        let expected_normal = Vec3::unit_vector(&Vec3::new(-1.0, 1.0, 0.0));
+        assert!((record.normal - expected_normal).length() < 1e-9);
+        assert!(record.front_face);
+        assert!((record.u - 0.75).abs() < 1e-9);
+        assert!((record.v - 0.75).abs() < 1e-9);
+
+        let beside = Ray::new(Vec3::new(2.5, 5.0, 0.25), Vec3::new(0.0, -1.0, 0.0));
+        assert!(ramp.hit(&beside, 0.001, INFTY).is_none());
+    }
+
+    #[test]
+    fn load_heightmap_image() {
+        let path = std::env::temp_dir().join("lib-raytracing-heightfield-test.pgm");
+        std::fs::write(&path, b"P2\n2 2\n255\n0 255\n0 255\n").unwrap();
+        let heightfield = Heightfield::load(
+            &path,
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(1.0, 3.0, 1.0),
+            test_material(),
+        )
+        .unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let bounding_box = heightfield.bounding_box().unwrap();
+        assert!((bounding_box.maximum.y - 3.0).abs() < 0.001);
+        assert!((heightfield.position(1, 1).y - 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn load_rejects_single_pixel_rows() {
+        let path = std::env::temp_dir().join("lib-raytracing-heightfield-row-test.pgm");
+        std::fs::write(&path, b"P2\n3 1\n255\n0 128 255\n").unwrap();
+        let result = Heightfield::load(
+            &path,
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(1.0, 1.0, 1.0),
+            test_material(),
+        );
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(result, Err(ImageError::Parameter(_))));
+    }
+}
@@ -0,0 +1,181 @@
+use crate::{
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable},
+    material::Material,
+    materials::henyey_greenstein::HenyeyGreenstein,
+    ray::Ray,
+    util,
+    vec3::Vec3,
+    voxel_grid::VoxelGrid,
+};
+
+// smoke or clouds whose density varies in space, given by a voxel grid stretched over a box.
+// Collisions are found by delta tracking: free flights are sampled against the largest
+// density in the grid and accepted with the ratio of the actual density to it, the rest
+// are null collisions the ray passes through. Shadow rays use ratio tracking instead
+pub struct HeterogeneousMedium {
+    pub grid: VoxelGrid,
+    pub bounds: Aabb,
+    pub density_scale: f64, // density at a grid value of 1, per unit length
+    pub phase_function: Box<dyn Material>,
+}
+
+impl HeterogeneousMedium {
+    pub fn new(
+        grid: VoxelGrid,
+        bounds: Aabb,
+        density_scale: f64,
+        color: Vec3,
+        anisotropy: f64,
+    ) -> HeterogeneousMedium {
+        HeterogeneousMedium {
+            grid,
+            bounds,
+            density_scale,
+            phase_function: Box::new(HenyeyGreenstein::new(color, anisotropy)),
+        }
+    }
+
+    pub fn density(&self, point: &Vec3) -> f64 {
+        let extent = self.bounds.extent();
+        let offset = *point - self.bounds.minimum;
+        let grid_point = Vec3::new(
+            offset.x / extent.x,
+            offset.y / extent.y,
+            offset.z / extent.z,
+        );
+        self.density_scale * self.grid.value(&grid_point)
+    }
+
+    // calls collision with every tentative collision inside the box between t_min and t_max
+    // and the ratio of the density there to the majorant until it returns false
+    fn track(
+        &self,
+        ray: &Ray,
+        t_min: f64,
+        t_max: f64,
+        mut collision: impl FnMut(f64, f64) -> bool,
+    ) {
+        let majorant = self.density_scale * self.grid.maximum();
+        let Some((start, end)) = self.bounds.hit_interval(ray, t_min, t_max) else {
+            return;
+        };
+        if majorant <= 0.0 {
+            return;
+        }
+        let length = ray.direction.length();
+        let mut t = start;
+        loop {
+            t -= (1.0 - util::random()).ln() / (majorant * length);
+            if t >= end || !collision(t, self.density(&ray.at(t)) / majorant) {
+                return;
+            }
+        }
+    }
+}
+
+impl Hittable for HeterogeneousMedium {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
+        let mut hit = None;
+        self.track(ray, t_min, t_max, |t, ratio| {
+            if util::random() < ratio {
+                hit = Some(t);
+            }
+            hit.is_none()
+        });
+        let t = hit?;
+
+        Some(HitRecord {
+            point: ray.at(t),
+            normal: Vec3::new(1.0, 0.0, 0.0), // arbitrary, phase functions do not use it
+            distance: t,
+            u: 0.0,
+            v: 0.0,
+            front_face: true,
+            vertex_color: None,
+            tangent: None,
+            material: self.phase_function.as_ref(),
+        })
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(self.bounds)
+    }
+
+    // ratio tracking: the product of the null collision probabilities along the ray
+    fn transmittance(&self, ray: &Ray, t_min: f64, t_max: f64) -> f64 {
+        let mut transmittance = 1.0;
+        self.track(ray, t_min, t_max, |_, ratio| {
+            transmittance *= 1.0 - ratio;
+            transmittance > 0.0
+        });
+        transmittance
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::INFTY;
+
+    // a unit cube of medium whose density rises linearly from 0 to 2 along x
+    fn ramp() -> HeterogeneousMedium {
+        let values = (0..64).map(|i| (i % 4) as f64 / 3.0).collect();
+        HeterogeneousMedium::new(
+            VoxelGrid::new([4, 4, 4], values),
+            Aabb::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 1.0, 1.0)),
+            2.0,
+            Vec3::new(1.0, 1.0, 1.0),
+            0.0,
+        )
+    }
+
+    #[test]
+    fn transmittance_matches_the_optical_depth() {
+        // along y at the value of the second voxel column the density is constant 2/3
+        let medium = ramp();
+        let ray = Ray::new(Vec3::new(0.375, -1.0, 0.5), Vec3::new(0.0, 2.0, 0.0));
+        let expected = (-2.0_f64 / 3.0).exp();
+        let samples = 20000;
+        let ratio_tracked: f64 = (0..samples)
+            .map(|_| medium.transmittance(&ray, 0.001, INFTY))
+            .sum::<f64>()
+            / samples as f64;
+        assert!((ratio_tracked - expected).abs() < 0.01);
+        let delta_tracked = (0..samples)
+            .filter(|_| medium.hit(&ray, 0.001, INFTY).is_none())
+            .count() as f64
+            / samples as f64;
+        assert!((delta_tracked - expected).abs() < 0.02);
+    }
+
+    #[test]
+    fn collisions_follow_the_density() {
+        let medium = ramp();
+        let ray = Ray::new(Vec3::new(-1.0, 0.5, 0.5), Vec3::new(1.0, 0.0, 0.0));
+        let mut dense_half = 0;
+        let mut collisions = 0;
+        for _ in 0..10000 {
+            if let Some(record) = medium.hit(&ray, 0.001, INFTY) {
+                assert!(medium
+                    .bounds
+                    .hit(&ray, record.distance - 1e-9, record.distance + 1e-9));
+                collisions += 1;
+                if record.point.x > 0.5 {
+                    dense_half += 1;
+                }
+            }
+        }
+        assert!(dense_half > collisions / 2);
+
+        let empty = HeterogeneousMedium::new(
+            VoxelGrid::new([1, 1, 1], vec![0.0]),
+            medium.bounds,
+            1.0,
+            Vec3::new(1.0, 1.0, 1.0),
+            0.0,
+        );
+        assert!(empty.hit(&ray, 0.001, INFTY).is_none());
+        assert_eq!(empty.transmittance(&ray, 0.001, INFTY), 1.0);
+    }
+}
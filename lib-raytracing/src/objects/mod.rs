@@ -1,2 +1,19 @@
+pub mod animated;
+pub mod cone;
+pub mod constant_medium;
+pub mod csg;
+pub mod cuboid;
+pub mod curve;
+pub mod cylinder;
+pub mod disk;
+pub mod heightfield;
+pub mod heterogeneous_medium;
+pub mod moving_sphere;
+pub mod plane;
+pub mod quad;
+pub mod ray_marched;
 pub mod sphere;
+pub mod torus;
+pub mod transformed;
 pub mod triangle;
+pub mod triangle_mesh;
@@ -0,0 +1,137 @@
+use crate::{
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable},
+    material::Material,
+    ray::Ray,
+    vec3::Vec3,
+};
+
+use super::sphere::Sphere;
+
+// sphere moving in a straight line from start_center at start_time to end_center at
+// end_time and resting at either end outside that interval. Rays cast over a shutter
+// interval see it blurred along its path
+pub struct MovingSphere {
+    pub sphere: Sphere, // where the sphere is at start_time
+    pub end_center: Vec3,
+    pub start_time: f64,
+    pub end_time: f64,
+}
+
+impl MovingSphere {
+    pub fn new(
+        start_center: Vec3,
+        end_center: Vec3,
+        start_time: f64,
+        end_time: f64,
+        radius: f64,
+        material: Box<dyn Material>,
+    ) -> MovingSphere {
+        MovingSphere {
+            sphere: Sphere {
+                center: start_center,
+                radius,
+                material,
+            },
+            end_center,
+            start_time,
+            end_time,
+        }
+    }
+
+    pub fn center(&self, time: f64) -> Vec3 {
+        self.sphere.center + self.offset(time)
+    }
+
+    // how far the sphere has moved from its start by the given time
+    fn offset(&self, time: f64) -> Vec3 {
+        let duration = self.end_time - self.start_time;
+        let progress = if duration > 0.0 {
+            ((time - self.start_time) / duration).clamp(0.0, 1.0)
+        } else if time < self.start_time {
+            0.0
+        } else {
+            1.0
+        };
+        (self.end_center - self.sphere.center) * progress
+    }
+
+    // the ray relative to the sphere at its start, so the resting sphere can be hit
+    fn to_start(&self, ray: &Ray) -> (Ray, Vec3) {
+        let offset = self.offset(ray.time);
+        (
+            Ray::with_time(ray.origin - offset, ray.direction, ray.time),
+            offset,
+        )
+    }
+}
+
+impl Hittable for MovingSphere {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
+        let (ray, offset) = self.to_start(ray);
+        let mut record = self.sphere.hit(&ray, t_min, t_max)?;
+        record.point += offset;
+        Some(record)
+    }
+
+    fn hit_all(&self, ray: &Ray, t_min: f64, t_max: f64) -> Vec<HitRecord<'_>> {
+        let (ray, offset) = self.to_start(ray);
+        let mut records = self.sphere.hit_all(&ray, t_min, t_max);
+        for record in records.iter_mut() {
+            record.point += offset;
+        }
+        records
+    }
+
+    // covers the whole path
+    fn bounding_box(&self) -> Option<Aabb> {
+        let start = self.sphere.bounding_box()?;
+        let offset = self.end_center - self.sphere.center;
+        let end = Aabb::new(start.minimum + offset, start.maximum + offset);
+        Some(Aabb::surrounding_box(&start, &end))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{materials::lambertian::Lambertian, util::INFTY};
+
+    fn moving_sphere() -> MovingSphere {
+        MovingSphere::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(4.0, 0.0, 0.0),
+            0.0,
+            1.0,
+            1.0,
+            Box::new(Lambertian::new(Vec3::new(0.5, 0.5, 0.5))),
+        )
+    }
+
+    #[test]
+    fn position_depends_on_the_ray_time() {
+        let sphere = moving_sphere();
+        let direction = Vec3::new(0.0, 0.0, 1.0);
+        let early = Ray::with_time(Vec3::new(0.0, 0.0, -5.0), direction, 0.0);
+        let halfway = Ray::with_time(Vec3::new(2.0, 0.0, -5.0), direction, 0.5);
+        let late = Ray::with_time(Vec3::new(0.0, 0.0, -5.0), direction, 1.0);
+
+        let record = sphere.hit(&early, 0.001, INFTY).unwrap();
+        assert!((record.point - Vec3::new(0.0, 0.0, -1.0)).length() < 1e-9);
+        let record = sphere.hit(&halfway, 0.001, INFTY).unwrap();
+        assert!((record.point - Vec3::new(2.0, 0.0, -1.0)).length() < 1e-9);
+        assert!((record.normal - Vec3::new(0.0, 0.0, -1.0)).length() < 1e-9);
+        assert!(sphere.hit(&late, 0.001, INFTY).is_none());
+
+        // resting after the end of the motion
+        let after = Ray::with_time(Vec3::new(4.0, 0.0, -5.0), direction, 7.0);
+        assert_eq!(sphere.hit_all(&after, 0.001, INFTY).len(), 2);
+    }
+
+    #[test]
+    fn bounding_box_covers_the_path() {
+        let bounding_box = moving_sphere().bounding_box().unwrap();
+        assert_eq!(bounding_box.minimum, Vec3::new(-1.0, -1.0, -1.0));
+        assert_eq!(bounding_box.maximum, Vec3::new(5.0, 1.0, 1.0));
+    }
+}
@@ -0,0 +1,95 @@
+use crate::{
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable},
+    material::Material,
+    ray::Ray,
+    vec3::Vec3,
+};
+
+// infinite plane through point, the outward side is the one normal points to.
+// Surface coordinates repeat every unit so image textures tile across the plane
+pub struct Plane {
+    pub point: Vec3,
+    pub normal: Vec3,
+    pub material: Box<dyn Material>,
+}
+
+impl Plane {
+    pub fn new(point: Vec3, normal: Vec3, material: Box<dyn Material>) -> Plane {
+        Plane {
+            point,
+            normal: Vec3::unit_vector(&normal),
+            material,
+        }
+    }
+}
+
+impl Hittable for Plane {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
+        let denominator = Vec3::dot(&self.normal, &ray.direction);
+        if denominator.abs() < 1e-12 {
+            return None;
+        }
+        let t = Vec3::dot(&self.normal, &(self.point - ray.origin)) / denominator;
+        if t < t_min || t > t_max {
+            return None;
+        }
+
+        let point = ray.at(t);
+        let (tangent_u, tangent_v) = Vec3::orthonormal_basis(&self.normal);
+        let offset = point - self.point;
+        let mut record = HitRecord {
+            point,
+            normal: self.normal,
+            distance: t,
+            u: Vec3::dot(&offset, &tangent_u).rem_euclid(1.0),
+            v: Vec3::dot(&offset, &tangent_v).rem_euclid(1.0),
+            front_face: false,
+            vertex_color: None,
+            tangent: None,
+            material: self.material.as_ref(),
+        };
+        record.set_face_normal(ray, self.normal);
+        Some(record)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::materials::lambertian::Lambertian;
+
+    fn ground() -> Plane {
+        Plane::new(
+            Vec3::new(0.0, -1.0, 0.0),
+            Vec3::new(0.0, 2.0, 0.0),
+            Box::new(Lambertian::new(Vec3::new(0.5, 0.5, 0.5))),
+        )
+    }
+
+    #[test]
+    fn hits_from_both_sides() {
+        let plane = ground();
+        let from_above = Ray::new(Vec3::new(3.25, 1.0, -7.5), Vec3::new(0.0, -1.0, 0.0));
+        let record = plane.hit(&from_above, 0.001, f64::MAX).unwrap();
+        assert!((record.distance - 2.0).abs() < 1e-12);
+        assert!(record.front_face);
+        assert!((0.0..1.0).contains(&record.u) && (0.0..1.0).contains(&record.v));
+
+        let from_below = Ray::new(Vec3::new(0.0, -3.0, 0.0), Vec3::new(0.0, 1.0, 0.0));
+        let record = plane.hit(&from_below, 0.001, f64::MAX).unwrap();
+        assert!(!record.front_face);
+        assert_eq!(record.normal, Vec3::new(0.0, -1.0, 0.0));
+    }
+
+    #[test]
+    fn parallel_rays_miss() {
+        let ray = Ray::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        assert!(ground().hit(&ray, 0.001, f64::MAX).is_none());
+        assert!(ground().bounding_box().is_none());
+    }
+}
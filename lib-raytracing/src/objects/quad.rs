@@ -0,0 +1,217 @@
+use crate::{
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable},
+    light::{Light, LightSample},
+    material::Material,
+    ray::Ray,
+    util::random,
+    vec3::Vec3,
+};
+
+// parallelogram spanned by the edges u and v starting at corner, the outward side is the one
+// cross(u, v) points to. The surface coordinates run from 0 to 1 along u and v
+pub struct Quad {
+    pub corner: Vec3,
+    pub u: Vec3,
+    pub v: Vec3,
+    pub material: Box<dyn Material>,
+}
+
+impl Quad {
+    pub fn new(corner: Vec3, u: Vec3, v: Vec3, material: Box<dyn Material>) -> Quad {
+        Quad {
+            corner,
+            u,
+            v,
+            material,
+        }
+    }
+}
+
+// distance along the ray and the position (alpha, beta) of the hit in the parallelogram,
+// shared with objects made of several quads
+pub fn intersect(
+    corner: &Vec3,
+    u: &Vec3,
+    v: &Vec3,
+    ray: &Ray,
+    t_min: f64,
+    t_max: f64,
+) -> Option<(f64, f64, f64)> {
+    let normal = Vec3::cross(u, v);
+    let denominator = Vec3::dot(&normal, &ray.direction);
+    if denominator.abs() < 1e-12 {
+        return None; // parallel to the plane
+    }
+    let t = Vec3::dot(&normal, &(*corner - ray.origin)) / denominator;
+    if t < t_min || t > t_max {
+        return None;
+    }
+
+    // express the hit in the basis (u, v): w is the dual vector of the basis
+    let planar = ray.at(t) - *corner;
+    let w = normal / Vec3::dot(&normal, &normal);
+    let alpha = Vec3::dot(&w, &Vec3::cross(&planar, v));
+    let beta = Vec3::dot(&w, &Vec3::cross(u, &planar));
+    if !(0.0..=1.0).contains(&alpha) || !(0.0..=1.0).contains(&beta) {
+        return None;
+    }
+    Some((t, alpha, beta))
+}
+
+// hit record for a quad hit found by intersect
+pub fn hit_record<'a>(
+    u: &Vec3,
+    v: &Vec3,
+    ray: &Ray,
+    (t, alpha, beta): (f64, f64, f64),
+    material: &'a dyn Material,
+) -> HitRecord<'a> {
+    let outward_normal = Vec3::unit_vector(&Vec3::cross(u, v));
+    let mut record = HitRecord {
+        point: ray.at(t),
+        normal: outward_normal,
+        distance: t,
+        u: alpha,
+        v: beta,
+        front_face: false,
+        vertex_color: None,
+        tangent: None,
+        material,
+    };
+    record.set_face_normal(ray, outward_normal);
+    record
+}
+
+impl Hittable for Quad {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
+        let hit = intersect(&self.corner, &self.u, &self.v, ray, t_min, t_max)?;
+        Some(hit_record(
+            &self.u,
+            &self.v,
+            ray,
+            hit,
+            self.material.as_ref(),
+        ))
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let corners = [
+            self.corner,
+            self.corner + self.u,
+            self.corner + self.v,
+            self.corner + self.u + self.v,
+        ];
+        Some(Aabb::from_points(&corners).padded(0.0001))
+    }
+}
+
+// area light sampled uniformly over its surface, both sides emit
+impl Light for Quad {
+    fn sample(&self, point: &Vec3, time: f64) -> Option<LightSample> {
+        let target = self.corner + self.u * random() + self.v * random();
+        let to_target = target - *point;
+        let distance = to_target.length();
+        let direction = to_target / distance;
+        let ray = Ray::with_time(*point, direction, time);
+        let hit = intersect(&self.corner, &self.u, &self.v, &ray, 0.0, 2.0 * distance)?;
+        let record = hit_record(&self.u, &self.v, &ray, hit, self.material.as_ref());
+        let pdf = self.pdf(&ray, record.distance);
+        if pdf <= 0.0 {
+            return None; // seen edge on
+        }
+        Some(LightSample {
+            direction,
+            distance: record.distance,
+            radiance: record.material.emitted(&ray, &record),
+            pdf,
+            is_delta: false,
+        })
+    }
+
+    // the density per area 1 / area converted to solid angle
+    fn pdf(&self, ray: &Ray, distance: f64) -> f64 {
+        let tolerance = 1e-6 * distance;
+        let range = (distance - tolerance, distance + tolerance);
+        if intersect(&self.corner, &self.u, &self.v, ray, range.0, range.1).is_none() {
+            return 0.0;
+        }
+        let normal = Vec3::cross(&self.u, &self.v);
+        let area = normal.length();
+        let cos_theta =
+            (Vec3::dot(&normal, &ray.direction) / (area * ray.direction.length())).abs();
+        if cos_theta < 1e-9 {
+            return 0.0;
+        }
+        let distance_squared = (ray.direction * distance).length_squared();
+        distance_squared / (cos_theta * area)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::materials::lambertian::Lambertian;
+
+    fn quad() -> Quad {
+        Quad::new(
+            Vec3::new(-1.0, -1.0, 0.0),
+            Vec3::new(2.0, 0.0, 0.0),
+            Vec3::new(0.0, 4.0, 0.0),
+            Box::new(Lambertian::new(Vec3::new(0.5, 0.5, 0.5))),
+        )
+    }
+
+    #[test]
+    fn uvs_run_along_the_edges() {
+        let ray = Ray::new(Vec3::new(0.5, 2.0, 2.0), Vec3::new(0.0, 0.0, -1.0));
+        let quad = quad();
+        let record = quad.hit(&ray, 0.001, f64::MAX).unwrap();
+        assert!((record.distance - 2.0).abs() < 1e-12);
+        assert!((record.u - 0.75).abs() < 1e-12 && (record.v - 0.75).abs() < 1e-12);
+        assert!(record.front_face);
+        assert_eq!(record.normal, Vec3::new(0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn misses_outside_and_parallel() {
+        let quad = quad();
+        let outside = Ray::new(Vec3::new(1.5, 0.0, 2.0), Vec3::new(0.0, 0.0, -1.0));
+        assert!(quad.hit(&outside, 0.001, f64::MAX).is_none());
+        let parallel = Ray::new(Vec3::new(0.0, 0.0, 1.0), Vec3::new(1.0, 0.0, 0.0));
+        assert!(quad.hit(&parallel, 0.001, f64::MAX).is_none());
+    }
+
+    #[test]
+    fn bounding_box_covers_corners() {
+        let bounding_box = quad().bounding_box().unwrap();
+        assert!(bounding_box.minimum.x <= -1.0 && bounding_box.maximum.y >= 3.0);
+        assert!(bounding_box.maximum.z > bounding_box.minimum.z);
+    }
+
+    #[test]
+    fn light_samples_cover_the_solid_angle() {
+        // a 2 x 2 square seen from 1 above its center fills 4 asin(1 / 2) steradians
+        let quad = Quad::new(
+            Vec3::new(-1.0, -1.0, 0.0),
+            Vec3::new(2.0, 0.0, 0.0),
+            Vec3::new(0.0, 2.0, 0.0),
+            Box::new(Lambertian::new(Vec3::new(0.5, 0.5, 0.5))),
+        );
+        let point = Vec3::new(0.0, 0.0, 1.0);
+        let samples = 100000;
+        let mut solid_angle = 0.0;
+        for _ in 0..samples {
+            let sample = quad.sample(&point, 0.0).unwrap();
+            let ray = Ray::new(point, sample.direction);
+            assert!((quad.pdf(&ray, sample.distance) - sample.pdf).abs() < 1e-9);
+            solid_angle += 1.0 / sample.pdf / samples as f64;
+        }
+        let expected = 4.0 * 0.5_f64.asin();
+        assert!(
+            (solid_angle - expected).abs() < 0.01 * expected,
+            "{}",
+            solid_angle
+        );
+    }
+}
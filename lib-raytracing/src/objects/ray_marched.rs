@@ -0,0 +1,158 @@
+use crate::{
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable},
+    material::Material,
+    ray::Ray,
+    sdf::Sdf,
+    vec3::Vec3,
+};
+
+use super::sphere::Sphere;
+
+// renders a signed distance field by sphere tracing: step along the ray by the distance to
+// the closest surface until it is closer than epsilon. Normals are the gradient of the field,
+// uvs a spherical projection of the normal
+pub struct RayMarched {
+    pub sdf: Box<dyn Sdf>,
+    pub material: Box<dyn Material>,
+    pub max_steps: u32, // rays still outside after this many steps count as a miss
+    pub epsilon: f64,   // in world units, also the offset for the gradient
+}
+
+impl RayMarched {
+    pub fn new(sdf: impl Sdf + 'static, material: Box<dyn Material>) -> RayMarched {
+        RayMarched {
+            sdf: Box::new(sdf),
+            material,
+            max_steps: 256,
+            epsilon: 1e-4,
+        }
+    }
+
+    pub fn with_budget(mut self, max_steps: u32, epsilon: f64) -> RayMarched {
+        self.max_steps = max_steps;
+        self.epsilon = epsilon;
+        self
+    }
+
+    // central differences of the field, points outwards
+    fn gradient(&self, point: &Vec3) -> Vec3 {
+        let h = self.epsilon;
+        let difference = |offset: Vec3| {
+            self.sdf.distance(&(*point + offset)) - self.sdf.distance(&(*point - offset))
+        };
+        Vec3::unit_vector(&Vec3::new(
+            difference(Vec3::new(h, 0.0, 0.0)),
+            difference(Vec3::new(0.0, h, 0.0)),
+            difference(Vec3::new(0.0, 0.0, h)),
+        ))
+    }
+}
+
+impl Hittable for RayMarched {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
+        let (start, end) = self
+            .sdf
+            .bounding_box()
+            .padded(self.epsilon)
+            .hit_interval(ray, t_min, t_max)?;
+        let length = ray.direction.length();
+
+        // rays leaving a surface (e.g. after scattering) start closer than epsilon to it,
+        // they have to get away from it before a hit counts. Rays entering the box from
+        // outside can hit right away
+        let mut left_surface = start > t_min;
+        let mut t = start;
+        let mut hit = None;
+        for _ in 0..self.max_steps {
+            if t > end {
+                break;
+            }
+            let distance = self.sdf.distance(&ray.at(t)).abs();
+            if distance >= self.epsilon {
+                left_surface = true;
+            } else if left_surface {
+                hit = Some(t);
+                break;
+            }
+            t += distance.max(self.epsilon) / length;
+        }
+        let t = hit?;
+
+        let point = ray.at(t);
+        let outward_normal = self.gradient(&point);
+        if outward_normal.length_squared().is_nan() {
+            return None; // flat field, e.g. deep inside a fractal
+        }
+        let (u, v) = Sphere::get_sphere_uv(&outward_normal);
+        let mut record = HitRecord {
+            point,
+            normal: outward_normal,
+            distance: t,
+            u,
+            v,
+            front_face: false,
+            vertex_color: None,
+            tangent: None,
+            material: self.material.as_ref(),
+        };
+        record.set_face_normal(ray, outward_normal);
+        Some(record)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(self.sdf.bounding_box().padded(self.epsilon))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        hittable::test_material,
+        sdfs::{rounded_box::RoundedBox, sphere::Sphere as SdfSphere},
+    };
+
+    #[test]
+    fn matches_the_analytic_sphere() {
+        let marched = RayMarched::new(
+            SdfSphere {
+                center: Vec3::new(0.0, 0.0, 0.0),
+                radius: 1.0,
+            },
+            test_material(),
+        );
+        let ray = Ray::new(Vec3::new(0.3, 0.2, -5.0), Vec3::new(0.0, 0.0, 2.0));
+        let record = marched.hit(&ray, 0.001, f64::MAX).unwrap();
+        let expected_z = -(1.0 - 0.3 * 0.3 - 0.2 * 0.2_f64).sqrt();
+        assert!((record.point.z - expected_z).abs() < 1e-3);
+        assert!((record.normal - record.point).length() < 1e-3);
+        assert!(record.front_face);
+
+        // leaving the surface from the inside finds the far side
+        let inside = Ray::new(record.point, Vec3::new(0.0, 0.0, 1.0));
+        let record = marched.hit(&inside, 0.001, f64::MAX).unwrap();
+        assert!((record.point.z + expected_z).abs() < 1e-3);
+        assert!(!record.front_face);
+    }
+
+    #[test]
+    fn misses_and_step_budget() {
+        let rounded_box = || RoundedBox {
+            center: Vec3::new(0.0, 0.0, 0.0),
+            half_extent: Vec3::new(1.0, 1.0, 1.0),
+            radius: 0.2,
+        };
+        let grazing = Ray::new(Vec3::new(1.25, 1.25, -5.0), Vec3::new(0.0, 0.0, 1.0));
+        assert!(RayMarched::new(rounded_box(), test_material())
+            .hit(&grazing, 0.001, f64::MAX)
+            .is_none());
+
+        let straight = Ray::new(Vec3::new(0.0, 0.0, -5.0), Vec3::new(0.0, 0.0, 1.0));
+        let marched = RayMarched::new(rounded_box(), test_material());
+        let record = marched.hit(&straight, 0.001, f64::MAX).unwrap();
+        assert!((record.distance - 3.8).abs() < 1e-3);
+        let starved = RayMarched::new(rounded_box(), test_material()).with_budget(0, 1e-4);
+        assert!(starved.hit(&straight, 0.001, f64::MAX).is_none());
+    }
+}
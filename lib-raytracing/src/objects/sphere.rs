@@ -1,7 +1,10 @@
 use crate::{
+    aabb::Aabb,
     hittable::{HitRecord, Hittable},
+    light::{Light, LightSample},
     material::Material,
     ray::Ray,
+    util::{random, INFTY, PI},
     vec3::Vec3,
 };
 
@@ -11,8 +14,18 @@ pub struct Sphere {
     pub material: Box<dyn Material>,
 }
 
-impl Hittable for Sphere {
-    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+impl Sphere {
+    // u: angle around the y axis starting at -x, v: angle from the south to the north pole
+    pub fn get_sphere_uv(outward_normal: &Vec3) -> (f64, f64) {
+        let theta = (-outward_normal.y).clamp(-1.0, 1.0).acos();
+        let phi = f64::atan2(-outward_normal.z, outward_normal.x) + PI;
+        (phi / (2.0 * PI), theta / PI)
+    }
+}
+
+impl Sphere {
+    // both distances at which the ray line crosses the sphere, the nearer one first
+    fn roots(&self, ray: &Ray) -> Option<(f64, f64)> {
         // P(t) = ray.origin + ray.direction* t
         // find t for which (P(t)-sphere.center) dot (P(t)-sphere.center) = sphere.radius²
         // t² * direction dot direction + 2*t*direction dot (origin - center) + (origin - center)²
@@ -29,11 +42,43 @@ impl Hittable for Sphere {
         if discriminant <= 0.0 {
             return None;
         }
-        let root = (b * b - a * c).sqrt();
+        let root = discriminant.sqrt();
+        Some(((-b - root) / a, (-b + root) / a))
+    }
 
-        let t_plus = (-b + root) / a;
-        let t_minus = (-b - root) / a;
+    fn record_at(&self, ray: &Ray, t: f64) -> HitRecord<'_> {
+        let outward_normal = (ray.at(t) - self.center) / self.radius;
+        let (u, v) = Self::get_sphere_uv(&outward_normal);
+
+        let mut record = HitRecord {
+            distance: t,
+            u,
+            v,
+            point: ray.at(t),
+            normal: outward_normal,
+            front_face: false,
+            vertex_color: None,
+            tangent: None,
+            material: self.material.as_ref(),
+        };
+        record.set_face_normal(ray, outward_normal);
+        record
+    }
+
+    // 1 - cos of the half angle of the cone the sphere fills, seen from the given squared
+    // distance to its center, without cancellation for small angles. None from inside
+    fn one_minus_cos_max(&self, distance_squared: f64) -> Option<f64> {
+        let sin_squared = self.radius * self.radius / distance_squared;
+        if sin_squared >= 1.0 {
+            return None;
+        }
+        Some(sin_squared / (1.0 + (1.0 - sin_squared).sqrt()))
+    }
+}
 
+impl Hittable for Sphere {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
+        let (t_minus, t_plus) = self.roots(ray)?;
         let t = if t_minus >= t_min && t_minus <= t_max {
             t_minus
         } else if t_plus >= t_min && t_plus <= t_max {
@@ -41,16 +86,67 @@ impl Hittable for Sphere {
         } else {
             return None;
         };
-        let outward_normal = (ray.at(t) - self.center) / self.radius;
+        Some(self.record_at(ray, t))
+    }
 
-        let mut record = HitRecord {
-            distance: t,
-            point: ray.at(t),
-            normal: (ray.at(t) - self.center) / self.radius,
-            front_face: false,
-            material: &self.material,
+    // the entry and exit of the ray, e.g. for the extent of a medium inside the sphere
+    fn hit_all(&self, ray: &Ray, t_min: f64, t_max: f64) -> Vec<HitRecord<'_>> {
+        let Some((t_minus, t_plus)) = self.roots(ray) else {
+            return vec![];
         };
-        record.set_face_normal(ray, outward_normal);
-        Some(record)
+        [t_minus, t_plus]
+            .into_iter()
+            .filter(|t| (t_min..=t_max).contains(t))
+            .map(|t| self.record_at(ray, t))
+            .collect()
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let radius = Vec3::new(self.radius, self.radius, self.radius);
+        Some(Aabb::new(self.center - radius, self.center + radius))
+    }
+}
+
+// lights up the surroundings with its material's emission. Only the cone of directions under
+// which the sphere is seen is sampled, uniformly, so small and distant spheres converge as
+// quickly as large ones
+impl Light for Sphere {
+    fn sample(&self, point: &Vec3, time: f64) -> Option<LightSample> {
+        let to_center = self.center - *point;
+        let distance_squared = to_center.length_squared();
+        let one_minus_cos_max = self.one_minus_cos_max(distance_squared)?;
+        let axis = Vec3::unit_vector(&to_center);
+        let (tangent, bitangent) = Vec3::orthonormal_basis(&axis);
+        let cos_theta = 1.0 - random() * one_minus_cos_max;
+        let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+        let phi = 2.0 * PI * random();
+        let direction = tangent * (sin_theta * phi.cos())
+            + bitangent * (sin_theta * phi.sin())
+            + axis * cos_theta;
+
+        // rays grazing the silhouette may just miss the sphere numerically
+        let ray = Ray::with_time(*point, direction, time);
+        let record = self.hit(&ray, 0.0, INFTY)?;
+        Some(LightSample {
+            direction,
+            distance: record.distance,
+            radiance: record.material.emitted(&ray, &record),
+            pdf: 1.0 / (2.0 * PI * one_minus_cos_max),
+            is_delta: false,
+        })
+    }
+
+    fn pdf(&self, ray: &Ray, distance: f64) -> f64 {
+        let tolerance = 1e-6 * distance;
+        if self
+            .hit(ray, distance - tolerance, distance + tolerance)
+            .is_none()
+        {
+            return 0.0;
+        }
+        match self.one_minus_cos_max((self.center - ray.origin).length_squared()) {
+            Some(one_minus_cos_max) => 1.0 / (2.0 * PI * one_minus_cos_max),
+            None => 0.0,
+        }
     }
 }
@@ -0,0 +1,144 @@
+use crate::{
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable},
+    material::Material,
+    polynomial::solve_quartic,
+    ray::Ray,
+    util::PI,
+    vec3::Vec3,
+};
+
+use super::sphere::Sphere;
+
+// ring around the y axis through center: a tube of minor_radius swept along a circle of
+// major_radius in the xz plane. u wraps around the y axis like on a sphere, v around the tube
+pub struct Torus {
+    pub center: Vec3,
+    pub major_radius: f64,
+    pub minor_radius: f64,
+    pub material: Box<dyn Material>,
+}
+
+impl Torus {
+    pub fn new(
+        center: Vec3,
+        major_radius: f64,
+        minor_radius: f64,
+        material: Box<dyn Material>,
+    ) -> Torus {
+        Torus {
+            center,
+            major_radius,
+            minor_radius,
+            material,
+        }
+    }
+}
+
+impl Hittable for Torus {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
+        // solve along a unit direction to keep the quartic well conditioned
+        let length = ray.direction.length();
+        let direction = ray.direction / length;
+        let origin = ray.origin - self.center;
+
+        // (|p|² + R² - r²)² = 4R² (px² + pz²) with p = origin + s direction
+        let major2 = self.major_radius * self.major_radius;
+        let minor2 = self.minor_radius * self.minor_radius;
+        let f = Vec3::dot(&origin, &direction);
+        let g = Vec3::dot(&origin, &origin) + major2 - minor2;
+        let direction_xz = direction.x * direction.x + direction.z * direction.z;
+        let origin_direction_xz = origin.x * direction.x + origin.z * direction.z;
+        let origin_xz = origin.x * origin.x + origin.z * origin.z;
+        let roots = solve_quartic(
+            1.0,
+            4.0 * f,
+            4.0 * f * f + 2.0 * g - 4.0 * major2 * direction_xz,
+            4.0 * f * g - 8.0 * major2 * origin_direction_xz,
+            g * g - 4.0 * major2 * origin_xz,
+        );
+        let t = roots
+            .into_iter()
+            .map(|s| s / length)
+            .find(|t| (t_min..=t_max).contains(t))?;
+
+        let point = origin + (t * length) * direction;
+        let radial = Vec3::new(point.x, 0.0, point.z);
+        let radial_distance = radial.length();
+        let ring = if radial_distance > 0.0 {
+            radial * (self.major_radius / radial_distance)
+        } else {
+            Vec3::new(self.major_radius, 0.0, 0.0)
+        };
+        let outward_normal = Vec3::unit_vector(&(point - ring));
+        let (u, _) = Sphere::get_sphere_uv(&radial);
+        let tube_angle = f64::atan2(point.y, radial_distance - self.major_radius);
+
+        let mut record = HitRecord {
+            point: ray.at(t),
+            normal: outward_normal,
+            distance: t,
+            u,
+            v: (tube_angle + PI) / (2.0 * PI),
+            front_face: false,
+            vertex_color: None,
+            tangent: None,
+            material: self.material.as_ref(),
+        };
+        record.set_face_normal(ray, outward_normal);
+        Some(record)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let outer = self.major_radius + self.minor_radius;
+        let extent = Vec3::new(outer, self.minor_radius, outer);
+        Some(Aabb::new(self.center - extent, self.center + extent))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::materials::lambertian::Lambertian;
+
+    fn torus() -> Torus {
+        Torus::new(
+            Vec3::new(0.0, 1.0, 0.0),
+            2.0,
+            0.5,
+            Box::new(Lambertian::new(Vec3::new(0.5, 0.5, 0.5))),
+        )
+    }
+
+    #[test]
+    fn ray_through_the_ring_hits_both_sides() {
+        let torus = torus();
+        let ray = Ray::new(Vec3::new(-10.0, 1.0, 0.0), Vec3::new(2.0, 0.0, 0.0));
+        let record = torus.hit(&ray, 0.001, f64::MAX).unwrap();
+        // outer edge of the tube at x = -2.5, distance in units of the ray direction
+        assert!((record.distance - 3.75).abs() < 1e-9);
+        assert!((record.normal - Vec3::new(-1.0, 0.0, 0.0)).length() < 1e-9);
+        assert!(record.front_face);
+
+        // through the hole from inside the tube outwards
+        let record = torus.hit(&ray, 4.0, f64::MAX).unwrap();
+        assert!((record.point.x + 1.5).abs() < 1e-9);
+        assert!(!record.front_face);
+    }
+
+    #[test]
+    fn misses_through_the_hole() {
+        let ray = Ray::new(Vec3::new(0.0, 10.0, 0.0), Vec3::new(0.0, -1.0, 0.0));
+        assert!(torus().hit(&ray, 0.001, f64::MAX).is_none());
+    }
+
+    #[test]
+    fn top_of_the_tube() {
+        let ray = Ray::new(Vec3::new(0.0, 5.0, 2.0), Vec3::new(0.0, -1.0, 0.0));
+        let torus = torus();
+        let record = torus.hit(&ray, 0.001, f64::MAX).unwrap();
+        assert!((record.point.y - 1.5).abs() < 1e-9);
+        assert!((record.normal - Vec3::new(0.0, 1.0, 0.0)).length() < 1e-9);
+        assert!((record.v - 0.75).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,125 @@
+use std::sync::Arc;
+
+use crate::{
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable},
+    ray::Ray,
+    transform::Transform,
+    vec3::Vec3,
+};
+
+// places an object in the world by moving rays into its object space and the hit back out
+pub struct Transformed<H: Hittable> {
+    pub object: H,
+    pub transform: Transform,
+}
+
+// a shared object placed with its own transform, cloning the Arc is all it takes to
+// place the same mesh many times
+pub type Instance = Transformed<Arc<dyn Hittable>>;
+
+impl<H: Hittable> Transformed<H> {
+    pub fn new(object: H, transform: Transform) -> Transformed<H> {
+        Transformed { object, transform }
+    }
+}
+
+// moves a hit found in object space back into the world. The inverse transpose keeps the
+// sign of dot(normal, direction), so front_face and the orientation of the normal stay valid
+pub(crate) fn record_to_world<'a>(
+    transform: &Transform,
+    mut record: HitRecord<'a>,
+) -> HitRecord<'a> {
+    record.point = transform.point(&record.point);
+    record.normal = transform.normal(&record.normal);
+    record.tangent = record
+        .tangent
+        .map(|tangent| Vec3::unit_vector(&transform.direction(&tangent)));
+    record
+}
+
+impl<H: Hittable> Hittable for Transformed<H> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
+        let object_ray = self.transform.ray_to_object(ray);
+        let record = self.object.hit(&object_ray, t_min, t_max)?;
+        Some(record_to_world(&self.transform, record))
+    }
+
+    fn hit_all(&self, ray: &Ray, t_min: f64, t_max: f64) -> Vec<HitRecord<'_>> {
+        let object_ray = self.transform.ray_to_object(ray);
+        self.object
+            .hit_all(&object_ray, t_min, t_max)
+            .into_iter()
+            .map(|record| record_to_world(&self.transform, record))
+            .collect()
+    }
+
+    fn transmittance(&self, ray: &Ray, t_min: f64, t_max: f64) -> f64 {
+        let object_ray = self.transform.ray_to_object(ray);
+        self.object.transmittance(&object_ray, t_min, t_max)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let object_box = self.object.bounding_box()?;
+        Some(self.transform.bounding_box(&object_box))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{materials::lambertian::Lambertian, objects::sphere::Sphere};
+
+    fn unit_sphere() -> Arc<dyn Hittable> {
+        Arc::new(Sphere {
+            center: Vec3::new(0.0, 0.0, 0.0),
+            radius: 1.0,
+            material: Box::new(Lambertian::new(Vec3::new(0.5, 0.5, 0.5))),
+        })
+    }
+
+    #[test]
+    fn instances_share_one_object() {
+        let sphere = unit_sphere();
+        let instances: Vec<Instance> = (0..3)
+            .map(|i| {
+                let offset = Vec3::new(3.0 * i as f64, 0.0, 0.0);
+                Instance::new(sphere.clone(), Transform::default().translate(offset))
+            })
+            .collect();
+
+        let ray = Ray::new(Vec3::new(6.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0));
+        let record = instances[2].hit(&ray, 0.001, f64::MAX).unwrap();
+        assert!((record.point - Vec3::new(6.0, 0.0, 1.0)).length() < 1e-9);
+        assert!(instances[0].hit(&ray, 0.001, f64::MAX).is_none());
+    }
+
+    #[test]
+    fn scaled_hit_keeps_distance_and_normal() {
+        let ellipsoid = Transformed::new(
+            unit_sphere(),
+            Transform::default().scale(Vec3::new(2.0, 1.0, 1.0)),
+        );
+        let direction = Vec3::unit_vector(&Vec3::new(-1.0, 0.0, -1.0));
+        let ray = Ray::new(Vec3::new(4.0, 0.0, 4.0), direction);
+        let record = ellipsoid.hit(&ray, 0.001, f64::MAX).unwrap();
+
+        assert!((ray.at(record.distance) - record.point).length() < 1e-9);
+        // implicit surface (x/2)^2 + y^2 + z^2 = 1 has its normal along (x/4, y, z)
+        let p = record.point;
+        let expected = Vec3::unit_vector(&Vec3::new(p.x / 4.0, p.y, p.z));
+        assert!((record.normal - expected).length() < 1e-9);
+        assert!(record.front_face);
+    }
+
+    #[test]
+    fn bounding_box_is_transformed() {
+        let moved = Transformed::new(
+            unit_sphere(),
+            Transform::default().translate(Vec3::new(0.0, 5.0, 0.0)),
+        );
+        let bounding_box = moved.bounding_box().unwrap();
+        assert!((bounding_box.minimum.y - 4.0).abs() < 1e-9);
+        assert!((bounding_box.maximum.y - 6.0).abs() < 1e-9);
+    }
+}
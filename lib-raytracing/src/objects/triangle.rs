@@ -1,4 +1,5 @@
 use crate::{
+    aabb::Aabb,
     hittable::{HitRecord, Hittable},
     material::Material,
     ray::Ray,
@@ -9,55 +10,237 @@ pub struct Triangle {
     a: Vec3,
     b: Vec3,
     c: Vec3,
+    uvs: [(f64, f64); 3],       // texture coordinates at a, b and c
+    normals: Option<[Vec3; 3]>, // shading normals at a, b and c for smooth shading
     material: Box<dyn Material>,
 }
 
+// weights of the corners a, b and c for a point inside a triangle, they always sum up to 1
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Barycentric {
+    pub alpha: f64,
+    pub beta: f64,
+    pub gamma: f64,
+}
+
+impl Barycentric {
+    pub fn interpolate(&self, a: Vec3, b: Vec3, c: Vec3) -> Vec3 {
+        self.alpha * a + self.beta * b + self.gamma * c
+    }
+
+    pub fn interpolate_uv(&self, a: (f64, f64), b: (f64, f64), c: (f64, f64)) -> (f64, f64) {
+        (
+            self.alpha * a.0 + self.beta * b.0 + self.gamma * c.0,
+            self.alpha * a.1 + self.beta * b.1 + self.gamma * c.1,
+        )
+    }
+}
+
+// the uvs a triangle gets without explicit texture coordinates, u and v are then the
+// barycentric weights of b and c
+pub const DEFAULT_UVS: [(f64, f64); 3] = [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0)];
+
+// Möller–Trumbore ray triangle intersection, returns the distance along the ray and the
+// barycentric coordinates of the hit. Shared with meshes that store their vertices elsewhere
+pub fn intersect(
+    a: &Vec3,
+    b: &Vec3,
+    c: &Vec3,
+    ray: &Ray,
+    t_min: f64,
+    t_max: f64,
+) -> Option<(f64, Barycentric)> {
+    const EPSILON: f64 = 0.0000001;
+    let ab = *b - *a;
+    let ac = *c - *a;
+
+    let plane_vector = Vec3::cross(&ray.direction, &ac);
+    let determinant = Vec3::dot(&ab, &plane_vector);
+
+    if determinant.abs() < EPSILON {
+        return None;
+    }
+
+    let inverse_determinant = 1.0 / determinant;
+    let t_vector = ray.origin - *a;
+    let u = inverse_determinant * Vec3::dot(&t_vector, &plane_vector);
+
+    if !(-EPSILON..=1.0 + EPSILON).contains(&u) {
+        return None;
+    }
+    let q_vector = Vec3::cross(&t_vector, &ab);
+    let v = inverse_determinant * Vec3::dot(&ray.direction, &q_vector);
+
+    if v < -EPSILON || u + v > 1.0 {
+        return None;
+    }
+
+    let t = inverse_determinant * Vec3::dot(&ac, &q_vector);
+    if t > EPSILON && t < t_max && t > t_min {
+        Some((
+            t,
+            Barycentric {
+                alpha: 1.0 - u - v,
+                beta: u,
+                gamma: v,
+            },
+        ))
+    } else {
+        None
+    } // ray intersects
+}
+
 impl Triangle {
+    // the outward side is the one from which a, b and c appear counter clockwise
+    pub fn new(a: Vec3, b: Vec3, c: Vec3, material: Box<dyn Material>) -> Triangle {
+        Triangle {
+            a,
+            b,
+            c,
+            uvs: DEFAULT_UVS,
+            normals: None,
+            material,
+        }
+    }
+
     fn get_surface_normal(&self) -> Vec3 {
         let ab = self.b - self.a;
         let ac = self.c - self.a;
-        Vec3::cross(&ab, &ac)
+        Vec3::unit_vector(&Vec3::cross(&ab, &ac))
+    }
+
+    pub fn with_uvs(mut self, uvs: [(f64, f64); 3]) -> Triangle {
+        self.uvs = uvs;
+        self
+    }
+
+    pub fn with_normals(mut self, normals: [Vec3; 3]) -> Triangle {
+        self.normals = Some(normals.map(|normal| Vec3::unit_vector(&normal)));
+        self
+    }
+}
+
+// orient the record by the geometric normal, so front_face is correct even where the
+// interpolated shading normal leans away from the ray, then apply the shading normal on
+// the side the ray came from
+pub fn set_shading_normal(
+    record: &mut HitRecord,
+    ray: &Ray,
+    geometric_normal: Vec3,
+    shading_normal: Option<Vec3>,
+) {
+    record.set_face_normal(ray, geometric_normal);
+    if let Some(shading_normal) = shading_normal {
+        let shading_normal = Vec3::unit_vector(&shading_normal);
+        record.normal = if record.front_face {
+            shading_normal
+        } else {
+            shading_normal * -1.0
+        };
     }
 }
 
 impl Hittable for Triangle {
-    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
-        const EPSILON: f64 = 0.0000001;
-        let ab = self.b - self.a;
-        let ac = self.c - self.a;
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
+        let (t, barycentric) = intersect(&self.a, &self.b, &self.c, ray, t_min, t_max)?;
+        let (u, v) = barycentric.interpolate_uv(self.uvs[0], self.uvs[1], self.uvs[2]);
+        let geometric_normal = self.get_surface_normal();
+        let mut record = HitRecord {
+            point: ray.at(t),
+            normal: geometric_normal,
+            distance: t,
+            u,
+            v,
+            front_face: false,
+            vertex_color: None,
+            tangent: None,
+            material: self.material.as_ref(),
+        };
+        let shading_normal = self
+            .normals
+            .map(|normals| barycentric.interpolate(normals[0], normals[1], normals[2]));
+        set_shading_normal(&mut record, ray, geometric_normal, shading_normal);
+        Some(record)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(Aabb::from_points(&[self.a, self.b, self.c]).padded(0.0001))
+    }
+}
 
-        let plane_vector = Vec3::cross(&ray.direction, &ac);
-        let determinant = Vec3::dot(&ab, &plane_vector);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::materials::lambertian::Lambertian;
 
-        if determinant.abs() < EPSILON {
-            return None;
-        }
+    fn triangle() -> Triangle {
+        Triangle::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            Box::new(Lambertian::new(Vec3::new(0.5, 0.5, 0.5))),
+        )
+    }
 
-        let inverse_determinant = 1.0 / determinant;
-        let t_vector = ray.origin - self.a;
-        let u = inverse_determinant * Vec3::dot(&t_vector, &plane_vector);
+    #[test]
+    fn barycentrics_reproduce_hit_point() {
+        let (a, b, c) = (
+            Vec3::new(-1.0, 0.0, 2.0),
+            Vec3::new(3.0, 0.5, 2.0),
+            Vec3::new(0.0, 2.0, 1.0),
+        );
+        let ray = Ray::new(Vec3::new(0.5, 0.5, -5.0), Vec3::new(0.0, 0.0, 1.0));
+        let (t, barycentric) = intersect(&a, &b, &c, &ray, 0.0, f64::MAX).unwrap();
+        assert!((barycentric.interpolate(a, b, c) - ray.at(t)).length() < 1e-9);
+        assert!((barycentric.alpha + barycentric.beta + barycentric.gamma - 1.0).abs() < 1e-12);
+    }
 
-        if u < -EPSILON || u > 1.0 + EPSILON {
-            return None;
-        }
-        let q_vector = Vec3::cross(&t_vector, &ab);
-        let v = inverse_determinant * Vec3::dot(&ray.direction, &q_vector);
+    #[test]
+    fn default_uvs_are_barycentric() {
+        let ray = Ray::new(Vec3::new(0.25, 0.5, -1.0), Vec3::new(0.0, 0.0, 1.0));
+        let triangle = triangle();
+        let record = triangle.hit(&ray, 0.0, f64::MAX).unwrap();
+        assert!((record.u - 0.25).abs() < 1e-12 && (record.v - 0.5).abs() < 1e-12);
+    }
 
-        if v < -EPSILON || u + v > 1.0 {
-            return None;
-        }
+    #[test]
+    fn per_vertex_uvs_are_interpolated() {
+        let triangle = triangle().with_uvs([(0.5, 0.5), (1.0, 0.5), (0.5, 1.0)]);
+        let ray = Ray::new(Vec3::new(0.5, 0.0, -1.0), Vec3::new(0.0, 0.0, 1.0));
+        let record = triangle.hit(&ray, 0.0, f64::MAX).unwrap();
+        assert!((record.u - 0.75).abs() < 1e-12 && (record.v - 0.5).abs() < 1e-12);
+    }
 
-        let t = inverse_determinant * Vec3::dot(&ac, &q_vector);
-        if t > EPSILON && t < t_max && t > t_min {
-            Some(HitRecord {
-                point: ray.at(t),
-                normal: self.get_surface_normal(),
-                distance: t,
-                front_face: false,
-                material: &self.material,
-            })
-        } else {
-            None
-        } // ray intersects
+    #[test]
+    fn front_face_follows_winding() {
+        let triangle = triangle();
+        let from_front = Ray::new(Vec3::new(0.25, 0.25, 1.0), Vec3::new(0.0, 0.0, -1.0));
+        let record = triangle.hit(&from_front, 0.0, f64::MAX).unwrap();
+        assert!(record.front_face);
+        assert_eq!(record.normal, Vec3::new(0.0, 0.0, 1.0));
+
+        let from_back = Ray::new(Vec3::new(0.25, 0.25, -1.0), Vec3::new(0.0, 0.0, 1.0));
+        let record = triangle.hit(&from_back, 0.0, f64::MAX).unwrap();
+        assert!(!record.front_face);
+        assert_eq!(record.normal, Vec3::new(0.0, 0.0, -1.0));
+    }
+
+    #[test]
+    fn vertex_normals_are_interpolated() {
+        let tilted = Vec3::new(1.0, 0.0, 1.0);
+        let triangle =
+            triangle().with_normals([Vec3::new(0.0, 0.0, 1.0), tilted, Vec3::new(0.0, 0.0, 1.0)]);
+        let ray = Ray::new(Vec3::new(0.5, 0.0, -1.0), Vec3::new(0.0, 0.0, 1.0));
+        let record = triangle.hit(&ray, 0.0, f64::MAX).unwrap();
+        let expected =
+            Vec3::unit_vector(&(0.5 * Vec3::new(0.0, 0.0, 1.0) + 0.5 * Vec3::unit_vector(&tilted)));
+        assert!(!record.front_face);
+        assert!((record.normal + expected).length() < 1e-9);
+    }
+
+    #[test]
+    fn miss_outside() {
+        let ray = Ray::new(Vec3::new(1.0, 1.0, -1.0), Vec3::new(0.0, 0.0, 1.0));
+        assert!(triangle().hit(&ray, 0.0, f64::MAX).is_none());
     }
 }
@@ -0,0 +1,260 @@
+use crate::{
+    aabb::Aabb,
+    bvh::BvhTree,
+    hittable::{HitRecord, Hittable},
+    material::Material,
+    ray::Ray,
+    vec3::Vec3,
+};
+
+use super::triangle::{intersect, set_shading_normal, DEFAULT_UVS};
+
+// one triangle of a mesh, all fields index into the buffers of the mesh
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MeshFace {
+    pub positions: [usize; 3],
+    pub normals: Option<[usize; 3]>,
+    pub uvs: Option<[usize; 3]>,
+    pub material: usize,
+}
+
+// triangles sharing vertex buffers and materials, with its own bvh over the faces
+pub struct TriangleMesh {
+    positions: Vec<Vec3>,
+    normals: Vec<Vec3>,
+    uvs: Vec<(f64, f64)>,
+    colors: Vec<Vec3>, // optional per vertex colors, indexed like the positions
+    faces: Vec<MeshFace>,
+    materials: Vec<Box<dyn Material>>,
+    tree: BvhTree,
+}
+
+impl TriangleMesh {
+    pub fn new(
+        positions: Vec<Vec3>,
+        normals: Vec<Vec3>,
+        uvs: Vec<(f64, f64)>,
+        faces: Vec<MeshFace>,
+        materials: Vec<Box<dyn Material>>,
+    ) -> TriangleMesh {
+        for face in &faces {
+            assert!(
+                face.positions.iter().all(|i| *i < positions.len()),
+                "mesh face references a missing position"
+            );
+            assert!(
+                face.normals.iter().flatten().all(|i| *i < normals.len()),
+                "mesh face references a missing normal"
+            );
+            assert!(
+                face.uvs.iter().flatten().all(|i| *i < uvs.len()),
+                "mesh face references a missing uv"
+            );
+            assert!(
+                face.material < materials.len(),
+                "mesh face references a missing material"
+            );
+        }
+
+        let bounding_boxes: Vec<Aabb> = faces
+            .iter()
+            .map(|face| Aabb::from_points(&face.positions.map(|i| positions[i])).padded(0.0001))
+            .collect();
+        let (tree, order) = BvhTree::build(&bounding_boxes);
+        let faces = order.into_iter().map(|index| faces[index]).collect();
+
+        TriangleMesh {
+            positions,
+            // zero normals stay zero, hit_face replaces them
+            normals: normals
+                .iter()
+                .map(|normal| {
+                    if normal.near_zero() {
+                        *normal
+                    } else {
+                        Vec3::unit_vector(normal)
+                    }
+                })
+                .collect(),
+            uvs,
+            colors: vec![],
+            faces,
+            materials,
+            tree,
+        }
+    }
+
+    // colors are interpolated like the positions and exposed to textures as
+    // HitRecord::vertex_color, see textures::vertex_color
+    pub fn with_vertex_colors(mut self, colors: Vec<Vec3>) -> TriangleMesh {
+        assert_eq!(
+            colors.len(),
+            self.positions.len(),
+            "mesh needs one vertex color per position"
+        );
+        self.colors = colors;
+        self
+    }
+
+    pub fn face_count(&self) -> usize {
+        self.faces.len()
+    }
+
+    pub fn material_count(&self) -> usize {
+        self.materials.len()
+    }
+
+    fn hit_face(
+        &self,
+        face: &MeshFace,
+        ray: &Ray,
+        t_min: f64,
+        t_max: f64,
+    ) -> Option<HitRecord<'_>> {
+        let [a, b, c] = face.positions.map(|i| self.positions[i]);
+        let (t, barycentric) = intersect(&a, &b, &c, ray, t_min, t_max)?;
+
+        let [uv_a, uv_b, uv_c] = match face.uvs {
+            Some(uvs) => uvs.map(|i| self.uvs[i]),
+            None => DEFAULT_UVS,
+        };
+        let (u, v) = barycentric.interpolate_uv(uv_a, uv_b, uv_c);
+        let geometric_normal = Vec3::unit_vector(&Vec3::cross(&(b - a), &(c - a)));
+        let vertex_color = (!self.colors.is_empty()).then(|| {
+            let [color_a, color_b, color_c] = face.positions.map(|i| self.colors[i]);
+            barycentric.interpolate(color_a, color_b, color_c)
+        });
+
+        let mut record = HitRecord {
+            point: ray.at(t),
+            normal: geometric_normal,
+            distance: t,
+            u,
+            v,
+            front_face: false,
+            vertex_color,
+            tangent: None,
+            material: self.materials[face.material].as_ref(),
+        };
+        let shading_normal = face.normals.map(|normals| {
+            // exporters write zero normals for vertices they found none for
+            let [n_a, n_b, n_c] = normals.map(|i| {
+                let normal = self.normals[i];
+                if normal.near_zero() {
+                    geometric_normal
+                } else {
+                    normal
+                }
+            });
+            barycentric.interpolate(n_a, n_b, n_c)
+        });
+        set_shading_normal(&mut record, ray, geometric_normal, shading_normal);
+        Some(record)
+    }
+}
+
+impl Hittable for TriangleMesh {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
+        self.tree.hit(ray, t_min, t_max, |index, closest_so_far| {
+            self.hit_face(&self.faces[index], ray, t_min, closest_so_far)
+        })
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.tree.bounding_box()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::materials::lambertian::Lambertian;
+
+    // unit quad in the xy plane made of two triangles, facing +z
+    fn quad() -> TriangleMesh {
+        TriangleMesh::new(
+            vec![
+                Vec3::new(0.0, 0.0, 0.0),
+                Vec3::new(1.0, 0.0, 0.0),
+                Vec3::new(1.0, 1.0, 0.0),
+                Vec3::new(0.0, 1.0, 0.0),
+            ],
+            vec![],
+            vec![(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)],
+            vec![
+                MeshFace {
+                    positions: [0, 1, 2],
+                    normals: None,
+                    uvs: Some([0, 1, 2]),
+                    material: 0,
+                },
+                MeshFace {
+                    positions: [0, 2, 3],
+                    normals: None,
+                    uvs: Some([0, 2, 3]),
+                    material: 0,
+                },
+            ],
+            vec![Box::new(Lambertian::new(Vec3::new(0.5, 0.5, 0.5)))],
+        )
+    }
+
+    #[test]
+    fn hits_both_faces_with_shared_uvs() {
+        let mesh = quad();
+        for (x, y) in [(0.75, 0.25), (0.25, 0.75)] {
+            let ray = Ray::new(Vec3::new(x, y, 1.0), Vec3::new(0.0, 0.0, -1.0));
+            let record = mesh.hit(&ray, 0.001, f64::MAX).unwrap();
+            assert!((record.distance - 1.0).abs() < 1e-12);
+            assert!((record.u - x).abs() < 1e-12 && (record.v - y).abs() < 1e-12);
+            assert!(record.front_face);
+        }
+    }
+
+    #[test]
+    fn misses_outside() {
+        let mesh = quad();
+        let ray = Ray::new(Vec3::new(1.5, 0.5, 1.0), Vec3::new(0.0, 0.0, -1.0));
+        assert!(mesh.hit(&ray, 0.001, f64::MAX).is_none());
+    }
+
+    #[test]
+    fn zero_normals_fall_back_to_the_face() {
+        let mesh = TriangleMesh::new(
+            vec![
+                Vec3::new(0.0, 0.0, 0.0),
+                Vec3::new(1.0, 0.0, 0.0),
+                Vec3::new(1.0, 1.0, 0.0),
+            ],
+            vec![Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 2.0)],
+            vec![],
+            vec![MeshFace {
+                positions: [0, 1, 2],
+                normals: Some([0, 0, 1]),
+                uvs: None,
+                material: 0,
+            }],
+            vec![Box::new(Lambertian::new(Vec3::new(0.5, 0.5, 0.5)))],
+        );
+        let ray = Ray::new(Vec3::new(0.5, 0.25, 1.0), Vec3::new(0.0, 0.0, -1.0));
+        let record = mesh.hit(&ray, 0.001, f64::MAX).unwrap();
+        assert_eq!(record.normal, Vec3::new(0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    #[should_panic]
+    fn rejects_bad_indices() {
+        TriangleMesh::new(
+            vec![Vec3::new(0.0, 0.0, 0.0)],
+            vec![],
+            vec![],
+            vec![MeshFace {
+                positions: [0, 1, 2],
+                normals: None,
+                uvs: None,
+                material: 0,
+            }],
+            vec![Box::new(Lambertian::new(Vec3::new(0.5, 0.5, 0.5)))],
+        );
+    }
+}
@@ -0,0 +1,138 @@
+// real roots of low degree polynomials, used by the analytic intersection of primitives
+
+use crate::util::PI;
+
+// roots of a x² + b x + c in ascending order, None if there are no real roots
+pub fn solve_quadratic(a: f64, b: f64, c: f64) -> Option<(f64, f64)> {
+    if a == 0.0 {
+        return (b != 0.0).then(|| (-c / b, -c / b));
+    }
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0.0 {
+        return None;
+    }
+    // avoids the cancellation of -b + sqrt(discriminant) when b² >> 4ac
+    let q = -0.5 * (b + b.signum() * discriminant.sqrt());
+    if q == 0.0 {
+        return Some((0.0, 0.0));
+    }
+    let (x0, x1) = (q / a, c / q);
+    Some((x0.min(x1), x0.max(x1)))
+}
+
+// real roots of x³ + a x² + b x + c, one or three of them
+pub fn solve_cubic(a: f64, b: f64, c: f64) -> Vec<f64> {
+    let q = (a * a - 3.0 * b) / 9.0;
+    let r = (2.0 * a * a * a - 9.0 * a * b + 27.0 * c) / 54.0;
+    let shift = a / 3.0;
+    if r * r < q * q * q {
+        // three real roots, trigonometric form
+        let theta = (r / (q * q * q).sqrt()).clamp(-1.0, 1.0).acos();
+        let scale = -2.0 * q.sqrt();
+        [theta, theta + 2.0 * PI, theta - 2.0 * PI]
+            .iter()
+            .map(|angle| scale * (angle / 3.0).cos() - shift)
+            .collect()
+    } else {
+        let big_a = -r.signum() * (r.abs() + (r * r - q * q * q).sqrt()).cbrt();
+        let big_b = if big_a == 0.0 { 0.0 } else { q / big_a };
+        vec![big_a + big_b - shift]
+    }
+}
+
+// real roots of a x⁴ + b x³ + c x² + d x + e (a != 0) in ascending order, using ferrari's
+// method on the depressed quartic. Roots are polished with newton steps on the original
+// polynomial since the closed form loses precision
+pub fn solve_quartic(a: f64, b: f64, c: f64, d: f64, e: f64) -> Vec<f64> {
+    let (b, c, d, e) = (b / a, c / a, d / a, e / a);
+    // x = y - b/4 removes the cubic term: y⁴ + p y² + q y + r
+    let b2 = b * b;
+    let p = c - 3.0 * b2 / 8.0;
+    let q = d - b * c / 2.0 + b2 * b / 8.0;
+    let r = e - b * d / 4.0 + b2 * c / 16.0 - 3.0 * b2 * b2 / 256.0;
+
+    let mut roots = vec![];
+    if q.abs() < 1e-12 {
+        // biquadratic, solve for y²
+        if let Some((z0, z1)) = solve_quadratic(1.0, p, r) {
+            for z in [z0, z1].into_iter().filter(|z| *z >= 0.0) {
+                roots.extend([z.sqrt(), -z.sqrt()]);
+            }
+        }
+    } else {
+        // any positive root m of the resolvent cubic splits the quartic into two quadratics
+        // (y² + p/2 + m)² = 2m (y - q / 4m)²
+        let m = solve_cubic(p, p * p / 4.0 - r, -q * q / 8.0)
+            .into_iter()
+            .fold(f64::NEG_INFINITY, f64::max);
+        if m > 0.0 {
+            let s = (2.0 * m).sqrt();
+            for sign in [1.0, -1.0] {
+                let constant = p / 2.0 + m + sign * s * q / (4.0 * m);
+                if let Some((y0, y1)) = solve_quadratic(1.0, -sign * s, constant) {
+                    roots.extend([y0, y1]);
+                }
+            }
+        }
+    }
+
+    let mut roots: Vec<f64> = roots
+        .into_iter()
+        .map(|y| {
+            let mut x = y - b / 4.0;
+            for _ in 0..2 {
+                let value = (((x + b) * x + c) * x + d) * x + e;
+                let derivative = ((4.0 * x + 3.0 * b) * x + 2.0 * c) * x + d;
+                if derivative != 0.0 {
+                    x -= value / derivative;
+                }
+            }
+            x
+        })
+        .collect();
+    roots.sort_by(f64::total_cmp);
+    roots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quadratic_roots() {
+        assert_eq!(solve_quadratic(1.0, -3.0, 2.0), Some((1.0, 2.0)));
+        assert_eq!(solve_quadratic(1.0, 0.0, 1.0), None);
+        assert_eq!(solve_quadratic(0.0, 2.0, -4.0), Some((2.0, 2.0)));
+    }
+
+    #[test]
+    fn cubic_roots() {
+        // (x - 1)(x - 2)(x + 3) = x³ - 7x + 6
+        let mut roots = solve_cubic(0.0, -7.0, 6.0);
+        roots.sort_by(f64::total_cmp);
+        for (root, expected) in roots.iter().zip([-3.0, 1.0, 2.0]) {
+            assert!((root - expected).abs() < 1e-9);
+        }
+        // x³ + x + 1 has a single real root near -0.6823
+        let roots = solve_cubic(0.0, 1.0, 1.0);
+        assert_eq!(roots.len(), 1);
+        assert!((roots[0] + 0.6823278).abs() < 1e-6);
+    }
+
+    #[test]
+    fn quartic_roots() {
+        // 2 (x + 4)(x + 1)(x - 0.5)(x - 3)
+        let expected = [-4.0, -1.0, 0.5, 3.0];
+        let roots = solve_quartic(2.0, 3.0, -24.0, -13.0, 12.0);
+        assert_eq!(roots.len(), 4);
+        for (root, expected) in roots.iter().zip(expected) {
+            assert!((root - expected).abs() < 1e-9, "{} != {}", root, expected);
+        }
+        // (x² - 4)(x² + 1) has only two real roots and no cubic or linear term
+        let roots = solve_quartic(1.0, 0.0, -3.0, 0.0, -4.0);
+        assert_eq!(roots.len(), 2);
+        assert!((roots[0] + 2.0).abs() < 1e-9 && (roots[1] - 2.0).abs() < 1e-9);
+        // x⁴ + 1 has none
+        assert!(solve_quartic(1.0, 0.0, 0.0, 0.0, 1.0).is_empty());
+    }
+}
@@ -0,0 +1,55 @@
+use std::sync::Arc;
+
+use crate::{
+    background::Background,
+    hittable::Hittable,
+    light::{Light, LightSample},
+    ray::Ray,
+    util::random,
+    vec3::Vec3,
+};
+
+// everything render_scene needs besides the camera
+pub struct Scene {
+    pub world: Box<dyn Hittable>,
+    pub background: Box<dyn Background>,
+    pub lights: Vec<Arc<dyn Light>>, // sampled directly at every bounce that is not specular
+}
+
+impl Scene {
+    pub fn new(world: impl Hittable + 'static, background: impl Background + 'static) -> Scene {
+        Scene {
+            world: Box::new(world),
+            background: Box::new(background),
+            lights: vec![],
+        }
+    }
+
+    // a sample of one light picked uniformly, its pdf includes the odds of picking the light
+    pub fn sample_light(&self, point: &Vec3, time: f64) -> Option<LightSample> {
+        if self.lights.is_empty() {
+            return None;
+        }
+        let count = self.lights.len();
+        let index = ((random() * count as f64) as usize).min(count - 1);
+        let sample = self.lights[index].sample(point, time)?;
+        Some(LightSample {
+            pdf: sample.pdf / count as f64,
+            ..sample
+        })
+    }
+
+    // density with which sample_light picks the direction of a ray that reaches a light at
+    // distance
+    pub fn light_pdf(&self, ray: &Ray, distance: f64) -> f64 {
+        if self.lights.is_empty() {
+            return 0.0;
+        }
+        let total: f64 = self
+            .lights
+            .iter()
+            .map(|light| light.pdf(ray, distance))
+            .sum();
+        total / self.lights.len() as f64
+    }
+}
@@ -0,0 +1,11 @@
+use crate::{aabb::Aabb, vec3::Vec3};
+
+// signed distance field: distance from a point to the closest surface, negative inside.
+// Fields that only bound the true distance from below (e.g. fractal estimators) work too,
+// sphere tracing just takes more steps
+pub trait Sdf: Send + Sync {
+    fn distance(&self, point: &Vec3) -> f64;
+
+    // the region the surface lies in, marching starts and ends at its boundary
+    fn bounding_box(&self) -> Aabb;
+}
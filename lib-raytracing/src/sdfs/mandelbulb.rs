@@ -0,0 +1,82 @@
+use crate::{aabb::Aabb, sdf::Sdf, vec3::Vec3};
+
+// the power 8 mandelbulb fractal scaled around center, more iterations give finer detail
+// at the cost of slower distance estimates
+pub struct Mandelbulb {
+    pub center: Vec3,
+    pub scale: f64,
+    pub power: f64,
+    pub iterations: u32,
+}
+
+impl Mandelbulb {
+    pub fn new(center: Vec3, scale: f64) -> Mandelbulb {
+        Mandelbulb {
+            center,
+            scale,
+            power: 8.0,
+            iterations: 12,
+        }
+    }
+
+    // distance estimate in fractal space from the running derivative of the iteration
+    fn estimate(&self, point: &Vec3) -> f64 {
+        const BAILOUT: f64 = 2.0;
+        let mut z = *point;
+        let mut derivative = 1.0;
+        let mut radius = z.length();
+        for _ in 0..self.iterations {
+            if radius > BAILOUT {
+                break;
+            }
+            // z = z^power + point in spherical coordinates
+            let theta = if radius > 0.0 {
+                (z.z / radius).clamp(-1.0, 1.0).acos()
+            } else {
+                0.0
+            };
+            let phi = f64::atan2(z.y, z.x);
+            derivative = self.power * radius.powf(self.power - 1.0) * derivative + 1.0;
+            let scaled_radius = radius.powf(self.power);
+            let (theta, phi) = (theta * self.power, phi * self.power);
+            z = scaled_radius
+                * Vec3::new(
+                    theta.sin() * phi.cos(),
+                    theta.sin() * phi.sin(),
+                    theta.cos(),
+                )
+                + *point;
+            radius = z.length();
+        }
+        if radius == 0.0 {
+            return 0.0;
+        }
+        0.5 * radius.ln() * radius / derivative
+    }
+}
+
+impl Sdf for Mandelbulb {
+    fn distance(&self, point: &Vec3) -> f64 {
+        self.scale * self.estimate(&((*point - self.center) / self.scale))
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        // the power 8 bulb fits into a radius of about 1.2
+        let extent = 1.25 * self.scale;
+        Aabb::new(self.center, self.center).padded(extent)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn distance_estimate_is_signed_like() {
+        let bulb = Mandelbulb::new(Vec3::new(0.0, 0.0, 0.0), 1.0);
+        // the origin is part of the set, points far away are roughly their distance off
+        assert!(bulb.distance(&Vec3::new(0.0, 0.0, 0.0)) <= 1e-9);
+        let far = bulb.distance(&Vec3::new(0.0, 0.0, 3.0));
+        assert!(far > 1.0 && far < 3.0);
+    }
+}
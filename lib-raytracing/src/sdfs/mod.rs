@@ -0,0 +1,4 @@
+pub mod mandelbulb;
+pub mod rounded_box;
+pub mod smooth_union;
+pub mod sphere;
@@ -0,0 +1,43 @@
+use crate::{aabb::Aabb, sdf::Sdf, vec3::Vec3};
+
+// axis aligned box around center with its edges rounded off by radius, the rounding is
+// added on top of half_extent
+pub struct RoundedBox {
+    pub center: Vec3,
+    pub half_extent: Vec3,
+    pub radius: f64,
+}
+
+impl Sdf for RoundedBox {
+    fn distance(&self, point: &Vec3) -> f64 {
+        let offset = *point - self.center;
+        let q = Vec3::new(offset.x.abs(), offset.y.abs(), offset.z.abs()) - self.half_extent;
+        let outside = Vec3::component_max(&q, &Vec3::new(0.0, 0.0, 0.0)).length();
+        let inside = q.x.max(q.y).max(q.z).min(0.0);
+        outside + inside - self.radius
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        let extent = self.half_extent + Vec3::new(self.radius, self.radius, self.radius);
+        Aabb::new(self.center - extent, self.center + extent)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn distance_to_faces_and_edges() {
+        let rounded_box = RoundedBox {
+            center: Vec3::new(0.0, 0.0, 0.0),
+            half_extent: Vec3::new(1.0, 2.0, 1.0),
+            radius: 0.5,
+        };
+        assert!((rounded_box.distance(&Vec3::new(3.0, 0.0, 0.0)) - 1.5).abs() < 1e-12);
+        assert!((rounded_box.distance(&Vec3::new(0.0, 0.0, 0.0)) + 1.5).abs() < 1e-12);
+        // diagonally off the corner of the inner box
+        let corner = Vec3::new(2.0, 3.0, 1.0);
+        assert!((rounded_box.distance(&corner) - (2.0_f64.sqrt() - 0.5)).abs() < 1e-12);
+    }
+}
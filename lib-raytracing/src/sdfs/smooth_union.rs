@@ -0,0 +1,68 @@
+use crate::{aabb::Aabb, sdf::Sdf, vec3::Vec3};
+
+// union of two fields that blends them together where they come closer than smoothness,
+// like drops of liquid merging (polynomial smooth minimum)
+pub struct SmoothUnion {
+    pub a: Box<dyn Sdf>,
+    pub b: Box<dyn Sdf>,
+    pub smoothness: f64,
+}
+
+impl SmoothUnion {
+    pub fn new(a: impl Sdf + 'static, b: impl Sdf + 'static, smoothness: f64) -> SmoothUnion {
+        SmoothUnion {
+            a: Box::new(a),
+            b: Box::new(b),
+            smoothness,
+        }
+    }
+}
+
+impl Sdf for SmoothUnion {
+    fn distance(&self, point: &Vec3) -> f64 {
+        let a = self.a.distance(point);
+        let b = self.b.distance(point);
+        if self.smoothness <= 0.0 {
+            return a.min(b);
+        }
+        let h = (0.5 + 0.5 * (b - a) / self.smoothness).clamp(0.0, 1.0);
+        b + (a - b) * h - self.smoothness * h * (1.0 - h)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        // the blend never grows the surface by more than a quarter of the smoothness
+        Aabb::surrounding_box(&self.a.bounding_box(), &self.b.bounding_box())
+            .padded(0.25 * self.smoothness)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sdfs::sphere::Sphere;
+
+    fn blob(smoothness: f64) -> SmoothUnion {
+        SmoothUnion::new(
+            Sphere {
+                center: Vec3::new(-1.0, 0.0, 0.0),
+                radius: 0.8,
+            },
+            Sphere {
+                center: Vec3::new(1.0, 0.0, 0.0),
+                radius: 0.8,
+            },
+            smoothness,
+        )
+    }
+
+    #[test]
+    fn blends_between_the_spheres_only() {
+        let between = Vec3::new(0.0, 0.0, 0.0);
+        assert!((blob(0.0).distance(&between) - 0.2).abs() < 1e-12);
+        // with enough smoothness the gap between the two spheres is filled
+        assert!(blob(1.0).distance(&between) < 0.0);
+        // far away from the blend region the union is exact
+        let far = Vec3::new(-5.0, 0.0, 0.0);
+        assert!((blob(1.0).distance(&far) - blob(0.0).distance(&far)).abs() < 1e-12);
+    }
+}
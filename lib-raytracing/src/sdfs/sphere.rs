@@ -0,0 +1,17 @@
+use crate::{aabb::Aabb, sdf::Sdf, vec3::Vec3};
+
+pub struct Sphere {
+    pub center: Vec3,
+    pub radius: f64,
+}
+
+impl Sdf for Sphere {
+    fn distance(&self, point: &Vec3) -> f64 {
+        (*point - self.center).length() - self.radius
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        let radius = Vec3::new(self.radius, self.radius, self.radius);
+        Aabb::new(self.center - radius, self.center + radius)
+    }
+}
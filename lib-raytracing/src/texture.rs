@@ -0,0 +1,11 @@
+use crate::{hittable::HitRecord, vec3::Vec3};
+
+// color as a function of the surface coordinates (u, v) and the hit point
+pub trait Texture: Send + Sync {
+    fn value(&self, u: f64, v: f64, point: &Vec3) -> Vec3;
+
+    // what materials call, textures that need more of the hit than (u, v, point) override this
+    fn value_at(&self, record: &HitRecord) -> Vec3 {
+        self.value(record.u, record.v, &record.point)
+    }
+}
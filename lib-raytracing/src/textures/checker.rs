@@ -0,0 +1,33 @@
+use crate::{texture::Texture, vec3::Vec3};
+
+use super::solid_color::SolidColor;
+
+// 3D checker pattern in world space, so it does not stretch with the uv mapping
+pub struct Checker {
+    pub even: Box<dyn Texture>,
+    pub odd: Box<dyn Texture>,
+    pub scale: f64, // edge length of one cell
+}
+
+impl Checker {
+    pub fn new(even: Vec3, odd: Vec3, scale: f64) -> Checker {
+        Checker {
+            even: Box::new(SolidColor { color: even }),
+            odd: Box::new(SolidColor { color: odd }),
+            scale,
+        }
+    }
+}
+
+impl Texture for Checker {
+    fn value(&self, u: f64, v: f64, point: &Vec3) -> Vec3 {
+        let cell = (point.x / self.scale).floor() as i64
+            + (point.y / self.scale).floor() as i64
+            + (point.z / self.scale).floor() as i64;
+        if cell.rem_euclid(2) == 0 {
+            self.even.value(u, v, point)
+        } else {
+            self.odd.value(u, v, point)
+        }
+    }
+}
@@ -0,0 +1,156 @@
+use std::path::Path;
+
+use image::{DynamicImage, ImageResult};
+
+use crate::{texture::Texture, vec3::Vec3};
+
+pub struct ImageTexture {
+    width: usize,
+    height: usize,
+    pixels: Vec<Vec3>, // linear rgb, row major, the first row is the top of the image
+    wrap: (Wrap, Wrap), // along u and along v
+}
+
+// how uvs outside [0, 1] find their texel, the ones inside are left as they are
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Wrap {
+    #[default]
+    Repeat,
+    MirroredRepeat,
+    Clamp,
+}
+
+impl Wrap {
+    fn apply(self, t: f64) -> f64 {
+        if (0.0..=1.0).contains(&t) {
+            return t;
+        }
+        match self {
+            Wrap::Repeat => t.rem_euclid(1.0),
+            Wrap::MirroredRepeat => 1.0 - (t.rem_euclid(2.0) - 1.0).abs(),
+            Wrap::Clamp => t.clamp(0.0, 1.0),
+        }
+    }
+}
+
+impl ImageTexture {
+    pub fn new(width: usize, height: usize, pixels: Vec<Vec3>) -> ImageTexture {
+        assert_eq!(
+            pixels.len(),
+            width * height,
+            "image texture needs width * height pixels"
+        );
+        ImageTexture {
+            width,
+            height,
+            pixels,
+            wrap: (Wrap::default(), Wrap::default()),
+        }
+    }
+
+    pub fn with_wrap(mut self, wrap_u: Wrap, wrap_v: Wrap) -> ImageTexture {
+        self.wrap = (wrap_u, wrap_v);
+        self
+    }
+
+    // loads PNG, PPM or HDR files, 8 and 16 bit images are assumed to be sRGB encoded
+    pub fn load<P: AsRef<Path>>(path: P) -> ImageResult<ImageTexture> {
+        ImageTexture::load_encoded(path, true)
+    }
+
+    // loads images of numbers rather than colors, like roughness or metallic maps, which are
+    // stored linearly
+    pub fn load_data<P: AsRef<Path>>(path: P) -> ImageResult<ImageTexture> {
+        ImageTexture::load_encoded(path, false)
+    }
+
+    fn load_encoded<P: AsRef<Path>>(path: P, srgb: bool) -> ImageResult<ImageTexture> {
+        let image = image::open(path)?;
+        let linear = !srgb
+            || matches!(
+                image,
+                DynamicImage::ImageRgb32F(_) | DynamicImage::ImageRgba32F(_)
+            );
+        let image = image.into_rgb32f();
+        let (width, height) = image.dimensions();
+        let pixels = image
+            .pixels()
+            .map(|pixel| {
+                let color = Vec3::new(pixel[0] as f64, pixel[1] as f64, pixel[2] as f64);
+                if linear {
+                    color
+                } else {
+                    Vec3::new(
+                        srgb_to_linear(color.x),
+                        srgb_to_linear(color.y),
+                        srgb_to_linear(color.z),
+                    )
+                }
+            })
+            .collect();
+        Ok(ImageTexture::new(width as usize, height as usize, pixels))
+    }
+}
+
+pub(crate) fn srgb_to_linear(value: f64) -> f64 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+impl Texture for ImageTexture {
+    fn value(&self, u: f64, v: f64, _point: &Vec3) -> Vec3 {
+        if self.pixels.is_empty() {
+            // cyan makes missing textures easy to spot
+            return Vec3::new(0.0, 1.0, 1.0);
+        }
+        // flip v, image rows go top to bottom
+        let u = self.wrap.0.apply(u);
+        let v = 1.0 - self.wrap.1.apply(v);
+        let x = ((u * self.width as f64) as usize).min(self.width - 1);
+        let y = ((v * self.height as f64) as usize).min(self.height - 1);
+        self.pixels[y * self.width + x]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn v_zero_is_bottom_row() {
+        let top = Vec3::new(1.0, 0.0, 0.0);
+        let bottom = Vec3::new(0.0, 0.0, 1.0);
+        let texture = ImageTexture::new(1, 2, vec![top, bottom]);
+        let point = Vec3::new(0.0, 0.0, 0.0);
+        assert_eq!(texture.value(0.5, 0.0, &point), bottom);
+        assert_eq!(texture.value(0.5, 1.0, &point), top);
+    }
+    #[test]
+    fn uvs_outside_the_image_wrap() {
+        let texels: Vec<Vec3> = (0..4).map(|x| Vec3::new(x as f64, 0.0, 0.0)).collect();
+        let texture = ImageTexture::new(4, 1, texels);
+        let point = Vec3::new(0.0, 0.0, 0.0);
+        assert_eq!(
+            texture.value(1.25, 0.5, &point),
+            texture.value(0.25, 0.5, &point)
+        );
+        assert_eq!(texture.value(-0.75, 0.5, &point).x, 1.0);
+        let mirrored = texture.with_wrap(Wrap::MirroredRepeat, Wrap::Clamp);
+        assert_eq!(mirrored.value(1.2, 0.5, &point).x, 3.0);
+        assert_eq!(mirrored.value(-0.1, 0.5, &point).x, 0.0);
+        let clamped = mirrored.with_wrap(Wrap::Clamp, Wrap::Clamp);
+        assert_eq!(clamped.value(1.25, 0.5, &point).x, 3.0);
+    }
+    #[test]
+    fn load_ppm_as_linear() {
+        let path = std::env::temp_dir().join("lib-raytracing-image-texture-test.ppm");
+        std::fs::write(&path, b"P3\n2 1\n255\n255 255 255 0 0 0\n").unwrap();
+        let texture = ImageTexture::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let point = Vec3::new(0.0, 0.0, 0.0);
+        assert_eq!(texture.value(0.0, 0.5, &point), Vec3::new(1.0, 1.0, 1.0));
+        assert_eq!(texture.value(1.0, 0.5, &point), Vec3::new(0.0, 0.0, 0.0));
+    }
+}
@@ -0,0 +1,74 @@
+use crate::{texture::Texture, vec3::Vec3};
+
+use super::perlin::Perlin;
+
+const TURBULENCE_DEPTH: u32 = 7;
+
+// smooth gray perlin noise
+pub struct Noise {
+    pub perlin: Perlin,
+    pub scale: f64, // frequency of the noise in world space
+}
+
+// fractal sum of noise octaves, looks like camouflage or clouds
+pub struct Turbulence {
+    pub perlin: Perlin,
+    pub scale: f64,
+}
+
+// sine stripes along z, phase shifted by turbulence
+pub struct Marble {
+    pub perlin: Perlin,
+    pub scale: f64,
+}
+
+impl Noise {
+    pub fn new(scale: f64) -> Noise {
+        Noise {
+            perlin: Perlin::default(),
+            scale,
+        }
+    }
+}
+
+impl Turbulence {
+    pub fn new(scale: f64) -> Turbulence {
+        Turbulence {
+            perlin: Perlin::default(),
+            scale,
+        }
+    }
+}
+
+impl Marble {
+    pub fn new(scale: f64) -> Marble {
+        Marble {
+            perlin: Perlin::default(),
+            scale,
+        }
+    }
+}
+
+impl Texture for Noise {
+    fn value(&self, _u: f64, _v: f64, point: &Vec3) -> Vec3 {
+        // map [-1, 1] to [0, 1]
+        Vec3::new(1.0, 1.0, 1.0) * 0.5 * (1.0 + self.perlin.noise(&(self.scale * *point)))
+    }
+}
+
+impl Texture for Turbulence {
+    fn value(&self, _u: f64, _v: f64, point: &Vec3) -> Vec3 {
+        let value = self
+            .perlin
+            .turbulence(&(self.scale * *point), TURBULENCE_DEPTH)
+            .min(1.0);
+        Vec3::new(1.0, 1.0, 1.0) * value
+    }
+}
+
+impl Texture for Marble {
+    fn value(&self, _u: f64, _v: f64, point: &Vec3) -> Vec3 {
+        let phase = self.scale * point.z + 10.0 * self.perlin.turbulence(point, TURBULENCE_DEPTH);
+        Vec3::new(1.0, 1.0, 1.0) * 0.5 * (1.0 + phase.sin())
+    }
+}
@@ -0,0 +1,109 @@
+use rand::seq::SliceRandom;
+
+use crate::vec3::Vec3;
+
+const POINT_COUNT: usize = 256;
+
+// gradient noise: random unit vectors on a lattice, interpolated with a hermite cubic
+pub struct Perlin {
+    random_vectors: Vec<Vec3>,
+    permutation_x: Vec<usize>,
+    permutation_y: Vec<usize>,
+    permutation_z: Vec<usize>,
+}
+
+impl Default for Perlin {
+    fn default() -> Self {
+        Perlin {
+            random_vectors: (0..POINT_COUNT)
+                .map(|_| Vec3::unit_vector(&Vec3::random_range(-1.0, 1.0)))
+                .collect(),
+            permutation_x: Self::generate_permutation(),
+            permutation_y: Self::generate_permutation(),
+            permutation_z: Self::generate_permutation(),
+        }
+    }
+}
+
+impl Perlin {
+    fn generate_permutation() -> Vec<usize> {
+        let mut permutation: Vec<usize> = (0..POINT_COUNT).collect();
+        permutation.shuffle(&mut rand::thread_rng());
+        permutation
+    }
+
+    // noise in [-1, 1]
+    pub fn noise(&self, point: &Vec3) -> f64 {
+        let u = point.x - point.x.floor();
+        let v = point.y - point.y.floor();
+        let w = point.z - point.z.floor();
+        let i = point.x.floor() as i64;
+        let j = point.y.floor() as i64;
+        let k = point.z.floor() as i64;
+
+        let mut corners = [[[Vec3::default(); 2]; 2]; 2];
+        for (di, plane) in corners.iter_mut().enumerate() {
+            for (dj, row) in plane.iter_mut().enumerate() {
+                for (dk, corner) in row.iter_mut().enumerate() {
+                    let index = self.permutation_x[((i + di as i64) & 255) as usize]
+                        ^ self.permutation_y[((j + dj as i64) & 255) as usize]
+                        ^ self.permutation_z[((k + dk as i64) & 255) as usize];
+                    *corner = self.random_vectors[index];
+                }
+            }
+        }
+        Self::trilinear_interpolation(&corners, u, v, w)
+    }
+
+    fn trilinear_interpolation(corners: &[[[Vec3; 2]; 2]; 2], u: f64, v: f64, w: f64) -> f64 {
+        let uu = u * u * (3.0 - 2.0 * u);
+        let vv = v * v * (3.0 - 2.0 * v);
+        let ww = w * w * (3.0 - 2.0 * w);
+
+        let mut accumulated = 0.0;
+        for (i, plane) in corners.iter().enumerate() {
+            for (j, row) in plane.iter().enumerate() {
+                for (k, corner) in row.iter().enumerate() {
+                    let (fi, fj, fk) = (i as f64, j as f64, k as f64);
+                    let weight = Vec3::new(u - fi, v - fj, w - fk);
+                    accumulated += (fi * uu + (1.0 - fi) * (1.0 - uu))
+                        * (fj * vv + (1.0 - fj) * (1.0 - vv))
+                        * (fk * ww + (1.0 - fk) * (1.0 - ww))
+                        * Vec3::dot(corner, &weight);
+                }
+            }
+        }
+        accumulated
+    }
+
+    // sum of octaves with halving weight and doubling frequency, always positive
+    pub fn turbulence(&self, point: &Vec3, depth: u32) -> f64 {
+        let mut accumulated = 0.0;
+        let mut temp_point = *point;
+        let mut weight = 1.0;
+        for _ in 0..depth {
+            accumulated += weight * self.noise(&temp_point);
+            weight *= 0.5;
+            temp_point *= 2.0;
+        }
+        accumulated.abs()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn noise_is_zero_on_lattice() {
+        let perlin = Perlin::default();
+        assert_eq!(perlin.noise(&Vec3::new(3.0, -2.0, 7.0)), 0.0);
+    }
+    #[test]
+    fn noise_is_bounded() {
+        let perlin = Perlin::default();
+        for _ in 0..1000 {
+            let value = perlin.noise(&Vec3::random_range(-100.0, 100.0));
+            assert!((-1.0..=1.0).contains(&value));
+        }
+    }
+}
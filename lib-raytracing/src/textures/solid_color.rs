@@ -0,0 +1,11 @@
+use crate::{texture::Texture, vec3::Vec3};
+
+pub struct SolidColor {
+    pub color: Vec3,
+}
+
+impl Texture for SolidColor {
+    fn value(&self, _u: f64, _v: f64, _point: &Vec3) -> Vec3 {
+        self.color
+    }
+}
@@ -0,0 +1,16 @@
+use crate::{hittable::HitRecord, texture::Texture, vec3::Vec3};
+
+// uses the interpolated vertex colors of meshes (e.g. scanned PLY data) as texture
+pub struct VertexColor {
+    pub fallback: Vec3, // for surfaces without vertex colors
+}
+
+impl Texture for VertexColor {
+    fn value(&self, _u: f64, _v: f64, _point: &Vec3) -> Vec3 {
+        self.fallback
+    }
+
+    fn value_at(&self, record: &HitRecord) -> Vec3 {
+        record.vertex_color.unwrap_or(self.fallback)
+    }
+}
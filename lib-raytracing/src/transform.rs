@@ -0,0 +1,152 @@
+use crate::{aabb::Aabb, mat4::Mat4, ray::Ray, vec3::Vec3};
+
+// an invertible affine transformation from object to world space, the inverse is kept
+// alongside so rays can be moved into object space without inverting per hit.
+// Built up step by step: Transform::default().scale(..).rotate(..).translate(..)
+// scales first, then rotates and translates last
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    matrix: Mat4,
+    inverse: Mat4,
+}
+
+impl Transform {
+    // None for singular matrices, e.g. scaling an axis by zero
+    pub fn new(matrix: Mat4) -> Option<Transform> {
+        Some(Transform {
+            matrix,
+            inverse: matrix.inverse()?,
+        })
+    }
+
+    pub fn translate(self, offset: Vec3) -> Transform {
+        self.then(&Transform {
+            matrix: Mat4::translation(offset),
+            inverse: Mat4::translation(-1.0 * offset),
+        })
+    }
+
+    pub fn rotate(self, axis: Vec3, angle_deg: f64) -> Transform {
+        let rotation = Mat4::rotation(axis, angle_deg);
+        self.then(&Transform {
+            matrix: rotation,
+            inverse: rotation.transpose(),
+        })
+    }
+
+    pub fn scale(self, factors: Vec3) -> Transform {
+        assert!(
+            factors.x != 0.0 && factors.y != 0.0 && factors.z != 0.0,
+            "scaling by zero can not be inverted"
+        );
+        self.then(&Transform {
+            matrix: Mat4::scaling(factors),
+            inverse: Mat4::scaling(Vec3::new(1.0 / factors.x, 1.0 / factors.y, 1.0 / factors.z)),
+        })
+    }
+
+    // applies this transform first and the other one afterwards
+    pub fn then(&self, other: &Transform) -> Transform {
+        Transform {
+            matrix: other.matrix * self.matrix,
+            inverse: self.inverse * other.inverse,
+        }
+    }
+
+    pub fn inverse(&self) -> Transform {
+        Transform {
+            matrix: self.inverse,
+            inverse: self.matrix,
+        }
+    }
+
+    pub fn matrix(&self) -> &Mat4 {
+        &self.matrix
+    }
+
+    pub fn point(&self, point: &Vec3) -> Vec3 {
+        self.matrix.transform_point(point)
+    }
+
+    pub fn direction(&self, direction: &Vec3) -> Vec3 {
+        self.matrix.transform_direction(direction)
+    }
+
+    pub fn normal(&self, normal: &Vec3) -> Vec3 {
+        self.matrix.transform_normal(normal)
+    }
+
+    // the direction is not normalized, so distances along the ray stay the same in both spaces
+    pub fn ray_to_object(&self, ray: &Ray) -> Ray {
+        Ray::with_time(
+            self.inverse.transform_point(&ray.origin),
+            self.inverse.transform_direction(&ray.direction),
+            ray.time,
+        )
+    }
+
+    // box around the transformed corners of an object space box
+    pub fn bounding_box(&self, object_box: &Aabb) -> Aabb {
+        let corners: Vec<Vec3> = (0..8)
+            .map(|corner| {
+                let pick = |axis: usize| {
+                    if corner & (1 << axis) == 0 {
+                        object_box.minimum[axis]
+                    } else {
+                        object_box.maximum[axis]
+                    }
+                };
+                self.point(&Vec3::new(pick(0), pick(1), pick(2)))
+            })
+            .collect();
+        Aabb::from_points(&corners)
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Transform {
+            matrix: Mat4::IDENTITY,
+            inverse: Mat4::IDENTITY,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn steps_apply_in_order() {
+        let transform = Transform::default()
+            .scale(Vec3::new(2.0, 2.0, 2.0))
+            .rotate(Vec3::new(0.0, 1.0, 0.0), 90.0)
+            .translate(Vec3::new(0.0, 1.0, 0.0));
+        let point = transform.point(&Vec3::new(1.0, 0.0, 0.0));
+        assert!((point - Vec3::new(0.0, 1.0, -2.0)).length() < 1e-9);
+
+        let back = transform.inverse().point(&point);
+        assert!((back - Vec3::new(1.0, 0.0, 0.0)).length() < 1e-9);
+    }
+
+    #[test]
+    fn matches_general_inverse() {
+        let transform = Transform::default()
+            .rotate(Vec3::new(1.0, 2.0, 3.0), 40.0)
+            .scale(Vec3::new(1.0, 3.0, 0.5))
+            .translate(Vec3::new(-1.0, 0.0, 2.0));
+        let general = Transform::new(*transform.matrix()).unwrap();
+        let point = Vec3::new(0.5, -0.25, 2.0);
+        let difference = general.inverse().point(&point) - transform.inverse().point(&point);
+        assert!(difference.length() < 1e-9);
+    }
+
+    #[test]
+    fn bounding_box_of_rotation() {
+        let transform = Transform::default().rotate(Vec3::new(0.0, 0.0, 1.0), 45.0);
+        let unit = Aabb::new(Vec3::new(-1.0, -1.0, -1.0), Vec3::new(1.0, 1.0, 1.0));
+        let rotated = transform.bounding_box(&unit);
+        assert!((rotated.maximum.x - 2.0_f64.sqrt()).abs() < 1e-9);
+        assert!((rotated.maximum.z - 1.0).abs() < 1e-9);
+    }
+}
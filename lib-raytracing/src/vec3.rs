@@ -34,6 +34,11 @@ impl Vec3 {
         self.length_squared().sqrt()
     }
 
+    // perceived brightness when the vector is used as a linear rgb color
+    pub fn luminance(&self) -> f64 {
+        0.2126 * self.x + 0.7152 * self.y + 0.0722 * self.z
+    }
+
     pub fn dot(vector_u: &Vec3, vector_v: &Vec3) -> f64 {
         vector_u.x * vector_v.x + vector_u.y * vector_v.y + vector_u.z * vector_v.z
     }
@@ -46,10 +51,38 @@ impl Vec3 {
         }
     }
 
+    pub fn component_min(vector_u: &Vec3, vector_v: &Vec3) -> Vec3 {
+        Vec3 {
+            x: vector_u.x.min(vector_v.x),
+            y: vector_u.y.min(vector_v.y),
+            z: vector_u.z.min(vector_v.z),
+        }
+    }
+
+    pub fn component_max(vector_u: &Vec3, vector_v: &Vec3) -> Vec3 {
+        Vec3 {
+            x: vector_u.x.max(vector_v.x),
+            y: vector_u.y.max(vector_v.y),
+            z: vector_u.z.max(vector_v.z),
+        }
+    }
+
     pub fn unit_vector(vector: &Vec3) -> Vec3 {
         *vector / vector.length()
     }
 
+    // two unit vectors perpendicular to the unit vector normal, with u x v = normal
+    pub fn orthonormal_basis(normal: &Vec3) -> (Vec3, Vec3) {
+        let helper = if normal.x.abs() > 0.9 {
+            Vec3::new(0.0, 1.0, 0.0)
+        } else {
+            Vec3::new(1.0, 0.0, 0.0)
+        };
+        let v = Self::unit_vector(&Self::cross(normal, &helper));
+        let u = Self::cross(&v, normal);
+        (u, v)
+    }
+
     pub fn random() -> Vec3 {
         Self::new(random(), random(), random())
     }
@@ -94,7 +127,7 @@ impl Vec3 {
     }
 
     pub fn reflect(vector: &Vec3, surface_normal: &Vec3) -> Vec3 {
-        *vector - 2.0 * Self::dot(&vector, &surface_normal) * *surface_normal
+        *vector - 2.0 * Self::dot(vector, surface_normal) * *surface_normal
     }
 
     pub fn refract(ray_direction: &Vec3, surface_normal: &Vec3, refraction_ratio: f64) -> Vec3 {
@@ -138,6 +171,19 @@ impl Default for Vec3 {
     }
 }
 
+impl ops::Index<usize> for Vec3 {
+    type Output = f64;
+
+    fn index(&self, axis: usize) -> &Self::Output {
+        match axis {
+            0 => &self.x,
+            1 => &self.y,
+            2 => &self.z,
+            _ => panic!("Vec3 axis {} out of range", axis),
+        }
+    }
+}
+
 impl ops::AddAssign for Vec3 {
     fn add_assign(&mut self, other: Self) {
         self.x += other.x;
@@ -280,10 +326,36 @@ mod tests {
             z: 4.2,
         };
         let target = Vec3 {
-            x: (4.2 as f64).powf(2.0),
-            y: (4.2 as f64).powf(2.0),
-            z: (4.2 as f64).powf(2.0),
+            x: 4.2_f64.powf(2.0),
+            y: 4.2_f64.powf(2.0),
+            z: 4.2_f64.powf(2.0),
         };
         assert_eq!(subject.pow(2.0), target);
     }
+    #[test]
+    fn index_matches_fields() {
+        let subject = Vec3::new(1.0, 2.0, 3.0);
+        assert_eq!(
+            (subject[0], subject[1], subject[2]),
+            (subject.x, subject.y, subject.z)
+        );
+    }
+    #[test]
+    fn component_min_max() {
+        let a = Vec3::new(1.0, 5.0, -2.0);
+        let b = Vec3::new(3.0, -1.0, 0.0);
+        assert_eq!(Vec3::component_min(&a, &b), Vec3::new(1.0, -1.0, -2.0));
+        assert_eq!(Vec3::component_max(&a, &b), Vec3::new(3.0, 5.0, 0.0));
+    }
+    #[test]
+    fn orthonormal_basis_is_right_handed() {
+        for normal in [
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::unit_vector(&Vec3::new(0.2, -1.0, 3.0)),
+        ] {
+            let (u, v) = Vec3::orthonormal_basis(&normal);
+            assert!((u.length() - 1.0).abs() < 1e-12 && Vec3::dot(&u, &normal).abs() < 1e-12);
+            assert!((Vec3::cross(&u, &v) - normal).length() < 1e-12);
+        }
+    }
 }
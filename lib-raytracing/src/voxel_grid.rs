@@ -0,0 +1,96 @@
+use crate::vec3::Vec3;
+
+// dense grid of densities sampled at the voxel centers, looked up with trilinear
+// interpolation in grid coordinates where the whole grid spans [0, 1]³
+pub struct VoxelGrid {
+    resolution: [usize; 3],
+    values: Vec<f64>, // x runs fastest, then y, then z
+    maximum: f64,
+}
+
+impl VoxelGrid {
+    pub fn new(resolution: [usize; 3], values: Vec<f64>) -> VoxelGrid {
+        assert!(
+            resolution.iter().all(|count| *count > 0),
+            "voxel grids need at least one voxel along every axis"
+        );
+        assert_eq!(
+            values.len(),
+            resolution.iter().product::<usize>(),
+            "a voxel grid needs one value per voxel"
+        );
+        let maximum = values
+            .iter()
+            .fold(0.0, |maximum: f64, value| maximum.max(*value));
+        VoxelGrid {
+            resolution,
+            values,
+            maximum,
+        }
+    }
+
+    pub fn resolution(&self) -> [usize; 3] {
+        self.resolution
+    }
+
+    // upper bound of value(), the majorant for delta tracking
+    pub fn maximum(&self) -> f64 {
+        self.maximum
+    }
+
+    fn voxel(&self, x: usize, y: usize, z: usize) -> f64 {
+        let [nx, ny, _] = self.resolution;
+        self.values[(z * ny + y) * nx + x]
+    }
+
+    // 0 outside the grid, points between the outermost voxel centers and the border keep
+    // the value of the border voxels
+    pub fn value(&self, point: &Vec3) -> f64 {
+        if (0..3).any(|axis| !(0.0..=1.0).contains(&point[axis])) {
+            return 0.0;
+        }
+        // the lower voxel and the weight of the upper one along every axis
+        let corner = |axis: usize| {
+            let count = self.resolution[axis];
+            let position = (point[axis] * count as f64 - 0.5).clamp(0.0, (count - 1) as f64);
+            let lower = (position as usize).min(count.saturating_sub(2));
+            (lower, (lower + 1).min(count - 1), position - lower as f64)
+        };
+        let (x0, x1, fx) = corner(0);
+        let (y0, y1, fy) = corner(1);
+        let (z0, z1, fz) = corner(2);
+        let lerp = |a: f64, b: f64, t: f64| a + (b - a) * t;
+        let along_x = |y: usize, z: usize| lerp(self.voxel(x0, y, z), self.voxel(x1, y, z), fx);
+        lerp(
+            lerp(along_x(y0, z0), along_x(y1, z0), fy),
+            lerp(along_x(y0, z1), along_x(y1, z1), fy),
+            fz,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interpolates_between_voxel_centers() {
+        let grid = VoxelGrid::new([2, 1, 1], vec![1.0, 3.0]);
+        assert_eq!(grid.maximum(), 3.0);
+        assert!((grid.value(&Vec3::new(0.25, 0.5, 0.5)) - 1.0).abs() < 1e-12);
+        assert!((grid.value(&Vec3::new(0.5, 0.5, 0.5)) - 2.0).abs() < 1e-12);
+        assert!((grid.value(&Vec3::new(0.75, 0.1, 0.9)) - 3.0).abs() < 1e-12);
+        // clamped towards the border, nothing outside
+        assert!((grid.value(&Vec3::new(1.0, 0.5, 0.5)) - 3.0).abs() < 1e-12);
+        assert_eq!(grid.value(&Vec3::new(1.1, 0.5, 0.5)), 0.0);
+    }
+
+    #[test]
+    fn single_voxel_is_constant() {
+        let grid = VoxelGrid::new([1, 1, 1], vec![0.5]);
+        for _ in 0..10 {
+            let point = Vec3::random();
+            assert!((grid.value(&point) - 0.5).abs() < 1e-12);
+        }
+    }
+}
@@ -1,20 +1,42 @@
-use std::time::Instant;
+use std::{sync::Arc, time::Instant};
 
 use lib_raytracing::{
+    aabb::Aabb,
+    background::Background,
+    backgrounds::{environment_map::EnvironmentMap, gradient::Gradient, solid_color::SolidColor},
+    bvh::Bvh,
     camera::Camera,
     hittable::HittableList,
+    lights::{directional_light::DirectionalLight, point_light::PointLight, spot_light::SpotLight},
+    loaders::voxel_grid::load_voxel_grid,
     material::Material,
-    materials::{dielectric::Dielectric, lambertian::Lambertian, metal::Metal},
-    objects::sphere::Sphere,
+    materials::{
+        dielectric::Dielectric, diffuse_light::DiffuseLight, hair::Hair, lambertian::Lambertian,
+        metal::Metal, principled::Principled, rough_conductor::RoughConductor,
+        rough_dielectric::RoughDielectric,
+    },
+    objects::{
+        constant_medium::ConstantMedium,
+        cuboid::Cuboid,
+        curve::{BezierCurve, CurveShape, Curves},
+        heightfield::Heightfield,
+        heterogeneous_medium::HeterogeneousMedium,
+        moving_sphere::MovingSphere,
+        plane::Plane,
+        quad::Quad,
+        sphere::Sphere,
+        transformed::Transformed,
+    },
     render_scene,
+    scene::Scene,
+    textures::{checker::Checker, noise::Marble},
+    transform::Transform,
     util::{self, clamp},
     vec3::Vec3,
 };
-use log::info;
+use log::{error, info};
 use pixels::{Pixels, SurfaceTexture};
-use rayon::prelude::{
-    IndexedParallelIterator, IntoParallelIterator, IntoParallelRefIterator, ParallelIterator,
-};
+use rayon::prelude::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
 use winit::{
     dpi::LogicalSize,
     event::{Event, VirtualKeyCode},
@@ -22,25 +44,19 @@ use winit::{
     window::WindowBuilder,
 };
 use winit_input_helper::WinitInputHelper;
-fn random_scene<'a>() -> HittableList {
+fn random_scene() -> HittableList {
     let mut world: HittableList = Default::default();
 
-    let ground_material = Lambertian {
-        color: Vec3 {
-            x: 0.5,
-            y: 0.5,
-            z: 0.5,
-        },
-    };
-    world.add(Sphere {
-        center: Vec3 {
-            x: 0.0,
-            y: -1000.0,
-            z: 0.0,
-        },
-        material: Box::new(ground_material),
-        radius: 1000.0,
-    });
+    let ground_material = Lambertian::textured(Checker::new(
+        Vec3::new(0.2, 0.3, 0.1),
+        Vec3::new(0.9, 0.9, 0.9),
+        1.0,
+    ));
+    world.add(Plane::new(
+        Vec3::new(0.0, 0.0, 0.0),
+        Vec3::new(0.0, 1.0, 0.0),
+        Box::new(ground_material),
+    ));
 
     for a in -11..11 {
         for b in -11..11 {
@@ -51,19 +67,29 @@ fn random_scene<'a>() -> HittableList {
                 (b as f64) + 0.9 * util::random(),
             );
             if (center - Vec3::new(4.0, 0.2, 0.0)).length() > 0.9 {
-                let material: Box<dyn Material> = if choose_mat < 0.8 {
-                    Box::new(Lambertian {
-                        color: Vec3::random() * Vec3::random(),
-                    })
-                } else if choose_mat < 0.95 {
-                    Box::new(Metal {
-                        color: Vec3::random_range(0.5, 1.0),
-                        fuzz: util::random_range(0.0, 0.5),
-                    })
-                } else {
+                if choose_mat < 0.8 {
+                    // diffuse spheres bounce up while the shutter is open
+                    world.add(MovingSphere::new(
+                        center,
+                        center + Vec3::new(0.0, util::random_range(0.0, 0.5), 0.0),
+                        0.0,
+                        1.0,
+                        0.2,
+                        Box::new(Lambertian::new(Vec3::random() * Vec3::random())),
+                    ));
+                    continue;
+                }
+                let material: Box<dyn Material> = if choose_mat < 0.95 {
+                    Box::new(RoughConductor::new(
+                        Vec3::random_range(0.5, 1.0),
+                        util::random_range(0.0, 0.7),
+                    ))
+                } else if choose_mat < 0.975 {
                     Box::new(Dielectric {
                         index_of_refraction: 1.5,
                     })
+                } else {
+                    Box::new(RoughDielectric::new(1.5, util::random_range(0.1, 0.5)))
                 };
                 world.add(Sphere {
                     center,
@@ -90,13 +116,7 @@ fn random_scene<'a>() -> HittableList {
             y: 1.0,
             z: 0.0,
         },
-        material: Box::new(Lambertian {
-            color: Vec3 {
-                x: 0.4,
-                y: 0.2,
-                z: 0.1,
-            },
-        }),
+        material: Box::new(Lambertian::textured(Marble::new(4.0))),
         radius: 1.0,
     });
     world.add(Sphere {
@@ -105,19 +125,178 @@ fn random_scene<'a>() -> HittableList {
             y: 1.0,
             z: 0.0,
         },
-        material: Box::new(Metal {
-            color: Vec3 {
+        material: Box::new(Metal::new(
+            Vec3 {
                 x: 0.7,
                 y: 0.6,
                 z: 0.5,
             },
-            fuzz: 0.0,
-        }),
+            0.0,
+        )),
         radius: 1.0,
     });
     world
 }
 
+// the classic 555 units wide cornell box with two rotated boxes, made of smoke if asked
+// brown fur growing out of a ball, every hair slightly bent by gravity
+fn fur_ball(center: Vec3, radius: f64) -> Curves {
+    let hairs = (0..20000)
+        .map(|_| {
+            let root_normal = Vec3::random_unit_vector();
+            let root = center + radius * root_normal;
+            let length = 0.25 * radius * (0.7 + 0.3 * util::random());
+            let droop = Vec3::new(0.0, -0.3 * length, 0.0);
+            let control_points = [0.0, 1.0, 2.0, 3.0].map(|step: f64| {
+                root + root_normal * (length * step / 3.0) + droop * (step / 3.0).powi(2)
+            });
+            BezierCurve::new(control_points, [0.004, 0.001], CurveShape::Cylinder)
+        })
+        .collect();
+    Curves::new(hairs, Box::new(Hair::from_melanin(1.3, 0.2, 0.3, 0.3)))
+}
+
+// a patch of grass blades standing on the ground, each leaning a little and facing a random way
+fn grass(corner: Vec3, size: f64) -> Curves {
+    let blades = (0..5000)
+        .map(|_| {
+            let root = corner + Vec3::new(size * util::random(), 0.0, size * util::random());
+            let height = 0.15 + 0.1 * util::random();
+            let lean = Vec3::new(0.0, 0.0, 0.1 * (util::random() - 0.5));
+            let control_points = [0.0, 1.0, 2.0, 3.0].map(|step: f64| {
+                root + Vec3::new(0.0, height * step / 3.0, 0.0) + lean * (step / 3.0).powi(2)
+            });
+            let facing = Vec3::new(util::random() - 0.5, 0.0, util::random() - 0.5);
+            let normal = if facing.near_zero() {
+                Vec3::new(1.0, 0.0, 0.0)
+            } else {
+                facing
+            };
+            BezierCurve::new(
+                control_points,
+                [0.02, 0.002],
+                CurveShape::Ribbon {
+                    normals: [normal, normal],
+                },
+            )
+        })
+        .collect();
+    Curves::new(blades, Box::new(Lambertian::new(Vec3::new(0.2, 0.5, 0.1))))
+}
+
+fn cornell_box(smoke: bool) -> Scene {
+    let mut world: HittableList = Default::default();
+    let red = || Box::new(Lambertian::new(Vec3::new(0.65, 0.05, 0.05)));
+    let white = || Box::new(Lambertian::new(Vec3::new(0.73, 0.73, 0.73)));
+    let green = || Box::new(Lambertian::new(Vec3::new(0.12, 0.45, 0.15)));
+
+    let size = 555.0;
+    let x = Vec3::new(size, 0.0, 0.0);
+    let y = Vec3::new(0.0, size, 0.0);
+    let z = Vec3::new(0.0, 0.0, size);
+    let origin = Vec3::new(0.0, 0.0, 0.0);
+    // the walls face into the box
+    world.add(Quad::new(x, z, y, green())); // left, seen from the camera
+    world.add(Quad::new(origin, y, z, red()));
+    world.add(Quad::new(origin, z, x, white())); // floor
+    world.add(Quad::new(y, x, z, white())); // ceiling
+    world.add(Quad::new(z, y, x, white())); // back
+
+    // the lamp is sampled directly as well
+    let lamp = Arc::new(Quad::new(
+        Vec3::new(343.0, 554.0, 332.0),
+        Vec3::new(-130.0, 0.0, 0.0),
+        Vec3::new(0.0, 0.0, -105.0),
+        Box::new(DiffuseLight::new(Vec3::new(15.0, 15.0, 15.0))),
+    ));
+    world.add(lamp.clone());
+
+    let tall_box = Transformed::new(
+        Cuboid::new(origin, Vec3::new(165.0, 330.0, 165.0), white()),
+        Transform::default()
+            .rotate(Vec3::new(0.0, 1.0, 0.0), 15.0)
+            .translate(Vec3::new(265.0, 0.0, 295.0)),
+    );
+    let short_box = Transformed::new(
+        Cuboid::new(origin, Vec3::new(165.0, 165.0, 165.0), white()),
+        Transform::default()
+            .rotate(Vec3::new(0.0, 1.0, 0.0), -18.0)
+            .translate(Vec3::new(130.0, 0.0, 65.0)),
+    );
+    if smoke {
+        world.add(ConstantMedium::new(
+            tall_box,
+            0.01,
+            Vec3::new(0.0, 0.0, 0.0),
+        ));
+        world.add(ConstantMedium::new(
+            short_box,
+            0.01,
+            Vec3::new(1.0, 1.0, 1.0),
+        ));
+    } else {
+        world.add(tall_box);
+        world.add(short_box);
+    }
+    Scene {
+        world: Box::new(Bvh::new(world)),
+        background: Box::new(SolidColor {
+            color: Vec3::new(0.0, 0.0, 0.0),
+        }),
+        lights: vec![lamp],
+    }
+}
+
+// spheres on a gray floor lit only by punctual lights: a warm key spot from the front left,
+// a dim cool fill from the right and a sun rim light from behind. Rays never hit these
+// lights, so only materials that can be evaluated (see Material::eval) are lit by them
+fn studio() -> Scene {
+    let mut world: HittableList = Default::default();
+    world.add(Sphere {
+        center: Vec3::new(0.0, -1000.0, 0.0),
+        radius: 1000.0,
+        material: Box::new(Lambertian::new(Vec3::new(0.5, 0.5, 0.5))),
+    });
+    world.add(Sphere {
+        center: Vec3::new(-4.0, 1.0, 0.0),
+        radius: 1.0,
+        material: Box::new(Lambertian::new(Vec3::new(0.7, 0.1, 0.1))),
+    });
+    world.add(Sphere {
+        center: Vec3::new(0.0, 1.0, 0.0),
+        radius: 1.0,
+        material: Box::new(Principled::new(Vec3::new(0.8, 0.8, 0.8), 0.0, 0.3)),
+    });
+    world.add(Sphere {
+        center: Vec3::new(4.0, 1.0, 0.0),
+        radius: 1.0,
+        material: Box::new(RoughConductor::new(Vec3::new(0.9, 0.7, 0.3), 0.2)),
+    });
+    Scene {
+        world: Box::new(Bvh::new(world)),
+        background: Box::new(SolidColor {
+            color: Vec3::new(0.01, 0.01, 0.02),
+        }),
+        lights: vec![
+            Arc::new(SpotLight::new(
+                Vec3::new(8.0, 8.0, 8.0),
+                Vec3::new(0.0, 1.0, 0.0),
+                Vec3::new(300.0, 270.0, 220.0),
+                30.0,
+                20.0,
+            )),
+            Arc::new(PointLight::new(
+                Vec3::new(6.0, 3.0, -6.0),
+                Vec3::new(20.0, 25.0, 35.0),
+            )),
+            Arc::new(DirectionalLight::new(
+                Vec3::new(1.0, -0.5, 1.0),
+                Vec3::new(1.5, 1.5, 1.5),
+            )),
+        ],
+    }
+}
+
 fn main() {
     // init logging
     dotenvy::dotenv().expect("could not load .env file");
@@ -141,18 +320,79 @@ fn main() {
     let dist_to_focus = 10.0;
     let aperture = 0.1;
 
-    let camera = Camera::new(
-        lookfrom,
-        lookat,
-        view_up,
-        FIELD_OF_VIEW,
-        ASPECT_RATIO,
-        aperture,
-        dist_to_focus,
-    );
+    // SCENE=cornell or SCENE=cornell_smoke renders the cornell box, SCENE=studio spheres under
+    // punctual lights instead of the random spheres
+    let scene = std::env::var("SCENE").unwrap_or_default();
+    let cornell = scene.starts_with("cornell");
+    let camera = if cornell {
+        Camera::new(
+            Vec3::new(278.0, 278.0, -800.0),
+            Vec3::new(278.0, 278.0, 0.0),
+            view_up,
+            40.0,
+            ASPECT_RATIO,
+            0.0,
+            800.0,
+        )
+    } else {
+        Camera::new(
+            lookfrom,
+            lookat,
+            view_up,
+            FIELD_OF_VIEW,
+            ASPECT_RATIO,
+            aperture,
+            dist_to_focus,
+        )
+        .with_shutter(0.0, 1.0)
+    };
 
-    // world
-    let world = random_scene();
+    // world, lit by an equirectangular .hdr if ENVIRONMENT_MAP points to one
+    let world = if cornell {
+        cornell_box(scene == "cornell_smoke")
+    } else if scene == "studio" {
+        studio()
+    } else {
+        let background: Box<dyn Background> = match std::env::var("ENVIRONMENT_MAP") {
+            Ok(path) => {
+                Box::new(EnvironmentMap::load(path).expect("could not load environment map"))
+            }
+            Err(_) => Box::new(Gradient::default()),
+        };
+        let mut world = random_scene();
+        // a cloud above the spheres if VOXEL_GRID points to a voxel grid file
+        if let Ok(path) = std::env::var("VOXEL_GRID") {
+            world.add(HeterogeneousMedium::new(
+                load_voxel_grid(path).expect("could not load voxel grid"),
+                Aabb::new(Vec3::new(-4.0, 2.0, -3.0), Vec3::new(4.0, 5.0, 1.0)),
+                2.0,
+                Vec3::new(0.9, 0.9, 0.9),
+                0.6,
+            ));
+        }
+        // mountains behind the spheres if HEIGHTMAP points to a grayscale image
+        if let Ok(path) = std::env::var("HEIGHTMAP") {
+            world.add(
+                Heightfield::load(
+                    path,
+                    Vec3::new(-40.0, 0.0, -40.0),
+                    Vec3::new(80.0, 8.0, 30.0),
+                    Box::new(Lambertian::new(Vec3::new(0.4, 0.35, 0.3))),
+                )
+                .expect("could not load heightmap"),
+            );
+        }
+        // a fur ball and a patch of grass in front of the big spheres if STRANDS is set
+        if std::env::var("STRANDS").is_ok() {
+            world.add(fur_ball(Vec3::new(6.0, 0.5, 1.5), 0.4));
+            world.add(grass(Vec3::new(5.0, 0.0, -1.5), 2.0));
+        }
+        Scene {
+            world: Box::new(Bvh::new(world)),
+            background,
+            lights: vec![],
+        }
+    };
 
     let event_loop = EventLoop::new();
     let mut input = WinitInputHelper::new();
@@ -174,10 +414,11 @@ fn main() {
 
     let mut pixels = vec![Vec3::new(0.0, 0.0, 0.0); (IMAGE_WIDTH * IMAGE_HEIGHT) as usize];
     let mut calculated_samples = 0.0;
-    let mut start = Instant::now();
+    let start = Instant::now();
     event_loop.run(move |event, _, control_flow: &mut ControlFlow| {
         if let Event::RedrawRequested(_) = event {
             if let Err(err) = pixel_frame_buffer.render() {
+                error!("pixels.render() failed: {}", err);
                 *control_flow = ControlFlow::Exit;
                 return;
             }
@@ -194,6 +435,7 @@ fn main() {
             // Resize the window
             if let Some(size) = input.window_resized() {
                 if let Err(err) = pixel_frame_buffer.resize_surface(size.width, size.height) {
+                    error!("pixels.resize_surface() failed: {}", err);
                     *control_flow = ControlFlow::Exit;
                     return;
                 }